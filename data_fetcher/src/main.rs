@@ -1,21 +1,37 @@
 
 // Crates used: ureq (HTTP), serde (typed JSON), std (time, file I/O)
 use serde::Deserialize;
-use std::fs::OpenOptions;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
 use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 // ========================= Shared (trait, time, IO) =========================
 
 // Network time (UTC)
 const TIME_API: &str = "https://timeapi.io/api/Time/current/zone?timeZone=UTC";
 
-//There is only 3 we cases we care about when working with http api calls
+// One JSON line per cycle with every asset's price
+const CYCLE_LOG_FILE: &str = "prices.ndjson";
+
+// Normal wait between cycles, and the longest we back off to when everything is failing
+const BASE_INTERVAL: Duration = Duration::from_secs(10);
+const MAX_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+// Per-asset txt files are rotated once they pass this size (`--max-file-mb`, see `append_line`)
+const DEFAULT_MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+//The 4 cases we care about when working with http api calls: a price, a non-200 status,
+//a 200 whose data we couldn't parse, and a request that never got an answer
 #[derive(Debug)]
 pub enum ApiResult {
     Success(f64),
-    ApiError(String),
+    HttpStatus { code: u16, body_snippet: String }, // server answered with a non-200 status
+    ApiError(String),                               // 200, but the data couldn't be parsed
     NetworkError(String),
 }
 
@@ -24,6 +40,24 @@ pub trait Pricing {
     fn fetch_price(&self) -> ApiResult;
     fn save_to_file(&self, timestamp: &str, price: f64) -> std::io::Result<()>;
     fn display_name(&self) -> &'static str;
+    fn last_saved(&self) -> Option<PriceRecord>;
+
+    //The price with the rest of its quote, for APIs that send one (Stooq's OHLCV).
+    //By default that's just `fetch_price`'s close, and `save_quote` saves only the close.
+    fn fetch_quote(&self) -> Result<StooqSymbol, ApiResult> {
+        match self.fetch_price() {
+            ApiResult::Success(price) => Ok(StooqSymbol::from_close(price)),
+            other => Err(other),
+        }
+    }
+    fn save_quote(&self, timestamp: &str, quote: &StooqSymbol) -> std::io::Result<()> {
+        self.save_to_file(timestamp, quote.close)
+    }
+
+    //Rejects prices that can't be real (outage glitches like 0); override for asset-specific bounds
+    fn is_plausible(&self, price: f64) -> bool {
+        price.is_finite() && price > 0.0
+    }
 }
 
 // Typed model for timeapi.io
@@ -45,13 +79,180 @@ fn fetch_network_time_utc() -> Result<String, String> {
 }
 
 
-//Just writes the asset price/timestamp to its respective asset txt file
-fn write_price_to_file(file_name: &str, timestamp: &str, price: f64) -> std::io::Result<()> {
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(file_name)?;
-    writeln!(file, "[{}],{}", timestamp, price)?;
+// How much of an error response body we keep for the logs
+const BODY_SNIPPET_CHARS: usize = 200;
+
+//Turns a non-200 response into HttpStatus, keeping the start of the body for context
+fn http_status_result(code: u16, response: ureq::Response) -> ApiResult {
+    let body = response.into_string().unwrap_or_default();
+    let body_snippet = body.trim().chars().take(BODY_SNIPPET_CHARS).collect();
+    ApiResult::HttpStatus { code, body_snippet }
+}
+
+//Does the HTTP part of a price fetch: the body of a 200 response, or the ApiResult to report
+fn fetch_body(url: &str) -> Result<String, ApiResult> {
+    match ureq::get(url).call() {
+        Ok(response) if response.status() == 200 => response
+            .into_string()
+            .map_err(|e| ApiResult::NetworkError(format!("Failed to read response: {}", e))),
+        Ok(response) => Err(http_status_result(response.status(), response)),
+        // ureq reports 4xx/5xx as errors, but the server did answer
+        Err(ureq::Error::Status(code, response)) => Err(http_status_result(code, response)),
+        Err(e) => Err(ApiResult::NetworkError(format!("Request failed: {}", e))),
+    }
+}
+
+//Directory all output files go to: `--output-dir DIR`, else the OUTPUT_DIR env var, else the working directory
+fn output_dir() -> PathBuf {
+    output_dir_from(arg_value("--output-dir"), std::env::var("OUTPUT_DIR").ok())
+}
+
+//Picks the output directory: the CLI value wins over the env var; empty values are ignored
+fn output_dir_from(cli: Option<String>, env: Option<String>) -> PathBuf {
+    cli.filter(|dir| !dir.is_empty())
+        .or(env.filter(|dir| !dir.is_empty()))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+//Where an output file lives, e.g. "bitcoin_pricing.txt" -> "<output dir>/bitcoin_pricing.txt"
+fn output_path(file_name: &str) -> PathBuf {
+    output_dir().join(file_name)
+}
+
+//Opens a file for appending, creating it (and its directory) if needed
+fn open_for_append(path: &Path) -> std::io::Result<File> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+//Appends one line to a price file, rotating it first if it has grown too big: once the file
+//is over `max_bytes` it is renamed to "<name>.1" (replacing an older one) and a fresh file
+//is started; the size is checked before appending
+fn append_line(path: &Path, line: &str, max_bytes: u64) -> std::io::Result<()> {
+    if std::fs::metadata(path).is_ok_and(|m| m.len() > max_bytes) {
+        let mut rotated = path.as_os_str().to_owned();
+        rotated.push(".1");
+        std::fs::rename(path, rotated)?;
+    }
+    let mut file = open_for_append(path)?;
+    writeln!(file, "{}", line)
+}
+
+//Just writes the asset price/timestamp to its respective asset txt file, as "[ts],source,price"
+fn write_price_to_file(path: &Path, timestamp: &str, source: &str, price: f64, max_bytes: u64) -> std::io::Result<()> {
+    append_line(path, &price_line(timestamp, source, price), max_bytes)
+}
+
+//Formats one stored price line
+fn price_line(timestamp: &str, source: &str, price: f64) -> String {
+    format!("[{}],{},{}", timestamp, source, price)
+}
+
+//One line read back from a price file; source is None for files written before it was recorded
+#[derive(Debug, PartialEq)]
+pub struct PriceRecord {
+    timestamp: String,
+    source: Option<String>,
+    price: f64,
+}
+
+//Last readable line of a price file (None if the file is missing or has no valid lines)
+fn read_last_price(path: &Path) -> Option<PriceRecord> {
+    let text = std::fs::read_to_string(path).ok()?;
+    text.lines().rev().find_map(parse_price_line)
+}
+
+//Parses "[ts],source,price" or the older "[ts],price"; None for anything else.
+//Extra columns after the price (the equities' OHLC, see `quote_line`) are ignored.
+fn parse_price_line(line: &str) -> Option<PriceRecord> {
+    let rest = line.trim().strip_prefix('[')?;
+    let (timestamp, fields) = rest.split_once("],")?;
+    let (source, price) = match fields.split_once(',') {
+        Some((source, rest)) => (Some(source.to_string()), rest.split(',').next().unwrap_or(rest)),
+        None => (None, fields),
+    };
+    Some(PriceRecord {
+        timestamp: timestamp.to_string(),
+        source,
+        price: price.trim().parse().ok()?,
+    })
+}
+
+// ============================ History analysis ============================
+
+//Every readable price line of a file, oldest first
+fn load_prices(path: &Path) -> std::io::Result<Vec<PriceRecord>> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(text.lines().filter_map(parse_price_line).collect())
+}
+
+//(min, max, mean) of a file's prices; an error message if it can't be read or has no prices
+fn analyze_file(path: &Path) -> Result<(f64, f64, f64), String> {
+    let prices: Vec<f64> = load_prices(path).map_err(|e| e.to_string())?.iter().map(|r| r.price).collect();
+    if prices.is_empty() {
+        return Err("no prices".to_string());
+    }
+    let min = prices.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = prices.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let mean = prices.iter().sum::<f64>() / prices.len() as f64;
+    Ok((min, max, mean))
+}
+
+//Analyzes every file on one thread per CPU core; see `analyze_all_with`
+fn analyze_all(files: &[&str]) -> HashMap<String, Result<(f64, f64, f64), String>> {
+    let workers = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    analyze_all_with(files, workers)
+}
+
+//Min/max/mean per file, keyed by file name. `workers` threads take the next file from a
+//shared counter and send results back over a channel; an unreadable file only gets an
+//error entry, the other files are still analyzed.
+fn analyze_all_with(files: &[&str], workers: usize) -> HashMap<String, Result<(f64, f64, f64), String>> {
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..workers.clamp(1, files.len().max(1)) {
+            let tx = tx.clone();
+            let next = &next;
+            scope.spawn(move || {
+                while let Some(file) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let _ = tx.send((file.to_string(), analyze_file(Path::new(file))));
+                }
+            });
+        }
+    });
+    drop(tx);
+    rx.into_iter().collect()
+}
+
+//Turns a display name into a JSON key ("S&P 500" -> "sp500", "Bitcoin" -> "bitcoin")
+fn json_key(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+//Builds one cycle record like {"ts":"...","bitcoin":123.4,"ethereum":null}, null for failed fetches
+fn cycle_json_line(timestamp: &str, results: &[(&str, ApiResult)]) -> String {
+    let mut fields = vec![format!("\"ts\":{}", serde_json::Value::from(timestamp))];
+    for (name, result) in results {
+        let value = match result {
+            ApiResult::Success(price) => serde_json::Value::from(*price),
+            _ => serde_json::Value::Null,
+        };
+        fields.push(format!("{}:{}", serde_json::Value::from(json_key(name)), value));
+    }
+    format!("{{{}}}", fields.join(","))
+}
+
+//Appends the cycle record as a single line to the shared JSON log
+fn save_cycle_json(path: &Path, timestamp: &str, results: &[(&str, ApiResult)]) -> std::io::Result<()> {
+    let mut file = open_for_append(path)?;
+    writeln!(file, "{}", cycle_json_line(timestamp, results))?;
     Ok(())
 }
 
@@ -61,43 +262,55 @@ fn write_price_to_file(file_name: &str, timestamp: &str, price: f64) -> std::io:
 const BITCOIN_API: &str = "https://api.binance.us/api/v3/ticker/price?symbol=BTCUSD";
 const BITCOIN_FILE_NAME: &str = "bitcoin_pricing.txt";
 
-struct Bitcoin;
+struct Bitcoin {
+    source: &'static str, // where the price comes from, stored with every line
+    max_file_bytes: u64,  // rotation size of the txt file
+}
 
 #[derive(Deserialize)]
 struct BinancePrice {
     price: String, // Binance returns the price as a string
 }
 
+//Parses a Binance ticker body like {"symbol":"BTCUSD","price":"65000.12"}
+fn parse_binance_price(json: &str) -> ApiResult {
+    match serde_json::from_str::<BinancePrice>(json) {
+        Ok(v) => match v.price.parse::<f64>() {
+            Ok(p) => ApiResult::Success(p),
+            Err(e) => ApiResult::ApiError(format!("Failed to parse price: {}", e)),
+        },
+        Err(e) => ApiResult::ApiError(format!("Failed to parse JSON: {}", e)),
+    }
+}
+
 //This request the price from the API url
 impl Pricing for Bitcoin {
     fn fetch_price(&self) -> ApiResult {
-        match ureq::get(BITCOIN_API).call() {
-            Ok(response) => {
-                if response.status() == 200 {
-                    match response.into_json::<BinancePrice>() {
-                        Ok(v) => match v.price.parse::<f64>() {
-                            Ok(p) => ApiResult::Success(p),
-                            Err(e) => ApiResult::ApiError(format!("Failed to parse price: {}", e)),
-                        },
-                        Err(e) => ApiResult::ApiError(format!("Failed to parse JSON: {}", e)),
-                    }
-                } else {
-                    ApiResult::ApiError(format!("HTTP error: {}", response.status()))
-                }
-            }
-            Err(e) => ApiResult::NetworkError(format!("Request failed: {}", e)),
+        match fetch_body(BITCOIN_API) {
+            Ok(body) => parse_binance_price(&body),
+            Err(result) => result,
         }
     }
 
     //Just saves the date/price to a txt file
     fn save_to_file(&self, timestamp: &str, price: f64) -> std::io::Result<()> {
-        write_price_to_file(BITCOIN_FILE_NAME, timestamp, price)
+        write_price_to_file(&output_path(BITCOIN_FILE_NAME), timestamp, self.source, price, self.max_file_bytes)
     }
 
     //Returns the name of the asset
     fn display_name(&self) -> &'static str {
         "Bitcoin"
     }
+
+    //Last price stored in the txt file
+    fn last_saved(&self) -> Option<PriceRecord> {
+        read_last_price(&output_path(BITCOIN_FILE_NAME))
+    }
+
+    //Bitcoin hasn't traded under $100 in years; anything outside this range is a bad quote
+    fn is_plausible(&self, price: f64) -> bool {
+        price.is_finite() && (100.0..10_000_000.0).contains(&price)
+    }
 }
 
 // ============================== Ethereum (Binance US) ==============================
@@ -105,54 +318,119 @@ impl Pricing for Bitcoin {
 //declaring Api link, file name, and struct
 const ETHEREUM_API: &str = "https://api.binance.us/api/v3/ticker/price?symbol=ETHUSD";
 const ETHEREUM_FILE_NAME: &str = "ethereum_pricing.txt";
-struct Ethereum;
+struct Ethereum {
+    source: &'static str,
+    max_file_bytes: u64,
+}
 
 //This request the price from the API urls
 impl Pricing for Ethereum {
     fn fetch_price(&self) -> ApiResult {
-        match ureq::get(ETHEREUM_API).call() {
-            Ok(response) => {
-                if response.status() == 200 {
-                    match response.into_json::<BinancePrice>() {
-                        Ok(v) => match v.price.parse::<f64>() {
-                            Ok(p) => ApiResult::Success(p),
-                            Err(e) => ApiResult::ApiError(format!("Failed to parse price: {}", e)),
-                        },
-                        Err(e) => ApiResult::ApiError(format!("Failed to parse JSON: {}", e)),
-                    }
-                } else {
-                    ApiResult::ApiError(format!("HTTP error: {}", response.status()))
-                }
-            }
-            Err(e) => ApiResult::NetworkError(format!("Request failed: {}", e)),
+        match fetch_body(ETHEREUM_API) {
+            Ok(body) => parse_binance_price(&body),
+            Err(result) => result,
         }
     }
     //Just saves the date/price to a txt file
     fn save_to_file(&self, timestamp: &str, price: f64) -> std::io::Result<()> {
-        write_price_to_file(ETHEREUM_FILE_NAME, timestamp, price)
+        write_price_to_file(&output_path(ETHEREUM_FILE_NAME), timestamp, self.source, price, self.max_file_bytes)
     }
     //returns the name of the asset
     fn display_name(&self) -> &'static str {
         "Ethereum"
     }
+    //Last price stored in the txt file
+    fn last_saved(&self) -> Option<PriceRecord> {
+        read_last_price(&output_path(ETHEREUM_FILE_NAME))
+    }
+    //Same idea as Bitcoin, with Ethereum-sized bounds
+    fn is_plausible(&self, price: f64) -> bool {
+        price.is_finite() && (1.0..1_000_000.0).contains(&price)
+    }
 }
 
-// ============================== S&P 500 (Stooq) ==============================
+// ============================== Equities (Stooq) ==============================
 
-//declaring Api link, file name, and struct 
-const SP500_API: &str = "https://stooq.pl/q/l/?s=%5Espx&f=sd2t2ohlcv&h&e=json";
-const SP500_FILE_NAME: &str = "sp500_pricing.txt";
-struct Sp500;
+//Stooq quote endpoint; the symbol (e.g. "^spx", "aapl.us") is filled in per asset
+const STOOQ_API_BASE: &str = "https://stooq.pl/q/l/";
+
+//Any ticker Stooq knows about: its symbol, the file it is saved to, and a display name
+struct StooqAsset {
+    symbol: &'static str,
+    file_name: &'static str,
+    name: &'static str,
+    source: &'static str,
+    max_file_bytes: u64,
+}
+
+impl StooqAsset {
+    fn new(symbol: &'static str, file_name: &'static str, name: &'static str, max_file_bytes: u64) -> Self {
+        StooqAsset {
+            symbol,
+            file_name,
+            name,
+            source: "stooq",
+            max_file_bytes,
+        }
+    }
+
+    //Builds the quote URL for this symbol ("^" has to be percent-encoded)
+    fn api_url(&self) -> String {
+        format!(
+            "{}?s={}&f=sd2t2ohlcv&h&e=json",
+            STOOQ_API_BASE,
+            self.symbol.replace('^', "%5E")
+        )
+    }
+}
+
+//The two indexes we track
+fn sp500(max_file_bytes: u64) -> StooqAsset {
+    StooqAsset::new("^spx", "sp500_pricing.txt", "S&P 500", max_file_bytes)
+}
+fn dow_jones(max_file_bytes: u64) -> StooqAsset {
+    StooqAsset::new("^dji", "dowjones_pricing.txt", "Dow Jones", max_file_bytes)
+}
 
 #[derive(Deserialize)]
 struct StooqResponse {
     symbols: Vec<StooqSymbol>,
 }
 
-#[derive(Deserialize)]
-struct StooqSymbol {
+//One quote row; close is the price we alert on, the rest is logged when Stooq sends it
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct StooqSymbol {
+    #[serde(default, deserialize_with = "de_opt_str_or_f64")]
+    open: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_str_or_f64")]
+    high: Option<f64>,
+    #[serde(default, deserialize_with = "de_opt_str_or_f64")]
+    low: Option<f64>,
     #[serde(deserialize_with = "de_str_or_f64")]
     close: f64,
+    #[serde(default, deserialize_with = "de_opt_str_or_f64")]
+    volume: Option<f64>,
+}
+
+impl StooqSymbol {
+    //A quote with only the close, for assets whose API sends just a price
+    fn from_close(close: f64) -> Self {
+        StooqSymbol {
+            open: None,
+            high: None,
+            low: None,
+            close,
+            volume: None,
+        }
+    }
+}
+
+//Same as `de_str_or_f64`, but a missing or unparsable value (Stooq sends "N/D") becomes None
+fn de_opt_str_or_f64<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(de_str_or_f64(deserializer).ok())
 }
 
 // Custom deserializer to accept either a number or a string for "close"
@@ -191,50 +469,225 @@ where
     deserializer.deserialize_any(Visitor)
 }
 
+//Parses a Stooq quote body and returns the first symbol's quote
+fn parse_stooq_quote(json: &str) -> Result<StooqSymbol, ApiResult> {
+    match serde_json::from_str::<StooqResponse>(json) {
+        Ok(v) => match v.symbols.first() {
+            Some(first) => Ok(*first),
+            None => Err(ApiResult::ApiError("No symbols in Stooq response".to_string())),
+        },
+        Err(e) => Err(ApiResult::ApiError(format!("Failed to parse JSON: {}", e))),
+    }
+}
+
+//Parses a Stooq quote body like {"symbols":[{"symbol":"^SPX","close":5123.5}]} into the
+//first symbol's close, the same shape as `parse_binance_price`
+fn parse_stooq_price(json: &str) -> ApiResult {
+    match parse_stooq_quote(json) {
+        Ok(quote) => ApiResult::Success(quote.close),
+        Err(result) => result,
+    }
+}
+
+//Formats an equity line: "[ts],source,close,open,high,low,volume" (missing values left empty).
+//Close stays in the price column so `parse_price_line` reads these like any other line.
+fn quote_line(timestamp: &str, source: &str, quote: &StooqSymbol) -> String {
+    let field = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+    format!(
+        "{},{},{},{},{}",
+        price_line(timestamp, source, quote.close),
+        field(quote.open),
+        field(quote.high),
+        field(quote.low),
+        field(quote.volume)
+    )
+}
 
 //This request the price from the API urls
-impl Pricing for Sp500 {
+impl Pricing for StooqAsset {
     fn fetch_price(&self) -> ApiResult {
-        match ureq::get(SP500_API).call() {
-            Ok(response) => {
-                if response.status() == 200 {
-                    match response.into_json::<StooqResponse>() {
-                        Ok(v) => {
-                            if let Some(first) = v.symbols.get(0) {
-                                ApiResult::Success(first.close)
-                            } else {
-                                ApiResult::ApiError("No symbols in Stooq response".to_string())
-                            }
-                        }
-                        Err(e) => ApiResult::ApiError(format!("Failed to parse JSON: {}", e)),
-                    }
-                } else {
-                    ApiResult::ApiError(format!("HTTP error: {}", response.status()))
-                }
-            }
-            Err(e) => ApiResult::NetworkError(format!("Request failed: {}", e)),
+        match fetch_body(&self.api_url()) {
+            Ok(body) => parse_stooq_price(&body),
+            Err(result) => result,
         }
     }
-    //Just saves the date/price to a txt file
+    //Same request, keeping the whole OHLCV quote
+    fn fetch_quote(&self) -> Result<StooqSymbol, ApiResult> {
+        fetch_body(&self.api_url()).and_then(|body| parse_stooq_quote(&body))
+    }
+    //Saves the date/price to a txt file
     fn save_to_file(&self, timestamp: &str, price: f64) -> std::io::Result<()> {
-        write_price_to_file(SP500_FILE_NAME, timestamp, price)
+        write_price_to_file(&output_path(self.file_name), timestamp, self.source, price, self.max_file_bytes)
+    }
+    //Saves the date/close with the rest of the OHLCV quote
+    fn save_quote(&self, timestamp: &str, quote: &StooqSymbol) -> std::io::Result<()> {
+        append_line(&output_path(self.file_name), &quote_line(timestamp, self.source, quote), self.max_file_bytes)
     }
     //returns the name of the asset
     fn display_name(&self) -> &'static str {
-        "S&P 500"
+        self.name
+    }
+    //Last price stored in the txt file
+    fn last_saved(&self) -> Option<PriceRecord> {
+        read_last_price(&output_path(self.file_name))
     }
 }
 
+// ============================ De-duplicating rows ============================
+
+// Decides whether a price row is worth writing: skips a price equal to the last one
+// written for the same asset, unless `heartbeat` has passed since that write.
+struct Dedupe {
+    heartbeat: Option<Duration>,                  // None = never re-write an unchanged price
+    last: HashMap<&'static str, (f64, Instant)>,  // asset -> (last written price, when)
+}
+
+impl Dedupe {
+    fn new(heartbeat: Option<Duration>) -> Self {
+        Dedupe { heartbeat, last: HashMap::new() }
+    }
+
+    // True if the row should be written; call `mark_written` once it actually was
+    fn should_write(&self, asset: &'static str, price: f64, now: Instant) -> bool {
+        match self.last.get(asset) {
+            Some(&(last_price, written_at)) => {
+                let heartbeat_due = self.heartbeat.is_some_and(|h| now.duration_since(written_at) >= h);
+                last_price != price || heartbeat_due
+            }
+            None => true,
+        }
+    }
+
+    // Remembers a row that was written, so the same price is skipped next time
+    fn mark_written(&mut self, asset: &'static str, price: f64, now: Instant) {
+        self.last.insert(asset, (price, now));
+    }
+}
+
+// Value after `flag` on the command line, e.g. `--heartbeat-mins 15`
+fn arg_value(flag: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).cloned()
+}
+
 // ================================== main ==================================
 
+//Dry run (fetch and print, write nothing): the `--dry-run` flag, or DRY_RUN set to anything but ""/"0"/"false"
+fn dry_run_from(flag: bool, env: Option<String>) -> bool {
+    flag || env.is_some_and(|v| !v.is_empty() && v != "0" && !v.eq_ignore_ascii_case("false"))
+}
+
+//Prints a good price and appends its quote to the asset's txt file, unless `write_txt` is off
+//(--no-txt / --dry-run) or dedupe says it's unchanged
+fn record_price(asset: &dyn Pricing, timestamp: &str, quote: &StooqSymbol, write_txt: bool, dedupe: Option<&mut Dedupe>) {
+    let price = quote.close;
+    println!("[{}] {} price: ${}", timestamp, asset.display_name(), price);
+    if !write_txt {
+        return;
+    }
+    let now = Instant::now();
+    if dedupe.as_ref().is_some_and(|d| !d.should_write(asset.display_name(), price, now)) {
+        return;
+    }
+    // Only a row that made it to the file counts as written: after a failed write the
+    // same price is tried again next cycle
+    match asset.save_quote(timestamp, quote) {
+        Ok(()) => {
+            if let Some(d) = dedupe {
+                d.mark_written(asset.display_name(), price, now);
+            }
+        }
+        Err(e) => eprintln!("Failed to write {} price: {}", asset.display_name(), e),
+    }
+}
+
+//Doubles the wait for every consecutive cycle where all assets failed, up to MAX_INTERVAL
+fn backoff_interval(failed_cycles: u32) -> Duration {
+    let factor = 2u32.saturating_pow(failed_cycles);
+    BASE_INTERVAL.saturating_mul(factor).min(MAX_INTERVAL)
+}
+
 fn main() {
-    // Make a list of the three things we track; each knows how to get its price and save it
+    // `--analyze` prints min/max/mean of every price file, then exits without fetching
+    if std::env::args().any(|a| a == "--analyze") {
+        let paths: Vec<String> = [BITCOIN_FILE_NAME, ETHEREUM_FILE_NAME, "sp500_pricing.txt", "dowjones_pricing.txt"]
+            .iter()
+            .map(|f| output_path(f).to_string_lossy().into_owned())
+            .collect();
+        let files: Vec<&str> = paths.iter().map(String::as_str).collect();
+        let results = analyze_all(&files);
+        for file in &files {
+            match &results[*file] {
+                Ok((min, max, mean)) => println!("{}: min ${} max ${} mean ${:.2}", file, min, max, mean),
+                Err(e) => eprintln!("{}: {}", file, e),
+            }
+        }
+        return;
+    }
+
+    // `--dry-run` (or DRY_RUN=1) fetches and prints as usual but writes no files at all
+    let dry_run = dry_run_from(std::env::args().any(|a| a == "--dry-run"), std::env::var("DRY_RUN").ok());
+    if dry_run {
+        println!("Dry run: prices are printed only, no files are written");
+    }
+
+    // Per-asset txt files are still written unless --no-txt is given
+    let write_txt = !dry_run && !std::env::args().any(|a| a == "--no-txt");
+
+    // `--max-file-mb N` sets the size a price txt file may reach before it is rotated (default 5 MB)
+    let max_file_bytes = arg_value("--max-file-mb").map_or(DEFAULT_MAX_FILE_BYTES, |v| {
+        match v.parse::<u64>().ok().and_then(|mb| mb.checked_mul(1024 * 1024)) {
+            Some(bytes) => bytes,
+            None => {
+                eprintln!("Invalid --max-file-mb value: {}", v);
+                std::process::exit(2);
+            }
+        }
+    });
+
+    // `--dedupe` skips txt rows whose price didn't change; `--heartbeat-mins N` still writes one every N minutes
+    let heartbeat = arg_value("--heartbeat-mins").map(|v| {
+        match v.parse::<u64>().ok().and_then(|mins| mins.checked_mul(60)) {
+            Some(secs) => Duration::from_secs(secs),
+            None => {
+                eprintln!("Invalid --heartbeat-mins value: {}", v);
+                std::process::exit(2);
+            }
+        }
+    });
+    let dedupe_on = std::env::args().any(|a| a == "--dedupe");
+    if heartbeat.is_some() && !dedupe_on {
+        eprintln!("--heartbeat-mins has no effect without --dedupe");
+    }
+    let mut dedupe = dedupe_on.then(|| Dedupe::new(heartbeat));
+
+    // Make a list of the things we track; each knows how to get its price and save it
     let assets: Vec<Box<dyn Pricing>> = vec![
-        Box::new(Bitcoin),
-        Box::new(Ethereum),
-        Box::new(Sp500),
+        Box::new(Bitcoin { source: "binance.us", max_file_bytes }),
+        Box::new(Ethereum { source: "binance.us", max_file_bytes }),
+        Box::new(sp500(max_file_bytes)),
+        Box::new(dow_jones(max_file_bytes)),
     ];
 
+    // Show where each txt file left off (older files have no source column)
+    if write_txt {
+        for asset in &assets {
+            if let Some(last) = asset.last_saved() {
+                println!(
+                    "{}: last stored ${} at [{}] from {}",
+                    asset.display_name(),
+                    last.price,
+                    last.timestamp,
+                    last.source.as_deref().unwrap_or("unknown source")
+                );
+            }
+        }
+    }
+
+    // How many cycles in a row had no asset succeed (e.g. no internet)
+    let mut failed_cycles: u32 = 0;
+    let mut interval = BASE_INTERVAL;
+
     loop {
         
         let timestamp = match fetch_network_time_utc() {
@@ -246,15 +699,29 @@ fn main() {
         };
 
         // Go through each asset: get its latest number, show it, and save it
+        let mut any_success = false;
+        let mut cycle: Vec<(&str, ApiResult)> = Vec::new();
         for asset in &assets {
-            match asset.fetch_price() {
+            // An implausible price (e.g. 0 during an outage) is treated as bad API data: not saved
+            let (result, quote) = match asset.fetch_quote() {
+                Ok(quote) if !asset.is_plausible(quote.close) => {
+                    (ApiResult::ApiError(format!("implausible price {}, not saved", quote.close)), None)
+                }
+                Ok(quote) => (ApiResult::Success(quote.close), Some(quote)),
+                Err(result) => (result, None),
+            };
+            match &result {
                 // Got a real price: print it and try to write a line to that asset's file
-                ApiResult::Success(price) => {
-                    println!("[{}] {} price: ${}", timestamp, asset.display_name(), price);
-                    if let Err(e) = asset.save_to_file(&timestamp, price) {
-                        eprintln!("Failed to write {} price: {}", asset.display_name(), e);
+                ApiResult::Success(_) => {
+                    any_success = true;
+                    if let Some(quote) = &quote {
+                        record_price(asset.as_ref(), &timestamp, quote, write_txt, dedupe.as_mut());
                     }
                 }
+                // The website answered with an error status (e.g. 429 rate limit, 500)
+                ApiResult::HttpStatus { code, body_snippet } => {
+                    eprintln!("[{}] {} HTTP {}: {}", timestamp, asset.display_name(), code, body_snippet);
+                }
                 // The website answered, but the data wasn't usable
                 ApiResult::ApiError(err) => {
                     eprintln!("[{}] {} API error: {}", timestamp, asset.display_name(), err);
@@ -264,9 +731,347 @@ fn main() {
                     eprintln!("[{}] {} Network error: {}", timestamp, asset.display_name(), err);
                 }
             }
+            cycle.push((asset.display_name(), result));
         }
 
-        // Wait 10 seconds
-        thread::sleep(Duration::from_secs(10));
+        // One consolidated JSON line for the whole cycle
+        if !dry_run
+            && let Err(e) = save_cycle_json(&output_path(CYCLE_LOG_FILE), &timestamp, &cycle)
+        {
+            eprintln!("Failed to write cycle log: {}", e);
+        }
+
+        // Back off while everything is failing, go back to normal once anything works
+        if any_success {
+            failed_cycles = 0;
+        } else {
+            failed_cycles = failed_cycles.saturating_add(1);
+        }
+        let next = backoff_interval(failed_cycles);
+        if next != interval {
+            println!("Sleep interval is now {} seconds", next.as_secs());
+            interval = next;
+        }
+
+        thread::sleep(interval);
+    }
+}
+
+// ================================== tests ==================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycle_json_has_prices_and_nulls() {
+        let path = std::env::temp_dir().join(format!("df_cycle_{}.ndjson", std::process::id()));
+        let path = path.as_path();
+        let _ = std::fs::remove_file(path);
+
+        let results = [
+            ("Bitcoin", ApiResult::Success(123.4)),
+            ("Ethereum", ApiResult::NetworkError("offline".into())),
+            ("S&P 500", ApiResult::ApiError("bad json".into())),
+        ];
+        save_cycle_json(path, "2020-01-01T00:00:00", &results).unwrap();
+        save_cycle_json(path, "2020-01-01T00:00:10", &results).unwrap();
+
+        let text = std::fs::read_to_string(path).unwrap();
+        let _ = std::fs::remove_file(path);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2, "one line per cycle");
+
+        let v: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(v["ts"], "2020-01-01T00:00:00");
+        assert_eq!(v["bitcoin"], 123.4);
+        assert!(v["ethereum"].is_null());
+        assert!(v["sp500"].is_null());
+        assert!(lines[0].starts_with("{\"ts\":"), "timestamp comes first");
+    }
+
+    #[test]
+    fn output_dir_is_created_and_used() {
+        let base = std::env::temp_dir().join(format!("df_output_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let env_dir = base.join("prices").to_str().unwrap().to_string();
+
+        let dir = output_dir_from(None, Some(env_dir.clone()));
+        assert_eq!(dir, PathBuf::from(&env_dir));
+        assert_eq!(output_dir_from(Some("cli".into()), Some(env_dir.clone())), PathBuf::from("cli"));
+        assert_eq!(output_dir_from(None, Some(String::new())), PathBuf::from("."));
+
+        // The directory doesn't exist yet; writing creates it and keeps the file name
+        write_price_to_file(&dir.join(BITCOIN_FILE_NAME), "2020-01-01T00:00:00", "binance.us", 1.5, DEFAULT_MAX_FILE_BYTES).unwrap();
+        let written = base.join("prices").join("bitcoin_pricing.txt");
+        assert!(written.is_file());
+        assert_eq!(read_last_price(&written).map(|r| r.price), Some(1.5));
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn implausible_prices_are_rejected() {
+        let assets: Vec<Box<dyn Pricing>> = vec![
+            Box::new(Bitcoin { source: "binance.us", max_file_bytes: DEFAULT_MAX_FILE_BYTES }),
+            Box::new(Ethereum { source: "binance.us", max_file_bytes: DEFAULT_MAX_FILE_BYTES }),
+            Box::new(sp500(DEFAULT_MAX_FILE_BYTES)),
+        ];
+        for asset in &assets {
+            for bad in [f64::NAN, f64::INFINITY, 0.0, -5.0] {
+                assert!(!asset.is_plausible(bad), "{} accepted {}", asset.display_name(), bad);
+            }
+        }
+        // Asset-specific bounds
+        assert!(assets[0].is_plausible(65_000.0));
+        assert!(!assets[0].is_plausible(3.2));
+        assert!(assets[1].is_plausible(3_200.0));
+        assert!(assets[2].is_plausible(5_000.0));
+    }
+
+    #[test]
+    fn price_lines_round_trip_old_and_new_formats() {
+        let path = std::env::temp_dir().join(format!("df_prices_{}.txt", std::process::id()));
+        let path = path.as_path();
+        let _ = std::fs::remove_file(path);
+
+        // A file started before sources were recorded, then appended to by the new code
+        std::fs::write(path, "[2020-01-01T00:00:00],42000.5\n").unwrap();
+        write_price_to_file(path, "2020-01-01T00:00:10", "coinbase", 42001.25, DEFAULT_MAX_FILE_BYTES).unwrap();
+
+        let text = std::fs::read_to_string(path).unwrap();
+        let _ = std::fs::remove_file(path);
+        let records: Vec<PriceRecord> = text.lines().filter_map(parse_price_line).collect();
+        assert_eq!(
+            records,
+            vec![
+                PriceRecord {
+                    timestamp: "2020-01-01T00:00:00".into(),
+                    source: None,
+                    price: 42000.5,
+                },
+                PriceRecord {
+                    timestamp: "2020-01-01T00:00:10".into(),
+                    source: Some("coinbase".into()),
+                    price: 42001.25,
+                },
+            ]
+        );
+
+        assert_eq!(parse_price_line(&price_line("ts", "stooq", 1.5)).unwrap().price, 1.5);
+        assert_eq!(parse_price_line("garbage"), None);
+        assert_eq!(parse_price_line("[ts],stooq,not-a-number"), None);
+    }
+
+    #[test]
+    fn price_files_rotate_past_the_size_limit() {
+        let path = std::env::temp_dir().join(format!("df_rotate_{}.txt", std::process::id()));
+        let rotated = std::env::temp_dir().join(format!("df_rotate_{}.txt.1", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&rotated);
+
+        // Each line is 31 bytes; with a 40-byte cap the third write rotates
+        for (i, price) in [1.5, 2.5, 3.5].iter().enumerate() {
+            let line = price_line(&format!("2020-01-01T00:00:0{}", i), "test", *price);
+            append_line(&path, &line, 40).unwrap();
+        }
+
+        let old = std::fs::read_to_string(&rotated).unwrap();
+        let active = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&rotated);
+        assert_eq!(old.lines().count(), 2);
+        assert_eq!(active, "[2020-01-01T00:00:02],test,3.5\n", "active file restarted");
+    }
+
+    #[test]
+    fn analyzes_files_in_parallel() {
+        let dir = std::env::temp_dir().join(format!("df_analyze_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let btc = dir.join("btc.txt");
+        let eth = dir.join("eth.txt");
+        let missing = dir.join("missing.txt");
+        // Old and new line formats mixed, plus a line that isn't a price
+        std::fs::write(&btc, "[t1],100\n[t2],binance.us,300\ngarbage\n[t3],binance.us,200\n").unwrap();
+        std::fs::write(&eth, "[t1],binance.us,2.5\n").unwrap();
+
+        let paths = [btc, eth, missing];
+        let names: Vec<String> = paths.iter().map(|p| p.to_string_lossy().into_owned()).collect();
+        let files: Vec<&str> = names.iter().map(String::as_str).collect();
+        let results = analyze_all_with(&files, 2);
+        let defaults = analyze_all(&files);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[files[0]], Ok((100.0, 300.0, 200.0)));
+        assert_eq!(results[files[1]], Ok((2.5, 2.5, 2.5)));
+        assert!(results[files[2]].is_err(), "unreadable file gets an error entry");
+
+        // Same answers from the default worker count
+        assert_eq!(defaults, results);
+    }
+
+    #[test]
+    fn dedupe_skips_repeats_until_heartbeat() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let prices = [5000.0, 5000.0, 5001.5, 5001.5, 5001.5, 5000.0];
+
+        // Every row the dedupe lets through is written successfully
+        let write_all = |dedupe: &mut Dedupe| -> Vec<bool> {
+            prices
+                .iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    let write = dedupe.should_write("S&P 500", *p, at(i as u64 * 10));
+                    if write {
+                        dedupe.mark_written("S&P 500", *p, at(i as u64 * 10));
+                    }
+                    write
+                })
+                .collect()
+        };
+
+        let mut plain = Dedupe::new(None);
+        assert_eq!(write_all(&mut plain), [true, false, true, false, false, true]);
+
+        // Assets are tracked separately
+        assert!(plain.should_write("Dow Jones", 5000.0, at(60)));
+
+        // With a 15s heartbeat an unchanged price is written again once 15s have passed
+        let mut heartbeat = Dedupe::new(Some(Duration::from_secs(15)));
+        assert_eq!(write_all(&mut heartbeat), [true, false, true, false, true, true]);
+
+        // A row whose write failed isn't marked, so the same price is tried again
+        let failing = Dedupe::new(None);
+        assert!(failing.should_write("S&P 500", 5000.0, at(0)));
+        assert!(failing.should_write("S&P 500", 5000.0, at(10)));
+    }
+
+    #[test]
+    fn parses_binance_prices() {
+        assert!(matches!(
+            parse_binance_price(r#"{"symbol":"BTCUSD","price":"65000.12"}"#),
+            ApiResult::Success(p) if p == 65000.12
+        ));
+        assert!(matches!(
+            parse_binance_price(r#"{"symbol":"BTCUSD","price":"n/a"}"#),
+            ApiResult::ApiError(e) if e.starts_with("Failed to parse price")
+        ));
+        assert!(matches!(
+            parse_binance_price(r#"{"code":-1121,"msg":"Invalid symbol."}"#),
+            ApiResult::ApiError(e) if e.starts_with("Failed to parse JSON")
+        ));
+        assert!(matches!(parse_binance_price("not json"), ApiResult::ApiError(_)));
+    }
+
+    #[test]
+    fn parses_stooq_prices() {
+        // Stooq sends close as a number, or sometimes as a string
+        let numeric = r#"{"symbols":[{"symbol":"^SPX","close":5123.5}]}"#;
+        assert!(matches!(parse_stooq_price(numeric), ApiResult::Success(p) if p == 5123.5));
+        let text = r#"{"symbols":[{"symbol":"^DJI","close":"39000.25"}]}"#;
+        assert!(matches!(parse_stooq_price(text), ApiResult::Success(p) if p == 39000.25));
+        assert!(matches!(parse_stooq_price("{"), ApiResult::ApiError(_)));
+        assert_eq!(parse_stooq_quote(numeric).map(|q| q.close).ok(), Some(5123.5));
+
+        assert!(matches!(
+            parse_stooq_quote(r#"{"symbols":[]}"#),
+            Err(ApiResult::ApiError(e)) if e == "No symbols in Stooq response"
+        ));
+        assert!(matches!(
+            parse_stooq_quote(r#"{"symbols":[{"symbol":"^SPX","close":"N/D"}]}"#),
+            Err(ApiResult::ApiError(_))
+        ));
+        assert!(matches!(parse_stooq_quote("{"), Err(ApiResult::ApiError(_))));
+    }
+
+    #[test]
+    fn parses_stooq_ohlcv() {
+        let sample = r#"{"symbols":[{"symbol":"^SPX","date":"20240105","time":"220000",
+            "open":4690.57,"high":"4721.49","low":4682.11,"close":4697.24,"volume":2479190000}]}"#;
+        let quote = parse_stooq_quote(sample).unwrap();
+        assert_eq!(
+            quote,
+            StooqSymbol {
+                open: Some(4690.57),
+                high: Some(4721.49),
+                low: Some(4682.11),
+                close: 4697.24,
+                volume: Some(2479190000.0),
+            }
+        );
+
+        // Close is still the stored price; the extra columns follow it
+        let line = quote_line("2024-01-05T22:00:00", "stooq", &quote);
+        assert_eq!(line, "[2024-01-05T22:00:00],stooq,4697.24,4690.57,4721.49,4682.11,2479190000");
+        assert_eq!(parse_price_line(&line).unwrap().price, 4697.24);
+
+        // Indexes often have no volume ("N/D"); the quote still parses
+        let no_volume = r#"{"symbols":[{"symbol":"^DJI","open":1,"high":3,"low":0.5,"close":2,"volume":"N/D"}]}"#;
+        let quote = parse_stooq_quote(no_volume).unwrap();
+        assert_eq!(quote.volume, None);
+        assert!(quote_line("ts", "stooq", &quote).ends_with(",2,1,3,0.5,"));
+    }
+
+    // Writes to a temp file instead of the output directory
+    struct TempAsset {
+        path: PathBuf,
+    }
+
+    impl Pricing for TempAsset {
+        fn fetch_price(&self) -> ApiResult {
+            ApiResult::Success(1.0)
+        }
+        fn save_to_file(&self, timestamp: &str, price: f64) -> std::io::Result<()> {
+            write_price_to_file(&self.path, timestamp, "test", price, DEFAULT_MAX_FILE_BYTES)
+        }
+        fn display_name(&self) -> &'static str {
+            "Test"
+        }
+        fn last_saved(&self) -> Option<PriceRecord> {
+            read_last_price(&self.path)
+        }
+    }
+
+    #[test]
+    fn dry_run_writes_nothing() {
+        assert!(dry_run_from(true, None));
+        assert!(dry_run_from(false, Some("1".into())));
+        assert!(!dry_run_from(false, Some("0".into())));
+        assert!(!dry_run_from(false, Some("false".into())));
+        assert!(!dry_run_from(false, None));
+
+        let path = std::env::temp_dir().join(format!("df_dry_run_{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let asset = TempAsset { path: path.clone() };
+
+        // Parsed like a real fetch, then recorded in dry-run mode (write_txt off)
+        let price = match parse_binance_price(r#"{"symbol":"BTCUSD","price":"65000.12"}"#) {
+            ApiResult::Success(p) => p,
+            other => panic!("expected a price, got {:?}", other),
+        };
+        let quote = StooqSymbol::from_close(price);
+        record_price(&asset, "2020-01-01T00:00:00", &quote, !dry_run_from(true, None), None);
+        assert!(!path.exists(), "dry run must not create the price file");
+
+        // The same call without dry run writes the line
+        record_price(&asset, "2020-01-01T00:00:00", &quote, true, None);
+        assert_eq!(asset.last_saved().map(|r| r.price), Some(65000.12));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn http_status_keeps_code_and_body_start() {
+        let long_body = format!("  Too Many Requests {}", "x".repeat(500));
+        let response = ureq::Response::new(429, "Too Many Requests", &long_body).unwrap();
+        match http_status_result(429, response) {
+            ApiResult::HttpStatus { code, body_snippet } => {
+                assert_eq!(code, 429);
+                assert!(body_snippet.starts_with("Too Many Requests"));
+                assert_eq!(body_snippet.chars().count(), BODY_SNIPPET_CHARS);
+            }
+            other => panic!("expected HttpStatus, got {:?}", other),
+        }
     }
 }
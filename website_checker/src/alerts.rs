@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::status::WebsiteStatus;
+
+// Counts consecutive unhealthy cycles per (URL, method) so alerts only fire after
+// N failures in a row (avoids flapping on a single bad check). The same URL checked
+// with GET and HEAD has two independent streaks.
+#[derive(Debug, Default)]
+pub struct FailureStreaks {
+    streaks: HashMap<(String, String), u32>, // (URL, method) -> consecutive unhealthy cycles
+}
+
+impl FailureStreaks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records one cycle: unhealthy results extend the target's streak, healthy ones reset it.
+    /// Targets missing from `results` keep their current streak.
+    pub fn record(&mut self, results: &[WebsiteStatus]) {
+        for ws in results {
+            let key = (ws.url.clone(), ws.method.clone());
+            if ws.healthy {
+                self.streaks.remove(&key);
+            } else {
+                *self.streaks.entry(key).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// (URL, method) targets unhealthy for at least `threshold` cycles in a row (sorted).
+    pub fn firing(&self, threshold: u32) -> Vec<(String, String)> {
+        let mut targets: Vec<(String, String)> = self
+            .streaks
+            .iter()
+            .filter(|(_, n)| **n >= threshold)
+            .map(|(target, _)| target.clone())
+            .collect();
+        targets.sort();
+        targets
+    }
+
+    /// Drops the streaks of `url` (every method), e.g. after it was removed from the URL list.
+    pub fn forget(&mut self, url: &str) {
+        self.streaks.retain(|(u, _), _| u != url);
+    }
+
+    /// Current streak for `url` checked with `method` (0 when it was last healthy or never seen).
+    pub fn streak(&self, url: &str, method: &str) -> u32 {
+        self.streaks.get(&(url.to_string(), method.to_string())).copied().unwrap_or(0)
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::CheckStatus;
+    use std::time::Duration;
+
+    fn result(url: &str, healthy: bool) -> WebsiteStatus {
+        WebsiteStatus {
+            status: if healthy {
+                CheckStatus::Success(200)
+            } else {
+                CheckStatus::HttpError(503)
+            },
+            response_time: Duration::from_millis(5),
+            healthy,
+            attempts: 1,
+            ..WebsiteStatus::not_sent(url, "GET", "2020-01-01T00:00:00Z", "")
+        }
+    }
+
+    #[test]
+    fn fires_after_threshold_and_resets_on_recovery() {
+        let mut streaks = FailureStreaks::new();
+
+        // Cycles 1-2: a.test down, b.test flaps
+        streaks.record(&[result("https://a.test", false), result("https://b.test", false)]);
+        streaks.record(&[result("https://a.test", false), result("https://b.test", true)]);
+        assert!(streaks.firing(3).is_empty());
+        assert_eq!(streaks.streak("https://b.test", "GET"), 0);
+
+        // Cycle 3: a.test reaches the threshold
+        streaks.record(&[result("https://a.test", false), result("https://b.test", false)]);
+        assert_eq!(streaks.firing(3), vec![("https://a.test".to_string(), "GET".to_string())]);
+        assert_eq!(streaks.streak("https://a.test", "GET"), 3);
+
+        // Cycle 4: a.test recovers, so it stops firing
+        streaks.record(&[result("https://a.test", true), result("https://b.test", false)]);
+        assert!(streaks.firing(3).is_empty());
+        assert_eq!(streaks.firing(2), vec![("https://b.test".to_string(), "GET".to_string())]);
+
+        // A URL dropped from the list stops firing right away
+        streaks.forget("https://b.test");
+        assert!(streaks.firing(1).is_empty());
+    }
+
+    #[test]
+    fn methods_of_one_url_keep_separate_streaks() {
+        let mut streaks = FailureStreaks::new();
+        let head = |healthy| WebsiteStatus { method: "HEAD".into(), ..result("https://a.test", healthy) };
+
+        // GET and HEAD both fail: each streak grows by one per cycle
+        streaks.record(&[result("https://a.test", false), head(false)]);
+        streaks.record(&[result("https://a.test", false), head(false)]);
+        assert_eq!(streaks.streak("https://a.test", "GET"), 2);
+        assert_eq!(streaks.streak("https://a.test", "HEAD"), 2);
+
+        // A healthy GET doesn't reset the failing HEAD
+        streaks.record(&[result("https://a.test", true), head(false)]);
+        assert_eq!(streaks.streak("https://a.test", "GET"), 0);
+        assert_eq!(streaks.firing(3), vec![("https://a.test".to_string(), "HEAD".to_string())]);
+
+        // Removing the URL drops every method
+        streaks.forget("https://a.test");
+        assert!(streaks.firing(1).is_empty());
+    }
+}
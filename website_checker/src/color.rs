@@ -0,0 +1,66 @@
+use std::io::IsTerminal;
+
+use crate::status::CheckStatus;
+
+// When to color console output: `--color auto|always|never` (`--no-color` = never).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    #[default]
+    Auto,   // color only when stdout is a terminal
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// Parses "auto", "always" or "never" (case-insensitive).
+    pub fn from_name(name: &str) -> Option<ColorMode> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Some(ColorMode::Auto),
+            "always" => Some(ColorMode::Always),
+            "never" => Some(ColorMode::Never),
+            _ => None,
+        }
+    }
+
+    /// True if output should be colored (Auto checks whether stdout is a TTY).
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorMode::Auto => std::io::stdout().is_terminal(),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+/// Wraps `text` in the ANSI color for `status` (success and unchanged green, http error yellow,
+/// transport red) when `on` is true; returns it unchanged otherwise.
+pub fn paint_status(text: &str, status: &CheckStatus, on: bool) -> String {
+    if !on {
+        return text.to_string();
+    }
+    let code = match status {
+        CheckStatus::Success(_) | CheckStatus::Connected | CheckStatus::Unchanged => "32",
+        CheckStatus::HttpError(_) => "33",
+        CheckStatus::Transport(_) => "31",
+    };
+    format!("\x1b[{}m{}\x1b[0m", code, text)
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paints_only_when_enabled() {
+        assert_eq!(paint_status("200", &CheckStatus::Success(200), false), "200");
+        assert_eq!(paint_status("200", &CheckStatus::Success(200), true), "\x1b[32m200\x1b[0m");
+        assert_eq!(paint_status("503", &CheckStatus::HttpError(503), true), "\x1b[33m503\x1b[0m");
+        assert_eq!(
+            paint_status("TRANSPORT", &CheckStatus::Transport("reset".into()), true),
+            "\x1b[31mTRANSPORT\x1b[0m"
+        );
+        assert!(!ColorMode::Never.enabled());
+        assert_eq!(ColorMode::from_name("ALWAYS"), Some(ColorMode::Always));
+    }
+}
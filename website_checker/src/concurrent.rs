@@ -1,9 +1,78 @@
-use std::sync::{mpsc, Arc, Mutex};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
-use crate::status::{CheckStatus, WebsiteStatus};
-use crate::validation::Config;
-use crate::time_utils::fetch_network_time_utc; // used to fetch a single timestamp for the batch
+use crate::rng::XorShift64;
+use crate::status::{CheckStatus, HttpClient, WebsiteStatus};
+use crate::validation::{parse_host_port, Config};
+use crate::time_utils::timestamp_utc_timed; // used to fetch a single timestamp for the batch
+
+// Options for a batch of concurrent checks.
+#[derive(Clone)]
+pub struct BatchConfig {
+    pub workers: usize,         // number of threads to use
+    pub max_retries: usize,     // how many times to retry if a transport error occurs
+    pub retry_delay: Duration,  // base wait before each retry
+    pub retry_jitter: f64,      // spread retries by ±this fraction of retry_delay (0.0 = off)
+    pub stagger: Duration,      // worker i waits i * stagger before its first job of a batch
+    pub max_total_requests: Option<usize>, // cap on requests per batch, retries included
+    pub max_per_host: Option<usize>, // cap on in-flight requests to one hostname (None = unlimited)
+    pub deadline: Option<Duration>, // wall-clock limit per batch; unfinished checks become timeouts
+    pub cfg: Config,            // validation rules applied to every URL
+}
+
+// Default batch options (matches the original `check_many(urls, 50, 1)` behavior)
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            workers: 50,
+            max_retries: 1,
+            retry_delay: Duration::ZERO,
+            retry_jitter: 0.0,
+            stagger: Duration::ZERO,
+            max_total_requests: None,
+            max_per_host: None,
+            deadline: None,
+            cfg: Config::default(),
+        }
+    }
+}
+
+// Checks are I/O-bound (threads mostly wait on the network), so running
+// several workers per core is fine.
+pub const WORKERS_PER_CORE: usize = 8;
+
+/// Sensible worker count for `url_count` URLs on a machine with `cores` cores:
+/// `min(url_count, cores * WORKERS_PER_CORE)`, and never less than 1.
+pub fn auto_workers(url_count: usize, cores: usize) -> usize {
+    url_count.min(cores.max(1) * WORKERS_PER_CORE).max(1)
+}
+
+/// `auto_workers` using this machine's available parallelism (1 if unknown).
+pub fn auto_workers_for(url_count: usize) -> usize {
+    let cores = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    auto_workers(url_count, cores)
+}
+
+/// Returns `base` randomly scaled into `[base * (1 - fraction), base * (1 + fraction)]`.
+/// A `fraction` of 0.0 returns `base` unchanged.
+pub fn jittered_delay(base: Duration, fraction: f64, rng: &mut XorShift64) -> Duration {
+    if fraction <= 0.0 || base.is_zero() {
+        return base;
+    }
+    let fraction = fraction.min(1.0);
+    // Map [0, 1) onto [-fraction, +fraction)
+    let factor = 1.0 + fraction * (2.0 * rng.next_f64() - 1.0);
+    base.mul_f64(factor)
+}
+
+/// Returns `interval` plus a random extra in `[0, max_jitter]`, so monitors started
+/// together drift apart instead of hitting targets in lockstep.
+pub fn interval_with_jitter(interval: Duration, max_jitter: Duration, rng: &mut XorShift64) -> Duration {
+    interval + max_jitter.mul_f64(rng.next_f64())
+}
 
 // Runs website checks concurrently across multiple worker threads.
 // - `urls`: list of websites to check
@@ -11,76 +80,476 @@ use crate::time_utils::fetch_network_time_utc; // used to fetch a single timesta
 // - `max_retries`: how many times to retry if a transport error occurs
 // Returns a vector of WebsiteStatus results in the same order as input URLs.
 pub fn check_many(urls: Vec<String>, workers: usize, max_retries: usize) -> Vec<WebsiteStatus> {
+    let batch = BatchConfig {
+        workers,
+        max_retries,
+        ..BatchConfig::default()
+    };
+    check_many_with(urls, &batch)
+}
+
+// Same as `check_many`, but with full control over the batch options.
+pub fn check_many_with(urls: Vec<String>, batch: &BatchConfig) -> Vec<WebsiteStatus> {
+    check_many_with_timeouts(urls.into_iter().map(|u| (u, None)).collect(), batch)
+}
+
+// Same as `check_many_with`, but each URL can carry its own request timeout
+// (`Some` overrides `batch.cfg.timeout` for that URL only).
+pub fn check_many_with_timeouts(urls: Vec<(String, Option<Duration>)>, batch: &BatchConfig) -> Vec<WebsiteStatus> {
     let n = urls.len();
     if n == 0 {
         return Vec::new(); // no URLs, return empty result
     }
 
     // Limit workers to at least 1 and at most the number of URLs
-    let workers = workers.max(1).min(n);
-    let cfg = Config::default();
-
-    // Fetch a single timestamp for the entire batch (shared across all threads)
-    let batch_ts = Arc::new(
-        fetch_network_time_utc().unwrap_or_else(|_| "unknown".to_string())
-    );
-
-    // Channels for sending jobs to workers and receiving results
-    let (job_tx, job_rx) = mpsc::channel::<(usize, String)>();
-    let (res_tx, res_rx) = mpsc::channel::<(usize, WebsiteStatus)>();
-    let job_rx = Arc::new(Mutex::new(job_rx)); // wrap in Arc+Mutex so threads can share
-
-    let mut handles = Vec::with_capacity(workers);
-
-    // Spawn worker threads
-    for _ in 0..workers {
-        let rx = Arc::clone(&job_rx);
-        let tx = res_tx.clone();
-        let cfg = cfg.clone();
-        let ts = Arc::clone(&batch_ts);
-
-        let handle = thread::spawn(move || {
-            // Process jobs until channel is closed
-            while let Ok((idx, url)) = rx.lock().unwrap().recv() {
-                let mut attempts = 0usize;
-
-                // Retry loop: only retry on transport errors
-                let ws = loop {
-                    let ws = WebsiteStatus::request_with_timestamp(&url, &cfg, &ts);
-                    match ws.status {
-                        CheckStatus::Transport(_) if attempts < max_retries => {
-                            attempts += 1;
-                            continue; // retry on transport error
+    let workers = batch.workers.max(1).min(n);
+    let checker = Checker::new(BatchConfig {
+        workers,
+        ..batch.clone()
+    });
+    checker.run_targets_timed(urls.into_iter().map(|(u, t)| (u, batch.cfg.method.clone(), t)).collect())
+    // checker dropped here: workers shut down and are joined
+}
+
+/// URLs whose result isn't a healthy `Success`, in input order and without duplicates
+/// (a URL checked with several methods is listed once). Feed them back into
+/// `check_many` / `Checker::run` for a quick confirmation pass.
+pub fn failed_urls(results: &[WebsiteStatus]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for ws in results {
+        let ok = ws.healthy && ws.status.is_success();
+        if !ok && !out.contains(&ws.url) {
+            out.push(ws.url.clone());
+        }
+    }
+    out
+}
+
+/// The targets (from the `run_targets_timed` call that produced `results`) whose result
+/// isn't a healthy `Success`, in input order: like `failed_urls`, but each keeps its
+/// method and timeout for the confirmation pass.
+pub fn failed_targets(targets: &[TimedTarget], results: &[WebsiteStatus]) -> Vec<TimedTarget> {
+    targets
+        .iter()
+        .zip(results)
+        .filter(|(_, ws)| !(ws.healthy && ws.status.is_success()))
+        .map(|(target, _)| target.clone())
+        .collect()
+}
+
+// Per-host counting semaphore: at most `max` checks of the same hostname run at once.
+// Workers block in `acquire` until a slot of that host frees up.
+struct HostLimiter {
+    max: usize,
+    in_flight: Mutex<HashMap<String, usize>>, // hostname -> checks currently running
+    freed: Condvar,                           // signalled whenever a slot is released
+}
+
+impl HostLimiter {
+    fn new(max: usize) -> Self {
+        HostLimiter {
+            max: max.max(1),
+            in_flight: Mutex::new(HashMap::new()),
+            freed: Condvar::new(),
+        }
+    }
+
+    // Waits for a free slot for `host` and takes it
+    fn acquire(&self, host: &str) -> HostSlot<'_> {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        while in_flight.get(host).is_some_and(|&n| n >= self.max) {
+            in_flight = self.freed.wait(in_flight).unwrap();
+        }
+        *in_flight.entry(host.to_string()).or_insert(0) += 1;
+        HostSlot { limiter: self, host: host.to_string() }
+    }
+
+    fn release(&self, host: &str) {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if let Some(n) = in_flight.get_mut(host) {
+            *n -= 1;
+            if *n == 0 {
+                in_flight.remove(host); // keep the map small across long runs
+            }
+        }
+        self.freed.notify_all();
+    }
+}
+
+// A taken host slot; released when dropped (even if the check panics)
+struct HostSlot<'a> {
+    limiter: &'a HostLimiter,
+    host: String,
+}
+
+impl Drop for HostSlot<'_> {
+    fn drop(&mut self) {
+        self.limiter.release(&self.host);
+    }
+}
+
+// A check target with its own timeout: (url, method, timeout override)
+pub type TimedTarget = (String, String, Option<Duration>);
+
+// One unit of work for the pool: where the URL sits in its batch, the URL itself,
+// the batch timestamp, and the channel of the batch that is waiting for the result.
+struct Job {
+    run: u64, // which `run` call this job belongs to
+    idx: usize,
+    url: String,
+    method: String,
+    timeout: Option<Duration>, // overrides `cfg.timeout` for this URL
+    ts: Arc<String>,
+    deadline: Option<Instant>, // skip the job if it's still queued after this
+    requests: Arc<AtomicUsize>, // requests made so far by this job's batch
+    results: mpsc::Sender<(usize, WebsiteStatus)>,
+}
+
+// A long-lived pool of worker threads that can run many batches.
+// Useful when checking the same list every cycle: threads are spawned once
+// and reused by every `run` call. Workers shut down when the Checker is dropped.
+pub struct Checker {
+    jobs: Option<mpsc::Sender<Job>>,    // None once shutting down
+    handles: Vec<thread::JoinHandle<()>>,
+    next_run: AtomicU64,                // id handed to the next `run` call
+    fetch_timestamp: bool,              // network time per batch (false = local clock)
+    timestamp_elapsed: Mutex<Duration>, // how long the last batch timestamp took
+    deadline: Option<Duration>,         // per-batch wall-clock limit
+    abandoned: AtomicBool,              // a batch left stragglers running (don't wait for them on drop)
+    method: String,                     // for `run` targets (`Config::method`)
+}
+
+impl Checker {
+    /// Spawns `batch.workers` threads (at least 1) that apply `batch` to every job.
+    pub fn new(batch: BatchConfig) -> Self {
+        let workers = batch.workers.max(1);
+        let (job_tx, job_rx) = mpsc::channel::<Job>();
+        let job_rx = Arc::new(Mutex::new(job_rx)); // wrap in Arc+Mutex so threads can share
+        // Shared by every worker so the per-host cap holds across the whole pool
+        let limiter = batch.max_per_host.map(|max| Arc::new(HostLimiter::new(max)));
+        // TLS setup (certificates, client identity) is done once for the whole pool
+        let client = HttpClient::new(&batch.cfg);
+
+        let mut handles = Vec::with_capacity(workers);
+
+        // Spawn worker threads
+        for worker_idx in 0..workers {
+            let rx = Arc::clone(&job_rx);
+            let batch = batch.clone();
+            let limiter = limiter.clone();
+            let client = client.clone();
+
+            let handle = thread::spawn(move || {
+                // Each worker gets its own RNG so retries don't line up across threads
+                let mut rng = XorShift64::from_time(worker_idx as u64);
+                let mut last_run = None;
+
+                // Process jobs until channel is closed
+                loop {
+                    // Hold the lock only while waiting for the next job
+                    let job = match rx.lock().unwrap().recv() {
+                        Ok(job) => job,
+                        Err(_) => break,
+                    };
+
+                    // Staggered start: spread each batch's first requests over time
+                    if last_run != Some(job.run) {
+                        last_run = Some(job.run);
+                        if !batch.stagger.is_zero() {
+                            thread::sleep(batch.stagger * worker_idx as u32);
                         }
-                        _ => break ws, // stop retrying on success or other error
                     }
-                };
 
-                // Send result back with original index
-                let _ = tx.send((idx, ws));
+                    // Batch already out of time: don't start a request nobody waits for
+                    if job.deadline.is_some_and(|d| Instant::now() >= d) {
+                        let _ = job.results.send((job.idx, deadline_result(&job.url, &job.method, &job.ts)));
+                        continue;
+                    }
+
+                    // Hold the host slot for the whole check, retries included.
+                    // URLs without a recognisable host aren't limited.
+                    let _slot = limiter.as_ref().and_then(|l| {
+                        parse_host_port(&job.url).map(|(host, _)| l.acquire(&host))
+                    });
+                    let ws = check_with_retries(&job, &batch, &client, &mut rng);
+
+                    // Send result back with original index
+                    let _ = job.results.send((job.idx, ws));
+                }
+            });
+            handles.push(handle);
+        }
+
+        Checker {
+            jobs: Some(job_tx),
+            handles,
+            next_run: AtomicU64::new(0),
+            fetch_timestamp: batch.cfg.fetch_timestamp,
+            timestamp_elapsed: Mutex::new(Duration::ZERO),
+            deadline: batch.deadline,
+            abandoned: AtomicBool::new(false),
+            method: batch.cfg.method.clone(),
+        }
+    }
+
+    /// How long getting the timestamp took in the most recent `run`.
+    pub fn last_timestamp_elapsed(&self) -> Duration {
+        *self.timestamp_elapsed.lock().unwrap()
+    }
+
+    /// Checks `urls` on the pool (with `Config::method`) and returns results in the same
+    /// order as the input.
+    pub fn run(&self, urls: Vec<String>) -> Vec<WebsiteStatus> {
+        self.run_targets(urls.into_iter().map(|u| (u, self.method.clone())).collect())
+    }
+
+    /// Like `run`, but each target is a (url, method) pair, e.g. the same URL
+    /// with "GET" and "HEAD". One result per target, in input order.
+    /// With a `deadline`, returns once it passes: checks still running or queued
+    /// are reported as `DEADLINE_EXCEEDED` transport errors.
+    pub fn run_targets(&self, targets: Vec<(String, String)>) -> Vec<WebsiteStatus> {
+        self.run_targets_timed(targets.into_iter().map(|(u, m)| (u, m, None)).collect())
+    }
+
+    /// Like `run_targets`, with an optional per-target request timeout that
+    /// replaces `cfg.timeout` for that target (e.g. 20s for one slow endpoint).
+    pub fn run_targets_timed(&self, targets: Vec<TimedTarget>) -> Vec<WebsiteStatus> {
+        let n = targets.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        let started = Instant::now();
+        let deadline = self.deadline.map(|d| started + d);
+        let jobs = self.jobs.as_ref().expect("checker is shutting down");
+        let run = self.next_run.fetch_add(1, Ordering::Relaxed);
+
+        // Fetch a single timestamp for the entire batch (shared across all threads)
+        let (ts, elapsed) = timestamp_utc_timed(self.fetch_timestamp);
+        *self.timestamp_elapsed.lock().unwrap() = elapsed;
+        let batch_ts = Arc::new(ts.unwrap_or_else(|_| "unknown".to_string()));
+
+        // Each run gets its own result channel (so runs never mix results) and request counter
+        let requests = Arc::new(AtomicUsize::new(0));
+        let (res_tx, res_rx) = mpsc::channel::<(usize, WebsiteStatus)>();
+
+        // Send jobs (URLs with their indices) to the workers
+        for (idx, (url, method, timeout)) in targets.iter().cloned().enumerate() {
+            let _ = jobs.send(Job {
+                run,
+                idx,
+                url,
+                method,
+                timeout,
+                ts: Arc::clone(&batch_ts),
+                deadline,
+                requests: Arc::clone(&requests),
+                results: res_tx.clone(),
+            });
+        }
+        drop(res_tx); // only the queued jobs hold senders now
+
+        // Collect results into a vector, preserving input order
+        let mut out: Vec<Option<WebsiteStatus>> = (0..n).map(|_| None).collect();
+        loop {
+            let received = match deadline {
+                Some(d) => res_rx.recv_timeout(d.saturating_duration_since(Instant::now())).ok(),
+                None => res_rx.recv().ok(),
+            };
+            let Some((idx, ws)) = received else {
+                break; // all results in, or out of time
+            };
+            out[idx] = Some(ws);
+        }
+
+        // Anything still missing was cut off by the deadline, or its worker died
+        // (e.g. a panicking `success_when`) and dropped the job without answering
+        let timed_out = deadline.is_some_and(|d| Instant::now() >= d);
+        if timed_out && out.iter().any(Option::is_none) {
+            self.abandoned.store(true, Ordering::Relaxed);
+        }
+        out.into_iter()
+            .zip(targets)
+            .map(|(o, (url, method, _))| {
+                o.unwrap_or_else(|| {
+                    let reason = if timed_out { DEADLINE_EXCEEDED } else { WORKER_LOST };
+                    WebsiteStatus {
+                        response_time: started.elapsed(), // waited this long before giving up
+                        ..WebsiteStatus::not_sent(&url, &method, &batch_ts, reason)
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+impl Drop for Checker {
+    fn drop(&mut self) {
+        drop(self.jobs.take()); // close job channel so workers stop when done
+
+        // After a deadline cut a batch short, don't block on its stragglers:
+        // the workers exit on their own once their current request finishes.
+        if self.abandoned.load(Ordering::Relaxed) {
+            return;
+        }
+
+        // Wait for all threads to finish
+        for h in self.handles.drain(..) {
+            let _ = h.join();
+        }
+    }
+}
+
+// Reason recorded for checks skipped because the batch used up `max_total_requests`
+pub const BUDGET_EXHAUSTED: &str = "request budget exhausted";
+
+// Reason recorded for checks that didn't finish before the batch `deadline`
+pub const DEADLINE_EXCEEDED: &str = "batch deadline exceeded";
+
+// Reason recorded for checks whose worker thread stopped before sending a result
+pub const WORKER_LOST: &str = "worker stopped before finishing the check";
+
+// Synthetic timeout for a check cut off by the batch deadline
+fn deadline_result(url: &str, method: &str, ts: &str) -> WebsiteStatus {
+    WebsiteStatus::not_sent(url, method, ts, DEADLINE_EXCEEDED)
+}
+
+// Claims one request from the batch budget; false once `max_total_requests` is used up
+fn take_request(batch: &BatchConfig, requests: &AtomicUsize) -> bool {
+    match batch.max_total_requests {
+        Some(max) => requests
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| (used < max).then_some(used + 1))
+            .is_ok(),
+        None => {
+            requests.fetch_add(1, Ordering::Relaxed);
+            true
+        }
+    }
+}
+
+// Checks one URL, retrying (with optional jittered delay) only on transport errors.
+// Every request, retries included, counts against the batch's request budget; when the
+// budget runs out the last result is kept (or a synthetic transport error if none was sent).
+fn check_with_retries(job: &Job, batch: &BatchConfig, client: &HttpClient, rng: &mut XorShift64) -> WebsiteStatus {
+    let cfg = &batch.cfg;
+    let (url, method, ts, requests) = (job.url.as_str(), job.method.as_str(), job.ts.as_str(), &*job.requests);
+    if !take_request(batch, requests) {
+        return WebsiteStatus::not_sent(url, method, ts, BUDGET_EXHAUSTED);
+    }
+    let mut attempts = 0usize;
+
+    // Retry loop: only retry on transport errors
+    loop {
+        let mut ws = WebsiteStatus::request_with_client(url, method, job.timeout, cfg, client, ts);
+        match ws.status {
+            CheckStatus::Transport(_) if attempts < batch.max_retries && take_request(batch, requests) => {
+                attempts += 1;
+                thread::sleep(jittered_delay(batch.retry_delay, batch.retry_jitter, rng));
+                continue; // retry on transport error
             }
-        });
-        handles.push(handle);
+            _ => {
+                ws.attempts = attempts as u32 + 1; // retries plus the first request
+                break ws; // stop retrying on success or other error
+            }
+        }
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_urls_lists_unhealthy_results_once() {
+        let with = |url: &str, status, healthy| WebsiteStatus {
+            status,
+            healthy,
+            ..WebsiteStatus::not_sent(url, "GET", "", "")
+        };
+        let results = vec![
+            with("https://ok.test", CheckStatus::Success(200), true),
+            with("https://down.test", CheckStatus::Transport("refused".into()), false),
+            with("https://invalid.test", CheckStatus::Success(200), false), // 200 but validation failed
+            with("https://err.test", CheckStatus::HttpError(500), false),
+            with("https://down.test", CheckStatus::Transport("refused".into()), false), // e.g. HEAD target
+            with("https://accepted.test", CheckStatus::HttpError(404), true), // healthy via custom rule
+            with("https://same.test", CheckStatus::Unchanged, true), // 304 to a conditional request
+        ];
+        assert_eq!(
+            failed_urls(&results),
+            vec!["https://down.test", "https://invalid.test", "https://err.test", "https://accepted.test"]
+        );
+
+        // Failed targets keep their method and timeout (both down.test targets are kept)
+        let slow = Some(Duration::from_secs(20));
+        let targets: Vec<TimedTarget> = results
+            .iter()
+            .enumerate()
+            .map(|(i, ws)| (ws.url.clone(), if i == 4 { "HEAD" } else { "GET" }.to_string(), slow.filter(|_| i == 3)))
+            .collect();
+        let failed = failed_targets(&targets, &results);
+        assert_eq!(failed.len(), 5);
+        assert_eq!(failed[2], ("https://err.test".to_string(), "GET".to_string(), slow));
+        assert_eq!(failed[3], ("https://down.test".to_string(), "HEAD".to_string(), None));
     }
-    drop(res_tx); // close extra result senders
 
-    // Send jobs (URLs with their indices) to the workers
-    for (i, url) in urls.into_iter().enumerate() {
-        let _ = job_tx.send((i, url));
+    #[test]
+    fn host_limiter_caps_each_host_separately() {
+        let limiter = HostLimiter::new(2);
+        let a1 = limiter.acquire("a.test");
+        let _a2 = limiter.acquire("a.test");
+        let _b1 = limiter.acquire("b.test"); // other hosts aren't affected
+        assert_eq!(limiter.in_flight.lock().unwrap()["a.test"], 2);
+
+        drop(a1);
+        assert_eq!(limiter.in_flight.lock().unwrap()["a.test"], 1);
+        let _a3 = limiter.acquire("a.test"); // freed slot is reusable without blocking
+        assert_eq!(limiter.in_flight.lock().unwrap()["a.test"], 2);
+    }
+
+    #[test]
+    fn auto_workers_scales_with_cores_and_urls() {
+        assert_eq!(auto_workers(1000, 4), 32); // capped by cores * 8
+        assert_eq!(auto_workers(10, 4), 10);   // capped by number of URLs
+        assert_eq!(auto_workers(0, 4), 1);     // never zero
+        assert_eq!(auto_workers(100, 0), 8);   // unknown cores treated as 1
     }
-    drop(job_tx); // close job sender so workers stop when done
 
-    // Collect results into a vector, preserving input order
-    let mut out: Vec<Option<WebsiteStatus>> = (0..n).map(|_| None).collect();
-    for (idx, ws) in res_rx.iter() {
-        out[idx] = Some(ws);
+    #[test]
+    fn jitter_off_returns_base_delay() {
+        let mut rng = XorShift64::new(42);
+        let base = Duration::from_millis(200);
+        for _ in 0..100 {
+            assert_eq!(jittered_delay(base, 0.0, &mut rng), base);
+        }
     }
 
-    // Wait for all threads to finish
-    for h in handles {
-        let _ = h.join();
+    #[test]
+    fn jittered_delays_stay_within_range() {
+        let mut rng = XorShift64::new(7);
+        let base = Duration::from_millis(1000);
+        let (lo, hi) = (Duration::from_millis(750), Duration::from_millis(1250));
+
+        let delays: Vec<Duration> = (0..1000).map(|_| jittered_delay(base, 0.25, &mut rng)).collect();
+        assert!(delays.iter().all(|d| *d >= lo && *d <= hi), "delay outside ±25%");
+        // Delays should actually be spread out, not all identical
+        assert!(delays.iter().any(|d| *d < base) && delays.iter().any(|d| *d > base));
     }
 
-    // Convert results from Option back to concrete WebsiteStatus
-    out.into_iter().map(|o| o.expect("missing result")).collect()
+    #[test]
+    fn interval_jitter_stays_within_bounds() {
+        let interval = Duration::from_secs(30);
+        let jitter = Duration::from_secs(10);
+        let mut rng = XorShift64::new(7);
+        let sleeps: Vec<Duration> = (0..1000).map(|_| interval_with_jitter(interval, jitter, &mut rng)).collect();
+        assert!(sleeps.iter().all(|d| *d >= interval && *d <= interval + jitter), "sleep outside [30s, 40s]");
+        // Actually spread out, not stuck at either end
+        assert!(sleeps.iter().any(|d| *d < interval + jitter / 2));
+        assert!(sleeps.iter().any(|d| *d > interval + jitter / 2));
+
+        // Same seed => same schedule; no jitter => plain interval
+        let mut again = XorShift64::new(7);
+        assert_eq!(interval_with_jitter(interval, jitter, &mut again), sleeps[0]);
+        assert_eq!(interval_with_jitter(interval, Duration::ZERO, &mut again), interval);
+    }
 }
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use crate::status::{CheckStatus, WebsiteStatus};
+
+// Smoothed response time per URL across monitoring cycles (exponential moving average).
+// Only successful checks feed it, so timeouts and connection errors don't dominate.
+#[derive(Debug, Clone)]
+pub struct EmaTracker {
+    alpha: f64,                     // weight of the newest sample, in (0, 1]
+    averages: HashMap<String, f64>, // URL -> smoothed response time (ms)
+}
+
+impl EmaTracker {
+    /// New tracker; `alpha` is clamped to (0, 1] (higher = reacts faster).
+    pub fn new(alpha: f64) -> Self {
+        EmaTracker {
+            alpha: alpha.clamp(f64::EPSILON, 1.0),
+            averages: HashMap::new(),
+        }
+    }
+
+    /// Folds one cycle's successful results in: `ema = alpha * ms + (1 - alpha) * ema`.
+    /// A URL's first sample becomes its starting average.
+    pub fn update(&mut self, results: &[WebsiteStatus]) {
+        fold_response_times(&mut self.averages, results, self.alpha, |status| {
+            status.is_success()
+        });
+    }
+
+    /// Current average for `url` in ms, if it has had a successful check.
+    pub fn get(&self, url: &str) -> Option<f64> {
+        self.averages.get(url).copied()
+    }
+
+    /// Drops the average of `url`, e.g. after it was removed from the URL list.
+    pub fn forget(&mut self, url: &str) {
+        self.averages.remove(url);
+    }
+
+    /// All (URL, average ms) pairs, sorted by URL.
+    pub fn snapshot(&self) -> Vec<(String, f64)> {
+        let mut out: Vec<(String, f64)> = self.averages.iter().map(|(u, v)| (u.clone(), *v)).collect();
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out
+    }
+}
+
+/// Folds the response time (ms) of each result whose status passes `counts` into
+/// `averages` (keyed by URL): `ema = alpha * ms + (1 - alpha) * ema`, with `alpha`
+/// clamped to [0, 1]. A URL's first sample becomes its starting average.
+/// The one EMA rule behind `EmaTracker` and `LatencyBaseline::update_ema`.
+pub fn fold_response_times(
+    averages: &mut HashMap<String, f64>,
+    results: &[WebsiteStatus],
+    alpha: f64,
+    counts: impl Fn(&CheckStatus) -> bool,
+) {
+    let alpha = alpha.clamp(0.0, 1.0);
+    for ws in results.iter().filter(|ws| counts(&ws.status)) {
+        let ms = ws.response_time.as_secs_f64() * 1000.0;
+        averages
+            .entry(ws.url.clone())
+            .and_modify(|ema| *ema = alpha * ms + (1.0 - alpha) * *ema)
+            .or_insert(ms);
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn result(url: &str, status: CheckStatus, ms: u64) -> WebsiteStatus {
+        WebsiteStatus {
+            status,
+            response_time: Duration::from_millis(ms),
+            ..WebsiteStatus::not_sent(url, "GET", "", "")
+        }
+    }
+
+    #[test]
+    fn converges_toward_a_steady_input_and_ignores_failures() {
+        let mut ema = EmaTracker::new(0.5);
+        ema.update(&[result("https://a.test", CheckStatus::Success(200), 1000)]);
+        assert_eq!(ema.get("https://a.test"), Some(1000.0));
+
+        // Steady 200 ms: the distance to 200 halves every cycle
+        let mut previous_gap = 800.0;
+        for _ in 0..10 {
+            ema.update(&[
+                result("https://a.test", CheckStatus::Success(200), 200),
+                result("https://a.test", CheckStatus::Transport("timed out".into()), 5000),
+                result("https://c.test", CheckStatus::Unchanged, 100), // a 304 is a real round trip
+            ]);
+            let gap = ema.get("https://a.test").unwrap() - 200.0;
+            assert!((gap - previous_gap / 2.0).abs() < 1e-9);
+            previous_gap = gap;
+        }
+        assert!(previous_gap < 1.0);
+        assert_eq!(ema.get("https://c.test"), Some(100.0));
+
+        // Non-successful results never start an average
+        ema.update(&[result("https://b.test", CheckStatus::HttpError(500), 30)]);
+        assert_eq!(ema.get("https://b.test"), None);
+        assert_eq!(ema.snapshot().len(), 2);
+
+        ema.forget("https://a.test");
+        assert_eq!(ema.get("https://a.test"), None);
+    }
+}
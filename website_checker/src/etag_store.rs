@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// Shared between the worker threads of a batch (`Config::etags`): checks read the
+// last ETag of their target and record the new one from each 200 response.
+pub type SharedEtags = Arc<Mutex<EtagStore>>;
+
+// Remembers each (URL, method) target's last ETag between runs (in a small JSON file),
+// so checks can send `If-None-Match` and a 304 answer means the page is unchanged.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EtagStore {
+    #[serde(skip)]
+    path: PathBuf,                  // where the store is persisted
+    etags: HashMap<String, String>, // target key (see `key`) -> last seen ETag
+}
+
+// Map key for a (URL, method) target, as in `FingerprintStore`: the bare URL for GET
+// and "METHOD URL" for anything else, so a HEAD check can't replace a GET's ETag
+fn key(url: &str, method: &str) -> String {
+    if method == "GET" { url.to_string() } else { format!("{} {}", method, url) }
+}
+
+impl EtagStore {
+    /// Loads the store from `path`, or starts empty if the file doesn't exist yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref().to_path_buf();
+        let mut store = if path.exists() {
+            let text = fs::read_to_string(&path)?;
+            serde_json::from_str::<EtagStore>(&text)?
+        } else {
+            EtagStore::default()
+        };
+        store.path = path;
+        Ok(store)
+    }
+
+    /// Wraps the store for use in `Config::etags`.
+    pub fn shared(self) -> SharedEtags {
+        Arc::new(Mutex::new(self))
+    }
+
+    /// Writes the current ETags back to the file.
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        fs::write(&self.path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Last known ETag for `url` checked with `method`, if any.
+    pub fn get(&self, url: &str, method: &str) -> Option<&str> {
+        self.etags.get(&key(url, method)).map(String::as_str)
+    }
+
+    /// Drops the ETags of `url` (every method), e.g. after it was removed from the URL list,
+    /// so its next check is a full request again.
+    pub fn forget(&mut self, url: &str) {
+        // Keys are "URL" or "METHOD URL" (see `key`); URLs contain no spaces
+        self.etags.retain(|k, _| k.rsplit(' ').next() != Some(url));
+    }
+
+    /// Records `etag` as the current version of `url` checked with `method`.
+    pub fn set(&mut self, url: &str, method: &str, etag: &str) {
+        self.etags.insert(key(url, method), etag.to_string());
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn etags_survive_a_save_and_load() {
+        let path = std::env::temp_dir().join(format!("wc_etags_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut store = EtagStore::load(&path).unwrap();
+        assert_eq!(store.get("https://a.test", "GET"), None);
+        store.set("https://a.test", "GET", "\"v1\"");
+        store.set("https://a.test", "GET", "\"v2\""); // newer version replaces the old one
+        store.set("https://a.test", "HEAD", "\"h1\""); // other methods are kept apart
+        store.save().unwrap();
+
+        let reloaded = EtagStore::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(reloaded.get("https://a.test", "GET"), Some("\"v2\""));
+        assert_eq!(reloaded.get("https://a.test", "HEAD"), Some("\"h1\""));
+    }
+}
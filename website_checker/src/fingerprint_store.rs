@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::status::WebsiteStatus;
+
+// Remembers each URL's body fingerprint between runs (in a small JSON file)
+// and reports which pages changed since last time: a lightweight defacement detector.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FingerprintStore {
+    #[serde(skip)]
+    path: PathBuf,                      // where the store is persisted
+    fingerprints: HashMap<String, String>, // target key (see `key`) -> last seen fingerprint
+}
+
+// Map key for a (URL, method) target: the bare URL for GET, so stores written before
+// methods were tracked stay valid, and "METHOD URL" for anything else
+fn key(url: &str, method: &str) -> String {
+    if method == "GET" { url.to_string() } else { format!("{} {}", method, url) }
+}
+
+impl FingerprintStore {
+    /// Loads the store from `path`, or starts empty if the file doesn't exist yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref().to_path_buf();
+        let mut store = if path.exists() {
+            let text = fs::read_to_string(&path)?;
+            serde_json::from_str::<FingerprintStore>(&text)?
+        } else {
+            FingerprintStore::default()
+        };
+        store.path = path;
+        Ok(store)
+    }
+
+    /// Writes the current fingerprints back to the file.
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        fs::write(&self.path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Records this cycle's fingerprints and returns the (URL, method) targets whose
+    /// fingerprint differs from the stored one. Targets seen for the first time are
+    /// recorded without alerting; results without a fingerprint are ignored.
+    pub fn update(&mut self, results: &[WebsiteStatus]) -> Vec<(String, String)> {
+        let mut changed = Vec::new();
+        for ws in results {
+            let Some(fp) = &ws.body_fingerprint else {
+                continue;
+            };
+            match self.fingerprints.insert(key(&ws.url, &ws.method), fp.clone()) {
+                Some(previous) if &previous != fp => changed.push((ws.url.clone(), ws.method.clone())),
+                _ => {} // unchanged, or first time we see this target (baseline)
+            }
+        }
+        changed
+    }
+
+    /// Drops the fingerprints of `url` (every method), e.g. after it was removed from the URL list.
+    pub fn forget(&mut self, url: &str) {
+        // Keys are "URL" or "METHOD URL" (see `key`); URLs contain no spaces
+        self.fingerprints.retain(|k, _| k.rsplit(' ').next() != Some(url));
+    }
+
+    /// Last known fingerprint for `url` checked with `method`, if any.
+    pub fn get(&self, url: &str, method: &str) -> Option<&str> {
+        self.fingerprints.get(&key(url, method)).map(String::as_str)
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::CheckStatus;
+    use std::time::Duration;
+
+    fn result(url: &str, fp: &str) -> WebsiteStatus {
+        WebsiteStatus {
+            status: CheckStatus::Success(200),
+            response_time: Duration::from_millis(5),
+            body_fingerprint: Some(fp.into()),
+            healthy: true,
+            attempts: 1,
+            ..WebsiteStatus::not_sent(url, "GET", "2020-01-01T00:00:00Z", "")
+        }
+    }
+
+    #[test]
+    fn flags_only_changed_urls_across_runs() {
+        let path = std::env::temp_dir().join(format!("wc_fingerprints_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        // First run: no baseline yet, so nothing is flagged
+        let mut store = FingerprintStore::load(&path).unwrap();
+        let first = [result("https://a.test", "111"), result("https://b.test", "222")];
+        assert!(store.update(&first).is_empty());
+        store.save().unwrap();
+
+        // Second run (fresh process): b.test changed
+        let mut store = FingerprintStore::load(&path).unwrap();
+        assert_eq!(store.get("https://a.test", "GET"), Some("111"));
+        let second = [result("https://a.test", "111"), result("https://b.test", "999")];
+        assert_eq!(store.update(&second), vec![("https://b.test".to_string(), "GET".to_string())]);
+        assert_eq!(store.get("https://b.test", "GET"), Some("999"));
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn methods_of_one_url_are_tracked_separately() {
+        let mut store = FingerprintStore::default();
+        let post = |fp| WebsiteStatus { method: "POST".into(), ..result("https://a.test", fp) };
+
+        // GET and POST return different bodies every cycle: neither counts as a change
+        assert!(store.update(&[result("https://a.test", "111"), post("222")]).is_empty());
+        assert!(store.update(&[result("https://a.test", "111"), post("222")]).is_empty());
+
+        // Only the POST body changes
+        assert_eq!(
+            store.update(&[result("https://a.test", "111"), post("333")]),
+            vec![("https://a.test".to_string(), "POST".to_string())]
+        );
+        assert_eq!(store.get("https://a.test", "GET"), Some("111"));
+        assert_eq!(store.get("https://a.test", "POST"), Some("333"));
+
+        store.update(&[result("https://b.test", "444")]);
+        store.forget("https://a.test");
+        assert_eq!(store.get("https://a.test", "GET"), None);
+        assert_eq!(store.get("https://a.test", "POST"), None);
+        assert_eq!(store.get("https://b.test", "GET"), Some("444"));
+    }
+}
@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ema::fold_response_times;
+use crate::status::{CheckStatus, WebsiteStatus};
+
+// Expected response time per URL, kept in a JSON file like {"https://a.test": 120.0}.
+// Used to flag latency regressions (a URL much slower than usual).
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LatencyBaseline {
+    #[serde(skip)]
+    path: PathBuf,                    // where the baseline is persisted
+    expected_ms: HashMap<String, f64>, // URL -> expected response time (ms)
+}
+
+// Weight of the newest run when updating the baseline (see `update_ema`)
+pub const BASELINE_EMA_ALPHA: f64 = 0.2;
+
+// A result that was more than `factor` times slower than its baseline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyRegression {
+    pub url: String,
+    pub actual_ms: u128,
+    pub baseline_ms: f64,
+}
+
+impl LatencyBaseline {
+    /// Loads the baseline from `path`, or starts empty if the file doesn't exist yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref().to_path_buf();
+        let mut baseline = if path.exists() {
+            let text = fs::read_to_string(&path)?;
+            serde_json::from_str::<LatencyBaseline>(&text)?
+        } else {
+            LatencyBaseline::default()
+        };
+        baseline.path = path;
+        Ok(baseline)
+    }
+
+    /// Writes the current baseline back to the file.
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        fs::write(&self.path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Expected response time for `url` in ms, if known.
+    pub fn get(&self, url: &str) -> Option<f64> {
+        self.expected_ms.get(url).copied()
+    }
+
+    /// Drops the baseline of `url`, e.g. after it was removed from the URL list.
+    pub fn forget(&mut self, url: &str) {
+        self.expected_ms.remove(url);
+    }
+
+    /// Results slower than `baseline * factor`. URLs without a baseline and
+    /// transport errors (their time is the timeout, not latency) are skipped.
+    pub fn regressions(&self, results: &[WebsiteStatus], factor: f64) -> Vec<LatencyRegression> {
+        results
+            .iter()
+            .filter(|ws| !matches!(ws.status, CheckStatus::Transport(_)))
+            .filter_map(|ws| {
+                let baseline_ms = self.get(&ws.url)?;
+                let actual_ms = ws.response_time.as_millis();
+                (actual_ms as f64 > baseline_ms * factor).then(|| LatencyRegression {
+                    url: ws.url.clone(),
+                    actual_ms,
+                    baseline_ms,
+                })
+            })
+            .collect()
+    }
+
+    /// Folds this run into the baseline as an exponential moving average:
+    /// `new = alpha * actual + (1 - alpha) * old`. New URLs start at their measured time.
+    /// Transport errors are skipped, as in `regressions`.
+    pub fn update_ema(&mut self, results: &[WebsiteStatus], alpha: f64) {
+        fold_response_times(&mut self.expected_ms, results, alpha, |status| {
+            !matches!(status, CheckStatus::Transport(_))
+        });
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn result(url: &str, status: CheckStatus, ms: u64) -> WebsiteStatus {
+        WebsiteStatus {
+            status,
+            response_time: Duration::from_millis(ms),
+            healthy: true,
+            attempts: 1,
+            ..WebsiteStatus::not_sent(url, "GET", "2020-01-01T00:00:00Z", "")
+        }
+    }
+
+    #[test]
+    fn flags_results_over_the_factor_and_updates_the_average() {
+        let path = std::env::temp_dir().join(format!("wc_baseline_{}.json", std::process::id()));
+        fs::write(&path, r#"{"https://a.test": 100.0, "https://b.test": 50.0}"#).unwrap();
+        let mut baseline = LatencyBaseline::load(&path).unwrap();
+
+        let results = [
+            result("https://a.test", CheckStatus::Success(200), 350),       // 3.5x: regression
+            result("https://b.test", CheckStatus::HttpError(500), 140),     // 2.8x: fine
+            result("https://c.test", CheckStatus::Success(200), 900),       // no baseline yet
+            result("https://b.test", CheckStatus::Transport("timeout".into()), 5000), // ignored
+        ];
+        assert_eq!(
+            baseline.regressions(&results, 3.0),
+            vec![LatencyRegression {
+                url: "https://a.test".into(),
+                actual_ms: 350,
+                baseline_ms: 100.0,
+            }]
+        );
+
+        baseline.update_ema(&results, 0.2);
+        assert!((baseline.get("https://a.test").unwrap() - 150.0).abs() < 1e-9);
+        assert!((baseline.get("https://b.test").unwrap() - 68.0).abs() < 1e-9);
+        assert_eq!(baseline.get("https://c.test"), Some(900.0));
+
+        // Round-trips through the file
+        baseline.save().unwrap();
+        let reloaded = LatencyBaseline::load(&path).unwrap();
+        assert_eq!(reloaded.get("https://c.test"), Some(900.0));
+        let _ = fs::remove_file(&path);
+
+        baseline.forget("https://c.test");
+        assert_eq!(baseline.get("https://c.test"), None);
+    }
+}
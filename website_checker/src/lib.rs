@@ -6,6 +6,9 @@ pub mod status;
 // Provides input and data validation functions
 pub mod validation;
 
+// ANSI coloring of check results for console output
+pub mod color;
+
 // Utilities for working with time and timestamps
 pub mod time_utils;
 
@@ -14,3 +17,67 @@ pub mod concurrent;
 
 // Collects and reports statistics
 pub mod stats;
+
+// Loads URL lists (plain text or JSON with names and tags)
+pub mod url_list;
+
+// Small seedable random number generator (jitter, sampling)
+pub mod rng;
+
+// TLS connector that reports the negotiated version and cipher
+pub mod tls;
+
+// Persists body fingerprints between runs to detect content changes
+pub mod fingerprint_store;
+
+// Persists ETags between runs for conditional (If-None-Match) requests
+pub mod etag_store;
+
+// Per-URL exponential moving average of response times across cycles
+pub mod ema;
+
+// Expected response time per URL, for flagging latency regressions
+pub mod latency_baseline;
+
+// Saves run results as JSON snapshots and diffs two snapshots
+pub mod snapshot;
+
+// Tracks consecutive failures per URL for alerting
+pub mod alerts;
+
+// Optional HTTP endpoint exposing the latest Stats (JSON and Prometheus)
+pub mod stats_server;
+
+// Tiny leveled logger used by the binary
+pub mod logger;
+
+// Output destinations for results and summaries (stdout, file, ...)
+pub mod sink;
+
+// Renders results as a minimal HTML status page
+pub mod status_page;
+
+use concurrent::{check_many_with, BatchConfig};
+use stats::Stats;
+use status::WebsiteStatus;
+use validation::Config;
+
+/// Checks every URL once (concurrently, retrying transport errors `retries` times)
+/// and returns the results in input order together with their summary.
+pub fn run_checks(urls: Vec<String>, workers: usize, retries: usize, cfg: Config) -> (Vec<WebsiteStatus>, Stats) {
+    let batch = BatchConfig {
+        workers,
+        max_retries: retries,
+        cfg,
+        ..BatchConfig::default()
+    };
+    run_checks_with(urls, &batch)
+}
+
+/// `run_checks` with full batch options. With `batch.deadline` set it returns within
+/// that wall-clock time: unfinished checks count as transport errors in the Stats.
+pub fn run_checks_with(urls: Vec<String>, batch: &BatchConfig) -> (Vec<WebsiteStatus>, Stats) {
+    let results = check_many_with(urls, batch);
+    let stats = Stats::compute(&results);
+    (results, stats)
+}
@@ -0,0 +1,131 @@
+use std::fmt;
+use std::io::{self, Stderr, Stdout, Write};
+
+// Log levels, from least to most verbose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    /// Parses "error", "warn", "info" or "debug" (case-insensitive).
+    pub fn from_name(name: &str) -> Option<Level> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "warn" | "warning" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Minimal leveled logger: writes messages at or below `level`.
+// Errors and warnings go to `err` with an "ERROR: "/"WARN: " prefix on every line;
+// info and debug messages go to `out` as-is so reports stay readable.
+pub struct Logger<W: Write, E: Write = W> {
+    level: Level,
+    out: W,
+    err: E,
+}
+
+impl Logger<Stdout, Stderr> {
+    /// Console logger: reports on stdout, errors and warnings on stderr.
+    pub fn console(level: Level) -> Self {
+        Logger::new(level, io::stdout(), io::stderr())
+    }
+}
+
+impl<W: Write, E: Write> Logger<W, E> {
+    pub fn new(level: Level, out: W, err: E) -> Self {
+        Logger { level, out, err }
+    }
+
+    /// True if messages at `level` would be written.
+    pub fn enabled(&self, level: Level) -> bool {
+        level <= self.level
+    }
+
+    /// Writes `msg` if `level` is enabled. Write failures are ignored (logging never aborts a run).
+    pub fn log(&mut self, level: Level, msg: &str) {
+        if !self.enabled(level) {
+            return;
+        }
+        for line in msg.lines() {
+            let _ = match level {
+                Level::Error | Level::Warn => writeln!(self.err, "{}: {}", level, line),
+                Level::Info | Level::Debug => writeln!(self.out, "{}", line),
+            };
+        }
+    }
+
+    pub fn error(&mut self, msg: &str) {
+        self.log(Level::Error, msg);
+    }
+
+    pub fn warn(&mut self, msg: &str) {
+        self.log(Level::Warn, msg);
+    }
+
+    pub fn info(&mut self, msg: &str) {
+        self.log(Level::Info, msg);
+    }
+
+    pub fn debug(&mut self, msg: &str) {
+        self.log(Level::Debug, msg);
+    }
+
+    /// Gives back the writers as (out, err), e.g. to inspect captured output in tests.
+    pub fn into_inner(self) -> (W, E) {
+        (self.out, self.err)
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (out, err) as written at `level`
+    fn capture(level: Level) -> (String, String) {
+        let mut log = Logger::new(level, Vec::new(), Vec::new());
+        log.error("disk full");
+        log.warn("timeout for https://a.test\nretrying");
+        log.info("=== Summary ===");
+        log.debug("URL: https://a.test");
+        let (out, err) = log.into_inner();
+        (String::from_utf8(out).unwrap(), String::from_utf8(err).unwrap())
+    }
+
+    #[test]
+    fn output_depends_on_level() {
+        assert_eq!(capture(Level::Error), (String::new(), "ERROR: disk full\n".to_string()));
+        let problems = "ERROR: disk full\nWARN: timeout for https://a.test\nWARN: retrying\n";
+        assert_eq!(capture(Level::Warn), (String::new(), problems.to_string()));
+        assert_eq!(capture(Level::Info), ("=== Summary ===\n".to_string(), problems.to_string()));
+        assert_eq!(capture(Level::Debug).0, "=== Summary ===\nURL: https://a.test\n");
+    }
+
+    #[test]
+    fn parses_level_names() {
+        assert_eq!(Level::from_name("WARN"), Some(Level::Warn));
+        assert_eq!(Level::from_name("debug"), Some(Level::Debug));
+        assert_eq!(Level::from_name("loud"), None);
+        assert!(Level::Error < Level::Debug);
+    }
+}
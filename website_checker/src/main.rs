@@ -1,59 +1,813 @@
 use std::error::Error;
-use std::fs;
+use std::io::{Stderr, Stdout, Write};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
-use website_checker::concurrent;
+use website_checker::alerts::FailureStreaks;
+use website_checker::color::ColorMode;
+use website_checker::concurrent::{auto_workers_for, failed_targets, interval_with_jitter, BatchConfig, Checker, TimedTarget};
+use website_checker::etag_store::{EtagStore, SharedEtags};
+use website_checker::fingerprint_store::FingerprintStore;
+use website_checker::latency_baseline::{LatencyBaseline, BASELINE_EMA_ALPHA};
+use website_checker::logger::{Level, Logger};
+use website_checker::rng::XorShift64;
+use website_checker::sink::{FileSink, MultiSink, OutputSink, StdoutSink, WebhookSink};
+use website_checker::snapshot::{read_snapshot, write_snapshot, SnapshotDiff};
 use website_checker::stats::Stats; // stats module for computing summaries
+use website_checker::status::{CheckStatus, RenderOptions, WebsiteStatus};
+use website_checker::time_utils::{slow_time_warning, TimestampFormat, SLOW_TIME_API};
+use website_checker::stats_server::{self, SharedStats};
+use website_checker::status_page::render_status_html;
+use website_checker::validation::{Config, IpVersion};
+use website_checker::url_list::{
+    diff_url_lists, merge_url_entries, read_urls_from_file_checked, read_urls_from_json, ListWatcher, UrlEntry,
+    UrlListError,
+};
 
-// Reads URLs from a text file, ignoring empty lines and comments.
-// Returns a vector of strings with cleaned URLs.
-fn read_urls_from_file(path: &str) -> Result<Vec<String>, Box<dyn Error>> {
-    let text = fs::read_to_string(path)?;
-    Ok(text
-        .lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty() && !l.starts_with('#'))
-        .map(|s| s.to_string())
-        .collect())
+// How many of the most common validation issues the summary lists
+const TOP_ISSUES: usize = 5;
+
+// Returns the value following `flag` on the command line (e.g. `--json list.json`).
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+// Keeps only entries carrying `tag` (all entries when no tag is given).
+// Returns the selected entries and how many were skipped.
+fn filter_by_tag(entries: Vec<UrlEntry>, tag: Option<&str>) -> (Vec<UrlEntry>, usize) {
+    let Some(tag) = tag else {
+        return (entries, 0);
+    };
+    let total = entries.len();
+    let selected: Vec<UrlEntry> = entries
+        .into_iter()
+        .filter(|e| e.tags.iter().any(|t| t == tag))
+        .collect();
+    let skipped = total - selected.len();
+    (selected, skipped)
+}
+
+// URL list used unless `--json` is given
+const DEFAULT_LIST: &str = "src/website_list.txt";
+
+// Reads the text list at `path` (plus an optional overlay list merged on top, whose
+// line options win), warning about skipped lines. Options after a URL (`timeout=N`) are kept.
+fn load_text_list<W: Write, E: Write>(path: &str, overlay: Option<&str>, log: &mut Logger<W, E>) -> Result<Vec<UrlEntry>, UrlListError> {
+    let (entries, skipped) = read_urls_from_file_checked(path)?;
+    for (line, reason) in &skipped {
+        log.warn(&format!("Skipping {} line {}: {}", path, line, reason));
+    }
+    let Some(overlay_path) = overlay else {
+        return Ok(entries);
+    };
+    let (overlay, skipped) = read_urls_from_file_checked(overlay_path)?;
+    for (line, reason) in &skipped {
+        log.warn(&format!("Skipping {} line {}: {}", overlay_path, line, reason));
+    }
+    Ok(merge_url_entries(&entries, &overlay))
+}
+
+// One check per (url, method); entries without `methods` are checked with GET
+// (a `timeout=N` line option or `timeout_secs` JSON field overrides the request timeout)
+fn check_targets(entries: &[UrlEntry]) -> (Vec<&UrlEntry>, Vec<TimedTarget>) {
+    entries
+        .iter()
+        .flat_map(|e| e.targets().into_iter().map(move |(url, method)| (e, (url, method, e.timeout()))))
+        .unzip()
+}
+
+// Re-reads the text URL list when its modification time (or the overlay's) changes,
+// applying the same overlay, `--tag` filter and `--sample` (same seed) as at startup.
+struct ListReload {
+    watcher: ListWatcher,
+    overlay: Option<ListWatcher>,
+    tag: Option<String>,
+    sample: Option<usize>,
+    seed: u64,
+}
+
+impl ListReload {
+    // Called once per cycle. Returns the new entries and the removed URLs if either list
+    // changed, after logging the added/removed URLs (shown as `render` asks). A list that
+    // can't be read (or ends up empty) is reported and the current entries are kept.
+    fn poll<W: Write, E: Write>(
+        &mut self,
+        current: &[UrlEntry],
+        render: RenderOptions,
+        log: &mut Logger<W, E>,
+    ) -> Option<(Vec<UrlEntry>, Vec<String>)> {
+        // Ask both watchers, so each remembers the version it has now seen
+        let base_changed = self.watcher.changed();
+        let overlay_changed = self.overlay.as_mut().is_some_and(ListWatcher::changed);
+        if !base_changed && !overlay_changed {
+            return None;
+        }
+        let overlay = self.overlay.as_ref().map(|w| w.path.as_str());
+        let entries = match load_text_list(&self.watcher.path, overlay, log) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log.warn(&format!("Keeping the current URL list: {}", e));
+                return None;
+            }
+        };
+        let (entries, _) = filter_by_tag(entries, self.tag.as_deref());
+        let entries = match self.sample {
+            Some(n) => XorShift64::new(self.seed).sample(entries, n),
+            None => entries,
+        };
+        if entries.is_empty() {
+            log.warn(&format!("{} has no URLs to check; keeping the current list", self.watcher.path));
+            return None;
+        }
+
+        let old: Vec<String> = current.iter().map(|e| e.url.clone()).collect();
+        let new: Vec<String> = entries.iter().map(|e| e.url.clone()).collect();
+        let (added, removed) = diff_url_lists(&old, &new);
+        log.info(&format!("Reloaded {}: {} added, {} removed", self.watcher.path, added.len(), removed.len()));
+        for url in &added {
+            log.info(&format!("  + {}", render.url(url)));
+        }
+        for url in &removed {
+            log.info(&format!("  - {}", render.url(url)));
+        }
+        Some((entries, removed))
+    }
+}
+
+// Drops what is remembered about URLs removed from the list (alert streaks, page
+// fingerprints, ETags, latency baseline), so one that is added back later starts over
+fn forget_removed(
+    removed: &[String],
+    streaks: &mut FailureStreaks,
+    fingerprints: Option<&mut FingerprintStore>,
+    etags: Option<&SharedEtags>,
+    baseline: Option<&mut LatencyBaseline>,
+) {
+    for url in removed {
+        streaks.forget(url);
+    }
+    if let Some(store) = fingerprints {
+        removed.iter().for_each(|url| store.forget(url));
+    }
+    if let Some(Ok(mut store)) = etags.map(|store| store.lock()) {
+        removed.iter().for_each(|url| store.forget(url));
+    }
+    if let Some(baseline) = baseline {
+        removed.iter().for_each(|url| baseline.forget(url));
+    }
+}
+
+// Worker threads for `url_count` URLs: `--workers N` (None = 50, 0 = by CPU count),
+// never more than there are URLs
+fn worker_count(setting: Option<usize>, url_count: usize) -> usize {
+    match setting {
+        None => url_count.min(50),
+        Some(0) => auto_workers_for(url_count),
+        Some(n) => n.min(url_count),
+    }
+}
+
+// A (URL, method) target as logged: "HEAD https://..." etc., just the URL for GET
+fn shown_target(url: &str, method: &str, render: RenderOptions) -> String {
+    let shown = render.url(url);
+    if method == "GET" { shown } else { format!("{} {}", method, shown) }
+}
+
+// Which `--once` outcome exits nonzero (`--exit-on degraded|any-failure|never`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExitOn {
+    Degraded,   // only when `--min-uptime` / `--max-avg-ms` are crossed
+    AnyFailure, // any unhealthy result, or a degraded batch (default)
+    Never,      // always exit 0
+}
+
+impl ExitOn {
+    fn from_name(name: &str) -> Option<ExitOn> {
+        match name {
+            "degraded" => Some(ExitOn::Degraded),
+            "any-failure" => Some(ExitOn::AnyFailure),
+            "never" => Some(ExitOn::Never),
+            _ => None,
+        }
+    }
+}
+
+// Exit code for a `--once` run: `failure_code` when the `exit_on` condition holds, else 0.
+// The batch counts as degraded only if at least one threshold is set and crossed.
+fn once_exit_code(
+    exit_on: ExitOn,
+    failure_code: i32,
+    results: &[WebsiteStatus],
+    stats: &Stats,
+    min_uptime: Option<f64>,
+    max_avg_ms: Option<f64>,
+) -> i32 {
+    let degraded = (min_uptime.is_some() || max_avg_ms.is_some())
+        && stats.is_degraded(min_uptime.unwrap_or(0.0), max_avg_ms.unwrap_or(f64::INFINITY));
+    let failed = match exit_on {
+        ExitOn::Degraded => degraded,
+        ExitOn::AnyFailure => degraded || results.iter().any(|ws| !ws.healthy),
+        ExitOn::Never => false,
+    };
+    if failed { failure_code } else { 0 }
+}
+
+// `--diff old.json new.json [--threshold-ms N] [--format json]`: compares two snapshots and exits
+fn run_diff(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let i = args.iter().position(|a| a == "--diff").unwrap_or(0);
+    let (Some(old), Some(new)) = (args.get(i + 1), args.get(i + 2)) else {
+        return Err("Usage: --diff <old.json> <new.json> [--threshold-ms N] [--format json]".into());
+    };
+    let threshold_ms = match arg_value(args, "--threshold-ms") {
+        Some(v) => v
+            .parse::<u64>()
+            .map_err(|_| format!("Invalid --threshold-ms value: {}", v))?,
+        None => 100,
+    };
+    let diff = SnapshotDiff::compute(&read_snapshot(old)?, &read_snapshot(new)?, threshold_ms);
+    if arg_value(args, "--format").as_deref() == Some("json") {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        diff.print();
+    }
+    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    // Load the list of websites once at startup
-    let urls: Vec<String> = read_urls_from_file("src/website_list.txt")?;
-    if urls.is_empty() {
-        eprintln!("No URLs found in src/website_list.txt");
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.iter().any(|a| a == "--diff") {
+        return run_diff(&args);
+    }
+
+    // `--verbosity error|warn|info|debug` (default debug: full per-URL reports)
+    let level = match arg_value(&args, "--verbosity") {
+        Some(v) => Level::from_name(&v).ok_or_else(|| format!("Invalid --verbosity value: {}", v))?,
+        None => Level::Debug,
+    };
+    let mut log: Logger<Stdout, Stderr> = Logger::console(level);
+
+    // Load the list of websites once at startup (JSON with names/tags if --json is given)
+    let (source, entries): (String, Vec<UrlEntry>) = match arg_value(&args, "--json") {
+        Some(path) => {
+            let entries = read_urls_from_json(&path)?;
+            (path, entries)
+        }
+        None => {
+            let path = DEFAULT_LIST.to_string();
+            // `--overlay extra.txt` adds an environment-specific list on top of the base one
+            match load_text_list(&path, arg_value(&args, "--overlay").as_deref(), &mut log) {
+                Ok(entries) => (path, entries),
+                Err(e) => {
+                    // Missing or empty list: explain it instead of a raw error, and still fail
+                    log.error(&e.to_string());
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+    if entries.is_empty() {
+        log.error(&format!("No URLs found in {}", source));
         return Ok(()); // exit gracefully if no URLs
     }
 
+    // Optionally narrow the list down to one tag (e.g. `--tag prod`)
+    let tag = arg_value(&args, "--tag");
+    let (entries, skipped) = filter_by_tag(entries, tag.as_deref());
+    if let Some(tag) = &tag {
+        log.info(&format!("Tag '{}': {} selected, {} skipped", tag, entries.len(), skipped));
+        if entries.is_empty() {
+            log.error(&format!("No URLs tagged '{}' in {}", tag, source));
+            return Ok(());
+        }
+    }
+
+    // `--seed S` makes the random choices below (sampling, interval jitter) repeatable
+    let seed = match arg_value(&args, "--seed") {
+        Some(s) => s.parse::<u64>().map_err(|_| format!("Invalid --seed value: {}", s))?,
+        None => XorShift64::from_time(0).next_u64(),
+    };
+
+    // Optional spot-check: `--sample N` picks N random URLs
+    let sample = match arg_value(&args, "--sample") {
+        Some(n) => Some(n.parse::<usize>().map_err(|_| format!("Invalid --sample value: {}", n))?),
+        None => None,
+    };
+    let mut entries = match sample {
+        Some(n) => {
+            let total = entries.len();
+            let sampled = XorShift64::new(seed).sample(entries, n);
+            log.info(&format!("Sampled {} of {} URLs (seed {})", sampled.len(), total, seed));
+            sampled
+        }
+        None => entries,
+    };
+    let urls: Vec<String> = entries.iter().map(|e| e.url.clone()).collect();
+
+    // The text list is re-read whenever it changes on disk (JSON lists are read once)
+    let mut reload = (source == DEFAULT_LIST).then(|| ListReload {
+        watcher: ListWatcher::new(DEFAULT_LIST),
+        overlay: arg_value(&args, "--overlay").map(ListWatcher::new),
+        tag: tag.clone(),
+        sample,
+        seed,
+    });
+
+    // Worker count: 50 by default, `--workers N`, or `--workers auto` (also `0`) to size by CPU count
+    let workers_setting = match arg_value(&args, "--workers").as_deref() {
+        None => None,
+        Some("auto") => Some(0),
+        Some(n) => Some(n.parse::<usize>().map_err(|_| format!("Invalid --workers value: {}", n))?),
+    };
+    let mut workers = worker_count(workers_setting, urls.len());
+    log.info(&format!("Using {} worker threads", workers));
+
+    // `--max-per-host N`: at most N requests in flight to the same hostname
+    let max_per_host = match arg_value(&args, "--max-per-host") {
+        Some(n) => Some(n.parse::<usize>().map_err(|_| format!("Invalid --max-per-host value: {}", n))?),
+        None => None,
+    };
+    // `--deadline-secs N`: each cycle returns after at most N seconds; unfinished checks count as timeouts
+    let deadline = match arg_value(&args, "--deadline-secs") {
+        Some(n) => Some(Duration::from_secs(
+            n.parse::<u64>().map_err(|_| format!("Invalid --deadline-secs value: {}", n))?,
+        )),
+        None => None,
+    };
+
+    // `--ip-version 4|6|auto`: check over IPv4 or IPv6 only (default: whatever resolves first)
+    let ip_version = match arg_value(&args, "--ip-version") {
+        Some(v) => IpVersion::from_name(&v).ok_or_else(|| format!("Invalid --ip-version value: {}", v))?,
+        None => IpVersion::Auto,
+    };
+
+    // `--interval-jitter-secs N` adds 0..=N random seconds to each 30-second sleep
+    let interval_jitter = match arg_value(&args, "--interval-jitter-secs") {
+        Some(n) => Duration::from_secs(
+            n.parse::<u64>().map_err(|_| format!("Invalid --interval-jitter-secs value: {}", n))?,
+        ),
+        None => Duration::ZERO,
+    };
+    let mut jitter_rng = XorShift64::new(seed);
+
+    // Optional defacement detection: `--fingerprints <file>` remembers page fingerprints between runs
+    let mut fingerprints = match arg_value(&args, "--fingerprints") {
+        Some(path) => Some(FingerprintStore::load(&path)?),
+        None => None,
+    };
+
+    // Optional conditional requests: `--etags <file>` remembers ETags between runs and sends
+    // If-None-Match, so unchanged pages answer 304 without a body
+    let etags = match arg_value(&args, "--etags") {
+        Some(path) => Some(EtagStore::load(&path)?.shared()),
+        None => None,
+    };
+
+    // Optional latency regression check: `--baseline <file>` maps URL -> expected ms.
+    // `--regression-factor F` (default 3) sets how much slower counts as a regression, and
+    // `--update-baseline` folds each run into the baseline as a moving average.
+    let mut baseline = match arg_value(&args, "--baseline") {
+        Some(path) => Some(LatencyBaseline::load(&path)?),
+        None => None,
+    };
+    let regression_factor = match arg_value(&args, "--regression-factor") {
+        Some(v) => v
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid --regression-factor value: {}", v))?,
+        None => 3.0,
+    };
+    let update_baseline = args.iter().any(|a| a == "--update-baseline");
+
+    // `--once` runs a single cycle instead of monitoring forever
+    let once = args.iter().any(|a| a == "--once");
+    // With `--once`, `--min-uptime PCT` / `--max-avg-ms MS` mark the batch as degraded
+    let min_uptime = match arg_value(&args, "--min-uptime") {
+        Some(v) => Some(v.parse::<f64>().map_err(|_| format!("Invalid --min-uptime value: {}", v))?),
+        None => None,
+    };
+    let max_avg_ms = match arg_value(&args, "--max-avg-ms") {
+        Some(v) => Some(v.parse::<f64>().map_err(|_| format!("Invalid --max-avg-ms value: {}", v))?),
+        None => None,
+    };
+    // `--exit-on` picks what makes `--once` fail, `--exit-code N` the code it then exits with
+    let exit_on = match arg_value(&args, "--exit-on") {
+        Some(v) => ExitOn::from_name(&v).ok_or_else(|| format!("Invalid --exit-on value: {}", v))?,
+        None => ExitOn::AnyFailure,
+    };
+    let exit_code = match arg_value(&args, "--exit-code") {
+        Some(v) => v
+            .parse::<i32>()
+            .ok()
+            .filter(|c| (1..=255).contains(c))
+            .ok_or_else(|| format!("Invalid --exit-code value: {} (expected 1-255)", v))?,
+        None => 1,
+    };
+
+    // Color statuses when stdout is a terminal; `--color always|never|auto` or `--no-color` override
+    let color_mode = if args.iter().any(|a| a == "--no-color") {
+        ColorMode::Never
+    } else {
+        match arg_value(&args, "--color") {
+            Some(v) => ColorMode::from_name(&v).ok_or_else(|| format!("Invalid --color value: {}", v))?,
+            None => ColorMode::Auto,
+        }
+    };
+    let color = color_mode.enabled();
+
+    // `--format line` prints one compact line per URL instead of the full report
+    let one_line = arg_value(&args, "--format").as_deref() == Some("line");
+
+    // `--time-format iso8601|unix|rfc2822` controls how timestamps appear in reports and snapshots
+    let timestamp_format = match arg_value(&args, "--time-format") {
+        Some(v) => Some(TimestampFormat::from_name(&v).ok_or_else(|| format!("Invalid --time-format value: {}", v))?),
+        None => None,
+    };
+
+    // Optionally save each run's results as a JSON snapshot (`--snapshot results.json`)
+    let snapshot_path = arg_value(&args, "--snapshot");
+    // Optionally append each cycle's summary as one JSON line (`--stats-history history.ndjson`)
+    let history_path = arg_value(&args, "--stats-history");
+    // Optionally rewrite an HTML status page after each cycle (`--html status.html`)
+    let html_path = arg_value(&args, "--html");
+
+    // Alert only after a URL has been unhealthy this many cycles in a row (`--alert-after N`, default 3)
+    let alert_after = match arg_value(&args, "--alert-after") {
+        Some(v) => v
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid --alert-after value: {}", v))?,
+        None => 3,
+    };
+    let mut streaks = FailureStreaks::new();
+    let confirm_failures = args.iter().any(|a| a == "--confirm-failures");
+
+    // `--serve 127.0.0.1:9100` exposes the latest summary at /stats (JSON) and /metrics (Prometheus)
+    let shared_stats: SharedStats = Arc::new(Mutex::new(None));
+    if let Some(addr) = arg_value(&args, "--serve") {
+        let (bound, _handle) = stats_server::serve(&addr, Arc::clone(&shared_stats))?;
+        log.info(&format!("Serving stats on http://{}/stats and /metrics", bound));
+    }
+
+    let cfg = Config {
+        fingerprint_body: fingerprints.is_some(),
+        // `--local-time`: timestamps from the system clock instead of the time API
+        fetch_timestamp: !args.iter().any(|a| a == "--local-time"),
+        timestamp_format,
+        ip_version,
+        // `--trace-timing`: connect / first byte / done times in each report
+        trace_timing: args.iter().any(|a| a == "--trace-timing"),
+        // `--redact-hosts`: hostnames become stable tokens in every report, log line and export
+        redact_hosts: args.iter().any(|a| a == "--redact-hosts"),
+        etags: etags.clone(),
+        ..Config::default()
+    };
+    // How results are shown (timestamp format, redacted hosts) in reports, exports and log lines
+    let render = RenderOptions::from_config(&cfg);
+
+    // Results and summaries go to stdout, or with `--output-file results.log` to that file
+    let output_file = arg_value(&args, "--output-file");
+    let mut sink: Box<dyn OutputSink> = match &output_file {
+        Some(path) => Box::new(FileSink::open(path, render)?),
+        None => Box::new(StdoutSink::new(level, one_line, color, render)),
+    };
+    // `--webhook URL` additionally POSTs each cycle's failures to URL as JSON
+    if let Some(url) = arg_value(&args, "--webhook") {
+        sink = Box::new(MultiSink::new(vec![sink, Box::new(WebhookSink::new(url, render))]));
+    }
+
+    // Worker pool reused by every cycle (retry once on transport errors)
+    let batch = BatchConfig {
+        workers,
+        max_retries: 1,
+        max_per_host,
+        deadline,
+        cfg,
+        ..BatchConfig::default()
+    };
+    let mut checker = Checker::new(batch.clone());
+
     // Main monitoring loop (runs indefinitely)
     loop {
-        println!("=== Running website checks ===");
+        // Pick up edits to the URL list without a restart
+        if let Some(reload) = reload.as_mut()
+            && let Some((reloaded, removed)) = reload.poll(&entries, render, &mut log)
+        {
+            forget_removed(&removed, &mut streaks, fingerprints.as_mut(), etags.as_ref(), baseline.as_mut());
+            // Resize the pool when the list grew or shrank past the worker count
+            let resized = worker_count(workers_setting, reloaded.len());
+            if resized != workers {
+                workers = resized;
+                checker = Checker::new(BatchConfig { workers, ..batch.clone() });
+                log.info(&format!("Using {} worker threads", workers));
+            }
+            entries = reloaded;
+        }
+        let (target_entries, targets) = check_targets(&entries);
 
-        // Run checks concurrently (50 threads, retry once on transport errors)
-        let results = concurrent::check_many(urls.clone(), 50, 1);
+        log.info("=== Running website checks ===");
+
+        // Run checks concurrently on the pool
+        let results = checker.run_targets_timed(targets.clone());
+        let ts_elapsed = checker.last_timestamp_elapsed();
+        log.debug(&format!("Timestamp acquisition took {} ms", ts_elapsed.as_millis()));
+        if let Some(warning) = slow_time_warning(ts_elapsed, SLOW_TIME_API) {
+            log.warn(&warning);
+        }
 
-        // Print individual website results
-        for ws in &results {
-            ws.print();
-            println!("----------------------------------------");
+        // Per-URL output: transport errors as warnings, then each result to the sink
+        for (entry, ws) in target_entries.iter().zip(&results) {
+            if let CheckStatus::Transport(err) = &ws.status {
+                log.warn(&format!("Transport error for {}: {}", render.url(&ws.url), render.text(err, &ws.hosts())));
+            }
+            if output_file.is_none() && !one_line && entry.name != entry.url {
+                log.debug(&format!("Name: {}", render.text(&entry.name, &ws.hosts())));
+            }
+            sink.emit_result(ws);
         }
 
         // Compute and print summary statistics
         let summary = Stats::compute(&results);
-        summary.print();
+        sink.emit_summary(&summary);
+        let common: Vec<_> = Stats::issue_frequencies(&results).into_iter().take(TOP_ISSUES).collect();
+        if !common.is_empty() {
+            log.info("Most common issues:");
+            let hosts: Vec<&str> = results.iter().flat_map(WebsiteStatus::hosts).collect();
+            for (issue, count) in common {
+                log.info(&format!("  {} x {}", count, render.text(&issue, &hosts)));
+            }
+        }
+        if let (Some(fastest), Some(slowest)) = Stats::extremes(&results) {
+            log.info(&format!("Fastest: {} ({} ms)", render.url(&fastest.url), fastest.response_time.as_millis()));
+            log.info(&format!("Slowest: {} ({} ms)", render.url(&slowest.url), slowest.response_time.as_millis()));
+        }
+        if let Some(path) = &history_path {
+            let ts = results.first().map_or_else(|| "unknown".to_string(), |r| render.timestamp(&r.timestamp_utc));
+            if let Err(e) = summary.append_history(path, &ts) {
+                log.error(&format!("Failed to append stats history {}: {}", path, e));
+            }
+        }
+        if let Some(path) = &html_path
+            && let Err(e) = std::fs::write(path, render_status_html(&results, &summary, render))
+        {
+            log.error(&format!("Failed to write status page {}: {}", path, e));
+        }
+        let once_code = once_exit_code(exit_on, exit_code, &results, &summary, min_uptime, max_avg_ms);
+        if let Ok(mut latest) = shared_stats.lock() {
+            *latest = Some(summary);
+        }
+
+        // Alert on URLs that keep failing. With `--confirm-failures`, failed URLs are checked
+        // once more right away (same method and timeout), and those that pass the second
+        // time don't count as down.
+        streaks.record(&results);
+        if confirm_failures {
+            let failed = failed_targets(&targets, &results);
+            if !failed.is_empty() {
+                let recovered: Vec<_> = checker.run_targets_timed(failed).into_iter().filter(|ws| ws.healthy).collect();
+                for ws in &recovered {
+                    log.info(&format!("Recovered on re-check: {}", render.url(&ws.url)));
+                }
+                streaks.record(&recovered);
+            }
+        }
+        for (url, method) in streaks.firing(alert_after) {
+            log.warn(&format!(
+                "ALERT: {} down for {} cycles",
+                shown_target(&url, &method, render),
+                streaks.streak(&url, &method)
+            ));
+        }
+
+        if let Some(path) = &snapshot_path
+            && let Err(e) = write_snapshot(path, &results, render)
+        {
+            log.error(&format!("Failed to write snapshot {}: {}", path, e));
+        }
+
+        // Report pages whose content changed since the previous run
+        if let Some(store) = fingerprints.as_mut() {
+            for (url, method) in store.update(&results) {
+                log.warn(&format!(
+                    "CHANGED: {} content differs from the previous run",
+                    shown_target(&url, &method, render)
+                ));
+            }
+            if let Err(e) = store.save() {
+                log.error(&format!("Failed to save fingerprints: {}", e));
+            }
+        }
+
+        if let Some(store) = &etags
+            && let Ok(Err(e)) = store.lock().map(|store| store.save())
+        {
+            log.error(&format!("Failed to save ETags: {}", e));
+        }
+
+        // Report URLs much slower than their baseline
+        if let Some(baseline) = baseline.as_mut() {
+            for r in baseline.regressions(&results, regression_factor) {
+                log.warn(&format!(
+                    "SLOW: {} took {} ms (baseline {:.0} ms)",
+                    render.url(&r.url), r.actual_ms, r.baseline_ms
+                ));
+            }
+            if update_baseline {
+                baseline.update_ema(&results, BASELINE_EMA_ALPHA);
+                if let Err(e) = baseline.save() {
+                    log.error(&format!("Failed to save latency baseline: {}", e));
+                }
+            }
+        }
+
+        // `--once`: a single check cycle, then exit (nonzero if the `--exit-on` condition holds)
+        if once {
+            if once_code != 0 {
+                log.error(&format!("Batch failed, exiting with {} (see --exit-on / --min-uptime / --max-avg-ms)", once_code));
+                drop(sink); // lets a webhook deliver this batch's failures first
+                std::process::exit(once_code);
+            }
+            return Ok(());
+        }
 
-        // Wait 30 seconds before the next cycle
-        println!("Sleeping 30 seconds before next run...\n");
-        thread::sleep(Duration::from_secs(30));
+        // Wait 30 seconds (plus any jitter) before the next cycle
+        let sleep = interval_with_jitter(Duration::from_secs(30), interval_jitter, &mut jitter_rng);
+        log.info(&format!("Sleeping {:.1} seconds before next run...\n", sleep.as_secs_f64()));
+        thread::sleep(sleep);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use website_checker::status::{WebsiteStatus, CheckStatus};
+    use super::{check_targets, filter_by_tag, forget_removed, once_exit_code, worker_count, ExitOn, ListReload};
+    use website_checker::alerts::FailureStreaks;
+    use website_checker::etag_store::EtagStore;
+    use website_checker::fingerprint_store::FingerprintStore;
+    use website_checker::logger::{Level, Logger};
+    use website_checker::url_list::ListWatcher;
+    use website_checker::stats::Stats;
+    use website_checker::status::{WebsiteStatus, CheckStatus, RenderOptions};
+    use website_checker::url_list::UrlEntry;
     use std::time::Duration;
 
+    fn entry(name: &str, tags: &[&str]) -> UrlEntry {
+        UrlEntry {
+            name: name.to_string(),
+            url: format!("https://{}.example.com", name),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            methods: Vec::new(),
+            timeout_secs: None,
+        }
+    }
+
+    // Test that --tag keeps only matching entries and counts the rest as skipped
+    #[test]
+    fn tag_filter_subsets_entries() {
+        let entries = vec![
+            entry("api", &["prod"]),
+            entry("staging-api", &["staging"]),
+            entry("web", &["prod", "public"]),
+        ];
+
+        let (all, skipped) = filter_by_tag(entries.clone(), None);
+        assert_eq!(all.len(), 3);
+        assert_eq!(skipped, 0);
+
+        let (prod, skipped) = filter_by_tag(entries.clone(), Some("prod"));
+        let names: Vec<&str> = prod.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["api", "web"]);
+        assert_eq!(skipped, 1);
+
+        let (none, skipped) = filter_by_tag(entries, Some("missing"));
+        assert!(none.is_empty());
+        assert_eq!(skipped, 3);
+    }
+
+    // Test that a list edited between two cycles is picked up and removed URLs lose their streaks
+    #[test]
+    fn changed_list_is_reloaded_between_cycles() {
+        let path = std::env::temp_dir().join(format!("wc_main_reload_{}.txt", std::process::id()));
+        let overlay = std::env::temp_dir().join(format!("wc_main_reload_overlay_{}.txt", std::process::id()));
+        std::fs::write(&path, "https://a.test\nhttps://b.test\n").unwrap();
+        std::fs::write(&overlay, "https://a.test\n").unwrap();
+        let mut reload = ListReload {
+            watcher: ListWatcher::new(path.to_str().unwrap()),
+            overlay: Some(ListWatcher::new(overlay.to_str().unwrap())),
+            tag: None,
+            sample: None,
+            seed: 1,
+        };
+        let mut entries = vec![UrlEntry::from_url("https://a.test"), UrlEntry::from_url("https://b.test")];
+        let mut streaks = FailureStreaks::new();
+        let mut log = Logger::new(Level::Info, Vec::new(), Vec::new());
+        let render = RenderOptions::default();
+
+        // Cycle 1: b.test fails; the file hasn't changed
+        let down = WebsiteStatus {
+            status: CheckStatus::HttpError(503),
+            ..WebsiteStatus::not_sent("https://b.test", "GET", "", "")
+        };
+        streaks.record(&[down]);
+        let mut fingerprints = FingerprintStore::default();
+        fingerprints.update(&[WebsiteStatus {
+            body_fingerprint: Some("111".into()),
+            ..WebsiteStatus::not_sent("https://b.test", "GET", "", "")
+        }]);
+        let etags = EtagStore::default().shared();
+        etags.lock().unwrap().set("https://b.test", "GET", "\"v1\"");
+        etags.lock().unwrap().set("https://b.test", "HEAD", "\"v1\"");
+        etags.lock().unwrap().set("https://a.test", "GET", "\"a1\"");
+        assert!(reload.poll(&entries, render, &mut log).is_none());
+
+        // Between cycles: b.test is replaced by c.test (with a timeout option).
+        // The mtime is set explicitly so the change is visible on coarse filesystems.
+        let later = std::time::SystemTime::now() + Duration::from_secs(10);
+        let touch = |path: &std::path::Path| std::fs::File::options().write(true).open(path).unwrap().set_modified(later).unwrap();
+        std::fs::write(&path, "https://a.test\nhttps://c.test timeout=20\n").unwrap();
+        touch(&path);
+
+        // Cycle 2: the new list is used, and b.test's state is dropped
+        let (reloaded, removed) = reload.poll(&entries, render, &mut log).expect("list reloaded");
+        entries = reloaded;
+        assert_eq!(removed, vec!["https://b.test"]);
+        forget_removed(&removed, &mut streaks, Some(&mut fingerprints), Some(&etags), None);
+        let (_, targets) = check_targets(&entries);
+        assert_eq!(
+            targets,
+            vec![
+                ("https://a.test".to_string(), "GET".to_string(), None),
+                ("https://c.test".to_string(), "GET".to_string(), Some(Duration::from_secs(20))),
+            ]
+        );
+        assert_eq!(streaks.streak("https://b.test", "GET"), 0);
+        assert_eq!(fingerprints.get("https://b.test", "GET"), None);
+        let store = etags.lock().unwrap();
+        assert_eq!((store.get("https://b.test", "GET"), store.get("https://b.test", "HEAD")), (None, None));
+        assert_eq!(store.get("https://a.test", "GET"), Some("\"a1\""));
+        drop(store);
+
+        // Cycle 3: an edit to the overlay alone reloads too
+        std::fs::write(&overlay, "https://a.test\nhttps://d.test\n").unwrap();
+        touch(&overlay);
+        let (reloaded, removed) = reload.poll(&entries, render, &mut log).expect("overlay reloaded");
+        let urls: Vec<&str> = reloaded.iter().map(|e| e.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://a.test", "https://c.test", "https://d.test"]);
+        assert!(removed.is_empty());
+        let _ = std::fs::remove_file(&overlay);
+
+        let (out, err) = log.into_inner();
+        let logged = String::from_utf8(out).unwrap() + &String::from_utf8(err).unwrap();
+        assert!(logged.contains("1 added, 1 removed"), "{}", logged);
+        assert!(logged.contains("+ https://c.test") && logged.contains("- https://b.test"));
+
+        // Deleted files: keep checking the current list
+        let _ = std::fs::remove_file(&path);
+        assert!(reload.poll(&entries, render, &mut Logger::new(Level::Info, Vec::new(), Vec::new())).is_none());
+    }
+
+    // Test that the pool is sized from the (possibly reloaded) list length
+    #[test]
+    fn worker_count_follows_the_list_size() {
+        assert_eq!(worker_count(None, 3), 3);
+        assert_eq!(worker_count(None, 500), 50);
+        assert_eq!(worker_count(Some(8), 3), 3);
+        assert_eq!(worker_count(Some(8), 20), 8);
+        assert!(worker_count(Some(0), 20) >= 1);
+    }
+
+    // Test that each --exit-on mode picks the right exit code
+    #[test]
+    fn exit_code_follows_exit_on_mode() {
+        let with = |status, healthy| WebsiteStatus {
+            status,
+            healthy,
+            ..WebsiteStatus::not_sent("https://a.test", "GET", "", "")
+        };
+        let all_ok = vec![with(CheckStatus::Success(200), true), with(CheckStatus::Success(200), true)];
+        let one_down = vec![with(CheckStatus::Success(200), true), with(CheckStatus::HttpError(500), false)];
+        let ok_stats = Stats::compute(&all_ok);
+        let down_stats = Stats::compute(&one_down); // 50% uptime
+
+        // Default mode: any unhealthy result fails with the chosen code
+        assert_eq!(once_exit_code(ExitOn::AnyFailure, 1, &all_ok, &ok_stats, None, None), 0);
+        assert_eq!(once_exit_code(ExitOn::AnyFailure, 1, &one_down, &down_stats, None, None), 1);
+        assert_eq!(once_exit_code(ExitOn::AnyFailure, 3, &one_down, &down_stats, None, None), 3);
+
+        // Degraded mode: only crossed thresholds count
+        assert_eq!(once_exit_code(ExitOn::Degraded, 2, &one_down, &down_stats, None, None), 0);
+        assert_eq!(once_exit_code(ExitOn::Degraded, 2, &one_down, &down_stats, Some(40.0), None), 0);
+        assert_eq!(once_exit_code(ExitOn::Degraded, 2, &one_down, &down_stats, Some(90.0), None), 2);
+        assert_eq!(once_exit_code(ExitOn::AnyFailure, 1, &all_ok, &ok_stats, None, Some(-1.0)), 1);
+
+        // Never mode ignores everything
+        assert_eq!(once_exit_code(ExitOn::Never, 1, &one_down, &down_stats, Some(90.0), None), 0);
+
+        assert_eq!(ExitOn::from_name("any-failure"), Some(ExitOn::AnyFailure));
+        assert_eq!(ExitOn::from_name("sometimes"), None);
+    }
+
     // Test that Google returns a valid 2xx status code within 5s
     #[test]
     fn google_returns_success() {
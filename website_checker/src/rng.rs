@@ -0,0 +1,91 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Tiny xorshift64* generator: cheap, seedable, and good enough for jitter
+// and sampling. Not suitable for anything security related.
+#[derive(Debug, Clone)]
+pub struct XorShift64 {
+    state: u64,
+}
+
+impl XorShift64 {
+    /// Creates a generator from a fixed seed (same seed => same sequence).
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck on zero, so remap it to an arbitrary odd constant
+        let state = if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed };
+        XorShift64 { state }
+    }
+
+    /// Creates a generator seeded from the clock, mixed with `salt`
+    /// (e.g. a worker index) so generators created together still differ.
+    pub fn from_time(salt: u64) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Self::new(nanos ^ salt.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+
+    /// Next pseudo-random 64-bit value.
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Next pseudo-random float in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        // Use the top 53 bits so every value is exactly representable
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Picks `n` items at random without replacement, keeping their original order.
+    /// Returns every item when `n` is at least the number of items.
+    pub fn sample<T>(&mut self, items: Vec<T>, n: usize) -> Vec<T> {
+        if n >= items.len() {
+            return items;
+        }
+        // Partial Fisher-Yates over the indices, then restore list order
+        let mut idx: Vec<usize> = (0..items.len()).collect();
+        for i in 0..n {
+            let j = i + (self.next_u64() % (idx.len() - i) as u64) as usize;
+            idx.swap(i, j);
+        }
+        let mut keep = vec![false; items.len()];
+        for &i in &idx[..n] {
+            keep[i] = true;
+        }
+        items
+            .into_iter()
+            .zip(keep)
+            .filter_map(|(item, k)| k.then_some(item))
+            .collect()
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_size_and_determinism() {
+        let items: Vec<u32> = (0..100).collect();
+
+        let a = XorShift64::new(42).sample(items.clone(), 10);
+        let b = XorShift64::new(42).sample(items.clone(), 10);
+        assert_eq!(a.len(), 10);
+        assert_eq!(a, b, "same seed must pick the same subset");
+        // No duplicates, and the original order is kept
+        assert!(a.windows(2).all(|w| w[0] < w[1]));
+
+        let c = XorShift64::new(7).sample(items.clone(), 10);
+        assert_ne!(a, c);
+
+        // Asking for more than there is returns everything
+        assert_eq!(XorShift64::new(1).sample(items.clone(), 500), items);
+        assert!(XorShift64::new(1).sample(items, 0).is_empty());
+    }
+}
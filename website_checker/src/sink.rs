@@ -0,0 +1,301 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Stderr, Stdout, Write};
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::logger::{Level, Logger};
+use crate::stats::Stats;
+use crate::status::{RenderOptions, WebsiteStatus};
+
+// Where check results and batch summaries go. The binary picks one sink from its
+// flags, so adding a destination (e.g. a webhook) means one more implementation.
+pub trait OutputSink {
+    /// Called once per check result, in input order.
+    fn emit_result(&mut self, ws: &WebsiteStatus);
+
+    /// Called once per batch, after all of its results.
+    fn emit_summary(&mut self, s: &Stats);
+}
+
+/// Sends every result of a batch, then its summary, to `sink`.
+pub fn emit_batch(sink: &mut dyn OutputSink, results: &[WebsiteStatus], s: &Stats) {
+    for ws in results {
+        sink.emit_result(ws);
+    }
+    sink.emit_summary(s);
+}
+
+// Console output: full reports (or one line per URL) at debug level, summaries at info.
+pub struct StdoutSink {
+    log: Logger<Stdout, Stderr>,
+    one_line: bool, // `--format line`
+    color: bool,    // ANSI-colored statuses
+    render: RenderOptions,
+}
+
+impl StdoutSink {
+    pub fn new(level: Level, one_line: bool, color: bool, render: RenderOptions) -> Self {
+        StdoutSink {
+            log: Logger::console(level),
+            one_line,
+            color,
+            render,
+        }
+    }
+}
+
+impl OutputSink for StdoutSink {
+    fn emit_result(&mut self, ws: &WebsiteStatus) {
+        let shown = ws.render(self.render);
+        if self.one_line {
+            self.log.debug(&shown.one_line(self.color));
+            return;
+        }
+        self.log.debug(&if self.color { format!("{:#}", shown) } else { shown.to_string() });
+        self.log.debug("----------------------------------------");
+    }
+
+    fn emit_summary(&mut self, s: &Stats) {
+        self.log.info(&s.to_string());
+    }
+}
+
+// Appends one line per result (`WebsiteStatus::one_line`) and each summary to a file.
+// Like the logger, write failures are ignored so output never aborts a run.
+pub struct FileSink {
+    file: File,
+    render: RenderOptions,
+}
+
+impl FileSink {
+    /// Opens `path` for appending, creating it if needed.
+    pub fn open(path: impl AsRef<Path>, render: RenderOptions) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(FileSink { file, render })
+    }
+}
+
+impl OutputSink for FileSink {
+    fn emit_result(&mut self, ws: &WebsiteStatus) {
+        let _ = writeln!(self.file, "{}", ws.render(self.render).one_line(false));
+    }
+
+    fn emit_summary(&mut self, s: &Stats) {
+        let _ = write!(self.file, "{}", s);
+    }
+}
+
+// Sends every result to several sinks, e.g. the console plus a webhook
+pub struct MultiSink {
+    sinks: Vec<Box<dyn OutputSink>>,
+}
+
+impl MultiSink {
+    pub fn new(sinks: Vec<Box<dyn OutputSink>>) -> Self {
+        MultiSink { sinks }
+    }
+}
+
+impl OutputSink for MultiSink {
+    fn emit_result(&mut self, ws: &WebsiteStatus) {
+        for sink in &mut self.sinks {
+            sink.emit_result(ws);
+        }
+    }
+
+    fn emit_summary(&mut self, s: &Stats) {
+        for sink in &mut self.sinks {
+            sink.emit_summary(s);
+        }
+    }
+}
+
+// One unhealthy check as sent to the webhook
+#[derive(Debug, Serialize)]
+pub struct WebhookFailure {
+    pub url: String,
+    pub status: String,    // `WebsiteStatus::kind_str`: "success", "http_error", "transport", ...
+    pub code: Option<u16>, // HTTP status code, if any
+    pub issues: Vec<String>,
+    pub timestamp: String,
+}
+
+impl WebhookFailure {
+    pub fn from_status(ws: &WebsiteStatus, render: RenderOptions) -> Self {
+        WebhookFailure {
+            url: render.url(&ws.url),
+            status: ws.kind_str().to_string(),
+            code: ws.http_code(),
+            issues: ws.validation.issues.iter().map(|i| render.text(i, &ws.hosts())).collect(),
+            timestamp: render.timestamp(&ws.timestamp_utc),
+        }
+    }
+}
+
+// How long to wait for the webhook endpoint before giving up on a batch
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+// What identifies a failure when deciding whether a batch's failures changed:
+// (url, status kind, code). Issues and timestamps differ from cycle to cycle.
+type FailureKey = (String, String, Option<u16>);
+
+// POSTs unhealthy results to `url` as JSON. Failures are collected per batch and sent
+// in one request when the summary arrives (`{"failures": [...]}`), so a bad cycle
+// doesn't flood the endpoint; healthy batches send nothing, and a batch failing the
+// same way as the last one (same URLs and statuses) isn't sent again. POSTs go out
+// on a background thread, so a slow endpoint never holds up the monitor; one that
+// is down is logged as a warning on stderr.
+pub struct WebhookSink {
+    pub url: String,
+    pending: Vec<WebhookFailure>,
+    last_sent: Vec<FailureKey>, // failures of the last batch (sorted), sent or not
+    last_error: Arc<Mutex<Option<String>>>,
+    render: RenderOptions,
+    posts: Option<(mpsc::Sender<Vec<WebhookFailure>>, thread::JoinHandle<()>)>, // started on first use
+}
+
+impl WebhookSink {
+    pub fn new(url: impl Into<String>, render: RenderOptions) -> Self {
+        WebhookSink {
+            url: url.into(),
+            render,
+            pending: Vec::new(),
+            last_sent: Vec::new(),
+            last_error: Arc::new(Mutex::new(None)),
+            posts: None,
+        }
+    }
+
+    /// Error from the most recent POST, if it failed.
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().map(|e| e.clone()).unwrap_or(None)
+    }
+
+    /// Waits until every POST handed to the background thread is done (each takes at
+    /// most `WEBHOOK_TIMEOUT`). Also called on drop, so failures of a last batch are
+    /// delivered before the program exits.
+    pub fn wait(&mut self) {
+        if let Some((tx, handle)) = self.posts.take() {
+            drop(tx); // the thread exits once the queue is empty
+            let _ = handle.join();
+        }
+    }
+
+    // Queues the pending failures (if any, and if they changed since the last batch) for one POST
+    fn flush(&mut self) {
+        let failures = std::mem::take(&mut self.pending);
+        let mut keys: Vec<FailureKey> = failures.iter().map(|f| (f.url.clone(), f.status.clone(), f.code)).collect();
+        keys.sort();
+        let unchanged = keys == self.last_sent && self.last_error().is_none();
+        self.last_sent = keys;
+        if failures.is_empty() || unchanged {
+            return;
+        }
+        let (url, last_error) = (self.url.clone(), Arc::clone(&self.last_error));
+        let (tx, _) = self.posts.get_or_insert_with(move || {
+            let (tx, rx) = mpsc::channel::<Vec<WebhookFailure>>();
+            let handle = thread::spawn(move || {
+                let agent = ureq::AgentBuilder::new().timeout(WEBHOOK_TIMEOUT).build();
+                let mut log = Logger::console(Level::Warn);
+                for failures in rx {
+                    let error = agent
+                        .post(&url)
+                        .send_json(serde_json::json!({ "failures": failures }))
+                        .err()
+                        .map(|e| e.to_string());
+                    if let Some(e) = &error {
+                        log.warn(&format!("Webhook {} failed ({} failures not delivered): {}", url, failures.len(), e));
+                    }
+                    if let Ok(mut last) = last_error.lock() {
+                        *last = error;
+                    }
+                }
+            });
+            (tx, handle)
+        });
+        let _ = tx.send(failures);
+    }
+}
+
+impl Drop for WebhookSink {
+    fn drop(&mut self) {
+        self.wait();
+    }
+}
+
+impl OutputSink for WebhookSink {
+    fn emit_result(&mut self, ws: &WebsiteStatus) {
+        if !ws.healthy {
+            self.pending.push(WebhookFailure::from_status(ws, self.render));
+        }
+    }
+
+    fn emit_summary(&mut self, _s: &Stats) {
+        self.flush();
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::CheckStatus;
+
+    // Records what it was given instead of writing anywhere
+    #[derive(Default)]
+    struct MemorySink {
+        results: Vec<String>,
+        summaries: Vec<usize>, // `total` of each summary
+    }
+
+    impl OutputSink for MemorySink {
+        fn emit_result(&mut self, ws: &WebsiteStatus) {
+            self.results.push(ws.url.clone());
+        }
+
+        fn emit_summary(&mut self, s: &Stats) {
+            self.summaries.push(s.total);
+        }
+    }
+
+    fn result(url: &str) -> WebsiteStatus {
+        WebsiteStatus {
+            status: CheckStatus::Success(200),
+            healthy: true,
+            ..WebsiteStatus::not_sent(url, "GET", "2020-01-01T00:00:00Z", "")
+        }
+    }
+
+    #[test]
+    fn batch_emits_results_in_order_then_summary() {
+        let results = vec![result("https://a.test"), result("https://b.test")];
+        let stats = Stats::compute(&results);
+
+        let mut sink = MemorySink::default();
+        emit_batch(&mut sink, &results, &stats);
+        emit_batch(&mut sink, &results[..1], &Stats::compute(&results[..1]));
+
+        assert_eq!(sink.results, vec!["https://a.test", "https://b.test", "https://a.test"]);
+        assert_eq!(sink.summaries, vec![2, 1]);
+    }
+
+    #[test]
+    fn file_sink_appends_lines() {
+        let path = std::env::temp_dir().join(format!("wc_sink_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let results = vec![result("https://a.test")];
+
+        let mut sink: Box<dyn OutputSink> = Box::new(FileSink::open(&path, RenderOptions::default()).unwrap());
+        emit_batch(sink.as_mut(), &results, &Stats::compute(&results));
+        drop(sink);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(text.starts_with(&results[0].one_line()));
+        assert!(text.contains("=== Summary ===\nTotal: 1\n"));
+    }
+}
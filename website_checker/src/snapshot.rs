@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::status::{RenderOptions, WebsiteStatus};
+
+// One URL's result as stored in a JSON snapshot file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    pub url: String,
+    pub status: String,       // `WebsiteStatus::kind_str`: "success", "http_error", "unchanged", ...
+    pub code: Option<u16>,    // HTTP status code, if any
+    pub response_ms: u64,
+    pub healthy: bool,
+    pub timestamp_utc: String,
+}
+
+impl SnapshotEntry {
+    pub fn from_status(ws: &WebsiteStatus, render: RenderOptions) -> Self {
+        Self {
+            url: render.url(&ws.url),
+            status: ws.kind_str().to_string(),
+            code: ws.http_code(),
+            response_ms: ws.response_time.as_millis() as u64,
+            healthy: ws.healthy,
+            timestamp_utc: render.timestamp(&ws.timestamp_utc),
+        }
+    }
+
+    // Human-readable status, e.g. "success 200" or "transport"
+    fn label(&self) -> String {
+        match self.code {
+            Some(code) => format!("{} {}", self.status, code),
+            None => self.status.clone(),
+        }
+    }
+}
+
+/// Writes one run's results to `path` as a JSON array, shown as `render` asks.
+pub fn write_snapshot(path: &str, results: &[WebsiteStatus], render: RenderOptions) -> Result<(), Box<dyn Error>> {
+    let entries: Vec<SnapshotEntry> = results.iter().map(|ws| SnapshotEntry::from_status(ws, render)).collect();
+    fs::write(path, serde_json::to_string_pretty(&entries)?)?;
+    Ok(())
+}
+
+/// Reads a snapshot written by `write_snapshot`.
+pub fn read_snapshot(path: &str) -> Result<Vec<SnapshotEntry>, Box<dyn Error>> {
+    let text = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatusChange {
+    pub url: String,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LatencyChange {
+    pub url: String,
+    pub before_ms: u64,
+    pub after_ms: u64,
+    pub delta_ms: i64,
+}
+
+// Differences between two snapshots (URLs matched by exact string).
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SnapshotDiff {
+    pub status_changed: Vec<StatusChange>,
+    pub started_failing: Vec<String>,
+    pub stopped_failing: Vec<String>,
+    pub latency: Vec<LatencyChange>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl SnapshotDiff {
+    /// Compares `old` to `new`; latency deltas at or below `threshold_ms` are ignored.
+    pub fn compute(old: &[SnapshotEntry], new: &[SnapshotEntry], threshold_ms: u64) -> Self {
+        let before: HashMap<&str, &SnapshotEntry> =
+            old.iter().map(|e| (e.url.as_str(), e)).collect();
+        let mut diff = Self::default();
+
+        // Walk the new snapshot in order so the output follows the URL list
+        for after in new {
+            let Some(prev) = before.get(after.url.as_str()) else {
+                diff.added.push(after.url.clone());
+                continue;
+            };
+            if prev.label() != after.label() {
+                diff.status_changed.push(StatusChange {
+                    url: after.url.clone(),
+                    before: prev.label(),
+                    after: after.label(),
+                });
+            }
+            match (prev.healthy, after.healthy) {
+                (true, false) => diff.started_failing.push(after.url.clone()),
+                (false, true) => diff.stopped_failing.push(after.url.clone()),
+                _ => {}
+            }
+            let delta = after.response_ms as i64 - prev.response_ms as i64;
+            if delta.unsigned_abs() > threshold_ms {
+                diff.latency.push(LatencyChange {
+                    url: after.url.clone(),
+                    before_ms: prev.response_ms,
+                    after_ms: after.response_ms,
+                    delta_ms: delta,
+                });
+            }
+        }
+
+        let current: HashMap<&str, ()> = new.iter().map(|e| (e.url.as_str(), ())).collect();
+        diff.removed = old
+            .iter()
+            .filter(|e| !current.contains_key(e.url.as_str()))
+            .map(|e| e.url.clone())
+            .collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // Print the diff one line per change
+    pub fn print(&self) {
+        if self.is_empty() {
+            println!("No differences");
+            return;
+        }
+        for c in &self.status_changed {
+            println!("STATUS   {}: {} -> {}", c.url, c.before, c.after);
+        }
+        for url in &self.started_failing {
+            println!("FAILING  {}", url);
+        }
+        for url in &self.stopped_failing {
+            println!("RECOVERED {}", url);
+        }
+        for l in &self.latency {
+            println!("LATENCY  {}: {}ms -> {}ms ({:+}ms)", l.url, l.before_ms, l.after_ms, l.delta_ms);
+        }
+        for url in &self.added {
+            println!("ADDED    {}", url);
+        }
+        for url in &self.removed {
+            println!("REMOVED  {}", url);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(url: &str, status: &str, code: Option<u16>, ms: u64, healthy: bool) -> SnapshotEntry {
+        SnapshotEntry {
+            url: url.to_string(),
+            status: status.to_string(),
+            code,
+            response_ms: ms,
+            healthy,
+            timestamp_utc: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    // Test the diff over two snapshot files written to disk
+    #[test]
+    fn diff_between_snapshot_files() {
+        let dir = std::env::temp_dir();
+        let old_path = dir.join(format!("snapshot_old_{}.json", std::process::id()));
+        let new_path = dir.join(format!("snapshot_new_{}.json", std::process::id()));
+
+        let old = vec![
+            entry("https://a.example", "success", Some(200), 100, true),
+            entry("https://b.example", "http_error", Some(503), 50, false),
+            entry("https://c.example", "success", Some(200), 100, true),
+            entry("https://gone.example", "success", Some(200), 10, true),
+        ];
+        let new = vec![
+            entry("https://a.example", "transport", None, 5000, false),
+            entry("https://b.example", "success", Some(200), 60, true),
+            entry("https://c.example", "success", Some(200), 120, true),
+            entry("https://new.example", "success", Some(200), 10, true),
+        ];
+        fs::write(&old_path, serde_json::to_string(&old).unwrap()).unwrap();
+        fs::write(&new_path, serde_json::to_string(&new).unwrap()).unwrap();
+
+        let old = read_snapshot(old_path.to_str().unwrap()).unwrap();
+        let new = read_snapshot(new_path.to_str().unwrap()).unwrap();
+        let diff = SnapshotDiff::compute(&old, &new, 50);
+
+        assert_eq!(
+            diff.status_changed,
+            vec![
+                StatusChange {
+                    url: "https://a.example".into(),
+                    before: "success 200".into(),
+                    after: "transport".into(),
+                },
+                StatusChange {
+                    url: "https://b.example".into(),
+                    before: "http_error 503".into(),
+                    after: "success 200".into(),
+                },
+            ]
+        );
+        assert_eq!(diff.started_failing, vec!["https://a.example"]);
+        assert_eq!(diff.stopped_failing, vec!["https://b.example"]);
+        // c only moved 20ms, below the threshold
+        assert_eq!(diff.latency.len(), 1);
+        assert_eq!(diff.latency[0].delta_ms, 4900);
+        assert_eq!(diff.added, vec!["https://new.example"]);
+        assert_eq!(diff.removed, vec!["https://gone.example"]);
+
+        // The machine-readable form is plain JSON
+        let json: serde_json::Value = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["started_failing"][0], "https://a.example");
+
+        let _ = fs::remove_file(old_path);
+        let _ = fs::remove_file(new_path);
+    }
+
+    // Test that a 304 is stored as "unchanged" with its code
+    #[test]
+    fn unchanged_result_keeps_its_code() {
+        let ws = WebsiteStatus {
+            status: crate::status::CheckStatus::Unchanged,
+            healthy: true,
+            ..WebsiteStatus::not_sent("https://a.example", "GET", "2026-01-01T00:00:00Z", "")
+        };
+        let entry = SnapshotEntry::from_status(&ws, RenderOptions::default());
+        assert_eq!((entry.status.as_str(), entry.code), ("unchanged", Some(304)));
+        assert_eq!(entry.label(), "unchanged 304");
+    }
+
+    // Test that identical snapshots produce an empty diff
+    #[test]
+    fn identical_snapshots_have_no_diff() {
+        let snap = vec![entry("https://a.example", "success", Some(200), 100, true)];
+        assert!(SnapshotDiff::compute(&snap, &snap, 0).is_empty());
+    }
+}
@@ -1,19 +1,38 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
 use crate::status::{CheckStatus, WebsiteStatus};
 
 // Holds summary statistics for a batch of website checks.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Stats {
     pub total: usize,            // total number of websites checked
     pub successes: usize,        // number of successful checks (2xx)
     pub http_errors: usize,      // number of HTTP-level errors (e.g. 404, 500)
     pub transport_errors: usize, // number of network/connection errors
+    pub validation_failures: usize, // 2xx but failed header/body/policy validation
+    pub fully_healthy: usize,    // 2xx and every validation passed
     pub avg_response_ms: f64,    // average response time across all checks
     pub uptime_pct: f64,         // percentage of successful checks
+    pub retried_count: usize,    // results that needed at least one retry
+    pub unchanged: usize,        // 304 answers to conditional requests (also counted in successes)
 }
 
 impl Stats {
     // Compute statistics from a list of WebsiteStatus results
     pub fn compute(results: &[WebsiteStatus]) -> Self {
+        Self::compute_with(results, false)
+    }
+
+    // Same as `compute`, but with `ignore_transport` set, transport errors (usually our own
+    // network) are left out of the uptime so it only reflects how the servers answered.
+    // The counters and average response time still include every result.
+    pub fn compute_with(results: &[WebsiteStatus], ignore_transport: bool) -> Self {
         let total = results.len();
         if total == 0 {
             // If no results, return empty/default stats
@@ -22,21 +41,43 @@ impl Stats {
                 successes: 0,
                 http_errors: 0,
                 transport_errors: 0,
+                validation_failures: 0,
+                fully_healthy: 0,
                 avg_response_ms: 0.0,
                 uptime_pct: 0.0,
+                retried_count: 0,
+                unchanged: 0,
             };
         }
 
         let mut successes = 0usize;
         let mut http_errors = 0usize;
         let mut transport_errors = 0usize;
+        let mut validation_failures = 0usize;
+        let mut fully_healthy = 0usize;
+        let mut unchanged = 0usize;
         let mut total_ms: u128 = 0;
+        let mut retried_count = 0usize;
 
         // Go through each result and update counters
         for r in results {
             total_ms += r.response_time.as_millis();
+            if r.attempts > 1 {
+                retried_count += 1;
+            }
             match r.status {
-                CheckStatus::Success(_) => successes += 1,
+                CheckStatus::Success(_) | CheckStatus::Connected | CheckStatus::Unchanged => {
+                    successes += 1;
+                    if r.status.is_unchanged() {
+                        unchanged += 1;
+                    }
+                    // A 2xx only counts as fully healthy if its content checks passed too
+                    if r.validation.overall_ok() {
+                        fully_healthy += 1;
+                    } else {
+                        validation_failures += 1;
+                    }
+                }
                 CheckStatus::HttpError(_) => http_errors += 1,
                 CheckStatus::Transport(_) => transport_errors += 1,
             }
@@ -44,26 +85,452 @@ impl Stats {
 
         // Calculate averages and uptime percentage
         let avg_response_ms = (total_ms as f64) / (total as f64);
-        let uptime_pct = (successes as f64) * 100.0 / (total as f64);
+        let uptime_base = if ignore_transport {
+            total - transport_errors
+        } else {
+            total
+        };
+        let uptime_pct = if uptime_base == 0 {
+            0.0
+        } else {
+            (successes as f64) * 100.0 / (uptime_base as f64)
+        };
 
         Self {
             total,
             successes,
             http_errors,
             transport_errors,
+            validation_failures,
+            fully_healthy,
             avg_response_ms,
             uptime_pct,
+            retried_count,
+            unchanged,
+        }
+    }
+
+    // Combine several batch summaries into one. Counts are summed; the average response
+    // time and uptime are weighted by each snapshot's total.
+    pub fn merge(others: &[Stats]) -> Self {
+        let total: usize = others.iter().map(|s| s.total).sum();
+        let weighted = |f: fn(&Stats) -> f64| {
+            if total == 0 {
+                0.0
+            } else {
+                others.iter().map(|s| f(s) * s.total as f64).sum::<f64>() / total as f64
+            }
+        };
+
+        Self {
+            total,
+            successes: others.iter().map(|s| s.successes).sum(),
+            http_errors: others.iter().map(|s| s.http_errors).sum(),
+            transport_errors: others.iter().map(|s| s.transport_errors).sum(),
+            validation_failures: others.iter().map(|s| s.validation_failures).sum(),
+            fully_healthy: others.iter().map(|s| s.fully_healthy).sum(),
+            avg_response_ms: weighted(|s| s.avg_response_ms),
+            uptime_pct: weighted(|s| s.uptime_pct),
+            retried_count: others.iter().map(|s| s.retried_count).sum(),
+            unchanged: others.iter().map(|s| s.unchanged).sum(),
         }
     }
 
+    /// Fastest and slowest result by `response_time`. Transport errors are left out
+    /// (their time reflects the timeout, not real latency); see `extremes_with`.
+    pub fn extremes(results: &[WebsiteStatus]) -> (Option<&WebsiteStatus>, Option<&WebsiteStatus>) {
+        Self::extremes_with(results, false)
+    }
+
+    // Same as `extremes`, optionally counting transport errors too.
+    // Ties go to the earliest result.
+    pub fn extremes_with(
+        results: &[WebsiteStatus],
+        include_transport: bool,
+    ) -> (Option<&WebsiteStatus>, Option<&WebsiteStatus>) {
+        let mut fastest: Option<&WebsiteStatus> = None;
+        let mut slowest: Option<&WebsiteStatus> = None;
+        for r in results {
+            if !include_transport && matches!(r.status, CheckStatus::Transport(_)) {
+                continue;
+            }
+            if fastest.is_none_or(|f| r.response_time < f.response_time) {
+                fastest = Some(r);
+            }
+            if slowest.is_none_or(|s| r.response_time > s.response_time) {
+                slowest = Some(r);
+            }
+        }
+        (fastest, slowest)
+    }
+
+    /// How many results reported each validation issue, most common first (ties by message).
+    /// Quoted values are replaced by '…' so e.g. title mismatches on different sites group
+    /// together; each result counts once per message, and "Info:" notes are left out.
+    pub fn issue_frequencies(results: &[WebsiteStatus]) -> Vec<(String, usize)> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for r in results {
+            let unique: HashSet<String> = r
+                .validation
+                .issues
+                .iter()
+                .filter(|i| !i.starts_with("Info:") && !i.ends_with("more issues suppressed"))
+                .map(|i| normalize_issue(i))
+                .collect();
+            for issue in unique {
+                *counts.entry(issue).or_insert(0) += 1;
+            }
+        }
+        let mut freq: Vec<(String, usize)> = counts.into_iter().collect();
+        freq.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        freq
+    }
+
+    /// True when the batch as a whole looks degraded: uptime below `min_uptime_pct` or
+    /// average latency above `max_avg_ms`. Values exactly at a limit still count as fine.
+    pub fn is_degraded(&self, min_uptime_pct: f64, max_avg_ms: f64) -> bool {
+        self.uptime_pct < min_uptime_pct || self.avg_response_ms > max_avg_ms
+    }
+
+    /// Fraction of the error budget allowed by `slo_pct` (e.g. 99.9) that this batch used:
+    /// 1.0 means exactly at budget, 2.0 twice the allowed errors. An empty batch uses none;
+    /// with a 100% SLO any failure is an infinite burn.
+    pub fn error_budget(&self, slo_pct: f64) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        let error_rate = 1.0 - self.successes as f64 / self.total as f64;
+        let allowed = 1.0 - slo_pct / 100.0;
+        if error_rate <= 0.0 {
+            0.0
+        } else if allowed <= 0.0 {
+            f64::INFINITY
+        } else {
+            error_rate / allowed
+        }
+    }
+
+    /// Appends this summary as one JSON line (with the batch `timestamp`) to `path`,
+    /// creating the file if needed. Used for `--stats-history` trend files.
+    pub fn append_history(&self, path: impl AsRef<Path>, timestamp: &str) -> Result<(), Box<dyn Error>> {
+        let line = serde_json::to_string(&HistoryLine { timestamp, stats: self })?;
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+
     // Print the summary statistics in a human-readable format
     pub fn print(&self) {
-        println!("=== Summary ===");
-        println!("Total: {}", self.total);
-        println!("Successes: {}", self.successes);
-        println!("HTTP errors: {}", self.http_errors);
-        println!("Transport errors: {}", self.transport_errors);
-        println!("Avg response time (ms): {:.2}", self.avg_response_ms);
-        println!("Uptime: {:.2}%", self.uptime_pct);
+        print!("{}", self);
+    }
+}
+
+// One line of the stats history: the batch timestamp followed by the Stats fields
+#[derive(Serialize)]
+struct HistoryLine<'a> {
+    timestamp: &'a str,
+    #[serde(flatten)]
+    stats: &'a Stats,
+}
+
+// Replaces every '...'-quoted value in an issue message with '…'
+fn normalize_issue(issue: &str) -> String {
+    let mut out = String::with_capacity(issue.len());
+    let mut parts = issue.split('\'');
+    out.push_str(parts.next().unwrap_or(""));
+    let rest: Vec<&str> = parts.collect();
+    // Quotes come in pairs: odd pieces are quoted values; a trailing unpaired quote is kept as-is
+    let pairs = rest.len() / 2 * 2;
+    for (i, piece) in rest.iter().enumerate() {
+        if i >= pairs {
+            out.push('\'');
+            out.push_str(piece);
+        } else if i % 2 == 0 {
+            out.push_str("'…'");
+        } else {
+            out.push_str(piece);
+        }
+    }
+    out
+}
+
+// Multi-line summary used by `print` (and by the binary's logger)
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "=== Summary ===")?;
+        writeln!(f, "Total: {}", self.total)?;
+        writeln!(f, "Successes: {}", self.successes)?;
+        if self.unchanged > 0 {
+            writeln!(f, "Unchanged (304): {}", self.unchanged)?;
+        }
+        writeln!(f, "HTTP errors: {}", self.http_errors)?;
+        writeln!(f, "Transport errors: {}", self.transport_errors)?;
+        writeln!(f, "Validation failures (2xx): {}", self.validation_failures)?;
+        writeln!(f, "Fully healthy: {}", self.fully_healthy)?;
+        writeln!(f, "Avg response time (ms): {:.2}", self.avg_response_ms)?;
+        writeln!(f, "Uptime: {:.2}%", self.uptime_pct)?;
+        writeln!(f, "Needed a retry: {}", self.retried_count)
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::ValidationReport;
+    use std::time::Duration;
+
+    fn result(status: CheckStatus) -> WebsiteStatus {
+        WebsiteStatus {
+            status,
+            response_time: Duration::from_millis(100),
+            attempts: 1,
+            ..WebsiteStatus::not_sent("https://example.com", "GET", "2020-01-01T00:00:00Z", "")
+        }
+    }
+
+    #[test]
+    fn history_appends_one_json_line_per_cycle() {
+        let path = std::env::temp_dir().join(format!("wc_stats_history_{}.ndjson", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let first = Stats::compute(&[result(CheckStatus::Success(200)), result(CheckStatus::HttpError(500))]);
+        let second = Stats::compute(&[result(CheckStatus::Transport("refused".into()))]);
+        first.append_history(&path, "2020-01-01T00:00:00Z").unwrap();
+        second.append_history(&path, "2020-01-01T00:01:00Z").unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<serde_json::Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["timestamp"], "2020-01-01T00:00:00Z");
+        assert_eq!(lines[0]["total"], 2);
+        assert_eq!(lines[0]["uptime_pct"], 50.0);
+        assert_eq!(lines[1]["timestamp"], "2020-01-01T00:01:00Z");
+        assert_eq!(lines[1]["transport_errors"], 1);
+    }
+
+    #[test]
+    fn issue_frequencies_group_common_failures() {
+        let with_issues = |issues: &[&str]| {
+            let mut r = result(CheckStatus::Success(200));
+            r.validation.issues = issues.iter().map(|s| s.to_string()).collect();
+            r
+        };
+        let results = vec![
+            with_issues(&["Missing header: X-Frame-Options", "Title mismatch: got 'Shop', expected 'Home'"]),
+            with_issues(&["Missing header: X-Frame-Options", "Missing header: X-Frame-Options"]),
+            with_issues(&["Title mismatch: got 'Blog', expected 'Home'", "Info: binary body (image/png); text rules skipped"]),
+            with_issues(&["Missing header: X-Frame-Options", "Body missing required text: 'Welcome'"]),
+            with_issues(&[]),
+        ];
+
+        assert_eq!(
+            Stats::issue_frequencies(&results),
+            vec![
+                ("Missing header: X-Frame-Options".to_string(), 3),
+                ("Title mismatch: got '…', expected '…'".to_string(), 2),
+                ("Body missing required text: '…'".to_string(), 1),
+            ]
+        );
+        assert_eq!(normalize_issue("it's odd"), "it's odd");
+    }
+
+    #[test]
+    fn extremes_skip_transport_errors_by_default() {
+        let timed = |url: &str, status, ms| WebsiteStatus {
+            url: url.into(),
+            response_time: Duration::from_millis(ms),
+            ..result(status)
+        };
+        let results = vec![
+            timed("https://a.test", CheckStatus::Success(200), 120),
+            timed("https://b.test", CheckStatus::HttpError(503), 40),
+            timed("https://c.test", CheckStatus::Success(200), 900),
+            timed("https://d.test", CheckStatus::Transport("timed out".into()), 5000),
+        ];
+
+        let (fastest, slowest) = Stats::extremes(&results);
+        assert_eq!(fastest.unwrap().url, "https://b.test");
+        assert_eq!(slowest.unwrap().url, "https://c.test");
+
+        let (_, slowest) = Stats::extremes_with(&results, true);
+        assert_eq!(slowest.unwrap().url, "https://d.test");
+
+        // Nothing but transport errors: no extremes
+        assert!(matches!(Stats::extremes(&results[3..]), (None, None)));
+    }
+
+    #[test]
+    fn degraded_at_uptime_floor_and_latency_ceiling() {
+        let stats = Stats {
+            uptime_pct: 95.0,
+            avg_response_ms: 300.0,
+            ..Stats::compute(&[])
+        };
+        // Exactly at either limit is still healthy
+        assert!(!stats.is_degraded(95.0, 300.0));
+        assert!(!stats.is_degraded(0.0, f64::INFINITY));
+        // Just past either limit is degraded
+        assert!(stats.is_degraded(95.01, 300.0));
+        assert!(stats.is_degraded(95.0, 299.99));
+        assert!(stats.is_degraded(100.0, 1000.0));
+        assert!(stats.is_degraded(50.0, 100.0));
+
+        // An empty batch has 0% uptime
+        assert!(Stats::compute(&[]).is_degraded(1.0, f64::INFINITY));
+        assert!(!Stats::compute(&[]).is_degraded(0.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn error_budget_compares_failures_to_the_slo() {
+        let with = |successes: usize, total: usize| Stats { successes, total, ..Stats::compute(&[]) };
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+
+        // 1 failure in 1000 checks against a 99.9% SLO: exactly the allowed budget
+        assert!(close(with(999, 1000).error_budget(99.9), 1.0));
+
+        // Under budget: half the allowed errors, or none at all
+        assert!(close(with(9995, 10000).error_budget(99.9), 0.5));
+        assert_eq!(with(1000, 1000).error_budget(99.9), 0.0);
+
+        // Over budget: 2 failures in 1000 is double
+        assert!(close(with(998, 1000).error_budget(99.9), 2.0));
+        assert!(close(with(90, 100).error_budget(99.0), 10.0));
+
+        // Edge cases: nothing checked, and a 100% SLO
+        assert_eq!(with(0, 0).error_budget(99.9), 0.0);
+        assert_eq!(with(999, 1000).error_budget(100.0), f64::INFINITY);
+        assert_eq!(with(1000, 1000).error_budget(100.0), 0.0);
+    }
+
+    #[test]
+    fn unchanged_pages_count_as_successes() {
+        let results = vec![
+            result(CheckStatus::Success(200)),
+            result(CheckStatus::Unchanged),
+            result(CheckStatus::HttpError(500)),
+        ];
+        let stats = Stats::compute(&results);
+        assert_eq!((stats.successes, stats.unchanged), (2, 1));
+        assert!((stats.uptime_pct - 200.0 / 3.0).abs() < 1e-9);
+        assert!(stats.to_string().contains("Unchanged (304): 1\n"));
+        assert!(!Stats::compute(&results[..1]).to_string().contains("Unchanged"));
+    }
+
+    #[test]
+    fn ignoring_transport_errors_changes_uptime() {
+        let results = vec![
+            result(CheckStatus::Success(200)),
+            result(CheckStatus::Success(200)),
+            result(CheckStatus::HttpError(500)),
+            result(CheckStatus::Transport("timed out".into())),
+        ];
+
+        let all = Stats::compute(&results);
+        assert_eq!(all.uptime_pct, 50.0);
+
+        let server_only = Stats::compute_with(&results, true);
+        assert!((server_only.uptime_pct - 200.0 / 3.0).abs() < 1e-9);
+        // Counters are unaffected by the mode
+        assert_eq!(server_only.total, 4);
+        assert_eq!(server_only.transport_errors, 1);
+
+        // Only transport errors: nothing left to measure
+        let down = [result(CheckStatus::Transport("dns".into()))];
+        assert_eq!(Stats::compute_with(&down, true).uptime_pct, 0.0);
+    }
+
+    #[test]
+    fn separates_validation_failures_from_status_failures() {
+        let checked = |status, ok: bool| {
+            let mut r = result(status);
+            r.validation = ValidationReport {
+                header_ok: ok,
+                body_ok: true,
+                https_policy_ok: true,
+                redirect_ok: true,
+                tls_policy_ok: true,
+                ..ValidationReport::default()
+            };
+            r
+        };
+        let results = vec![
+            checked(CheckStatus::Success(200), true),
+            checked(CheckStatus::Success(200), true),
+            checked(CheckStatus::Success(200), false), // 200 but missing a required header
+            checked(CheckStatus::HttpError(500), true),
+            checked(CheckStatus::HttpError(404), false),
+            result(CheckStatus::Transport("timeout".into())),
+        ];
+        let stats = Stats::compute(&results);
+        assert_eq!(stats.successes, 3);
+        assert_eq!(stats.fully_healthy, 2);
+        assert_eq!(stats.validation_failures, 1);
+        assert_eq!(stats.http_errors + stats.transport_errors, 3); // status failures aren't validation failures
+        assert_eq!(stats.uptime_pct, 50.0); // status-only uptime is unchanged
+
+        let text = stats.to_string();
+        assert!(text.contains("Validation failures (2xx): 1\n"));
+        assert!(text.contains("Fully healthy: 2\n"));
+    }
+
+    #[test]
+    fn merge_weights_averages_by_total() {
+        let prod = Stats {
+            total: 3,
+            successes: 3,
+            http_errors: 0,
+            transport_errors: 0,
+            validation_failures: 1,
+            fully_healthy: 2,
+            avg_response_ms: 100.0,
+            uptime_pct: 100.0,
+            retried_count: 1,
+            unchanged: 1,
+        };
+        let staging = Stats {
+            total: 1,
+            successes: 0,
+            http_errors: 1,
+            transport_errors: 0,
+            validation_failures: 0,
+            fully_healthy: 0,
+            avg_response_ms: 500.0,
+            uptime_pct: 0.0,
+            retried_count: 0,
+            unchanged: 0,
+        };
+
+        let merged = Stats::merge(&[prod, staging]);
+        assert_eq!(merged.total, 4);
+        assert_eq!(merged.successes, 3);
+        assert_eq!(merged.http_errors, 1);
+        assert_eq!(merged.avg_response_ms, 200.0); // (3*100 + 1*500) / 4
+        assert_eq!(merged.uptime_pct, 75.0);
+        assert_eq!(merged.retried_count, 1);
+        assert_eq!(merged.unchanged, 1);
+        assert_eq!((merged.validation_failures, merged.fully_healthy), (1, 2));
+
+        // Nothing to merge: zeroed stats rather than NaN
+        let empty = Stats::merge(&[Stats::compute(&[])]);
+        assert_eq!(empty.total, 0);
+        assert_eq!(empty.avg_response_ms, 0.0);
+        assert_eq!(empty.uptime_pct, 0.0);
+    }
+
+    #[test]
+    fn counts_results_that_needed_a_retry() {
+        let with_attempts = |status, attempts| WebsiteStatus {
+            attempts,
+            ..result(status)
+        };
+        let results = vec![
+            with_attempts(CheckStatus::Success(200), 1),
+            with_attempts(CheckStatus::Success(200), 2),
+            with_attempts(CheckStatus::Transport("reset".into()), 3),
+            with_attempts(CheckStatus::HttpError(500), 1),
+        ];
+        assert_eq!(Stats::compute(&results).retried_count, 2);
     }
 }
@@ -0,0 +1,97 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::stats::Stats;
+
+// How long a client may take to send its request before the connection is dropped,
+// so an idle client can't hold up the (single) server thread
+pub const CLIENT_READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Latest batch summary, written by the monitoring loop and read by the server.
+pub type SharedStats = Arc<Mutex<Option<Stats>>>;
+
+/// Starts a tiny HTTP server on `addr` (e.g. "127.0.0.1:9100") serving
+/// `GET /stats` (JSON) and `GET /metrics` (Prometheus text) from `shared`.
+/// Connections are handled one at a time on a background thread.
+/// Returns the bound address (useful with port 0) and the thread handle.
+pub fn serve(addr: &str, shared: SharedStats) -> io::Result<(SocketAddr, thread::JoinHandle<()>)> {
+    let listener = TcpListener::bind(addr)?;
+    let local = listener.local_addr()?;
+    let handle = thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // A misbehaving client only affects its own connection
+            let _ = handle_connection(stream, &shared);
+        }
+    });
+    Ok((local, handle))
+}
+
+// Reads the request line, ignores the headers and writes one response
+fn handle_connection(stream: TcpStream, shared: &SharedStats) -> io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain headers up to the blank line
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let stats = shared.lock().map(|s| s.clone()).unwrap_or(None);
+    let (status, content_type, body) = respond(method, path, stats.as_ref());
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+// Picks the response for a request: (status line, content type, body)
+fn respond(method: &str, path: &str, stats: Option<&Stats>) -> (&'static str, &'static str, String) {
+    if method != "GET" {
+        return ("405 Method Not Allowed", "text/plain", "only GET is supported\n".into());
+    }
+    match (path, stats) {
+        ("/stats" | "/metrics", None) => ("503 Service Unavailable", "text/plain", "no batch finished yet\n".into()),
+        ("/stats", Some(s)) => (
+            "200 OK",
+            "application/json",
+            serde_json::to_string(s).unwrap_or_else(|_| "{}".into()),
+        ),
+        ("/metrics", Some(s)) => ("200 OK", "text/plain; version=0.0.4", prometheus_text(s)),
+        _ => ("404 Not Found", "text/plain", "try /stats or /metrics\n".into()),
+    }
+}
+
+/// Renders `stats` in the Prometheus text exposition format.
+pub fn prometheus_text(stats: &Stats) -> String {
+    let metrics = [
+        ("website_checker_total", "Websites checked in the last batch", stats.total as f64),
+        ("website_checker_successes", "Successful (2xx) checks", stats.successes as f64),
+        ("website_checker_http_errors", "Checks answered with a non-2xx status", stats.http_errors as f64),
+        ("website_checker_transport_errors", "Checks that failed at the network level", stats.transport_errors as f64),
+        ("website_checker_validation_failures", "2xx checks that failed validation", stats.validation_failures as f64),
+        ("website_checker_fully_healthy", "2xx checks that passed every validation", stats.fully_healthy as f64),
+        ("website_checker_avg_response_ms", "Average response time in milliseconds", stats.avg_response_ms),
+        ("website_checker_uptime_pct", "Percentage of successful checks", stats.uptime_pct),
+        ("website_checker_retried", "Results that needed at least one retry", stats.retried_count as f64),
+        ("website_checker_unchanged", "Checks answered 304 Not Modified to a conditional request", stats.unchanged as f64),
+    ];
+    let mut out = String::new();
+    for (name, help, value) in metrics {
+        out.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n{} {}\n", name, help, name, name, value));
+    }
+    out
+}
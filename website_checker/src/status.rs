@@ -1,7 +1,18 @@
-use crate::time_utils::fetch_network_time_utc;
-use crate::validation::{enforce_https_policy, validate_response, Config, ValidationReport};
+use crate::color::paint_status;
+use crate::time_utils::{format_timestamp, parse_iso8601, timestamp_utc, unix_now, TimestampFormat};
+use crate::tls::{client_config_with_roots, insecure_client_config, load_client_identity, CapturingTlsConnector};
+use crate::validation::{
+    decode_body, enforce_https_policy, enforce_redirect_policy, enforce_tls_policy, fingerprint,
+    parse_host_port, response_headers, validate_not_modified, validate_response, validate_response_headers, Config, IpVersion, Timing, ValidationReport,
+};
+use crate::validation::SuccessPredicate;
+use rustls::ClientConfig;
 use std::fmt;
-use std::time::{Duration, Instant};
+use std::io::{self, Read};
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use ureq;
 
 // Represents the result of a website check
@@ -10,16 +21,58 @@ pub enum CheckStatus {
     Success(u16),       // HTTP success (2xx)
     HttpError(u16),     // Non-success HTTP status (e.g. 404, 500)
     Transport(String),  // Network/connection error (DNS, TLS, timeout, etc.)
+    Connected,          // `tcp://` probe: the port accepted a connection (no HTTP status)
+    Unchanged,          // 304 to a conditional request: same page as last time (`Config::etags`)
 }
 
+// Transport error recorded when the redirect limit (`Config::max_redirects`) is hit,
+// which usually means a redirect loop
+pub const TOO_MANY_REDIRECTS: &str = "too many redirects";
+
+impl CheckStatus {
+    /// True for an answer that counts as up: a 2xx, a 304 to a conditional request,
+    /// or an open port for a TCP probe.
+    pub fn is_success(&self) -> bool {
+        matches!(self, CheckStatus::Success(_) | CheckStatus::Connected | CheckStatus::Unchanged)
+    }
+
+    /// True if the check stopped because it followed too many redirects.
+    pub fn is_too_many_redirects(&self) -> bool {
+        matches!(self, CheckStatus::Transport(e) if e == TOO_MANY_REDIRECTS)
+    }
+
+    /// True for a 304 answer to a conditional request: a success whose content is
+    /// identical to the last check.
+    pub fn is_unchanged(&self) -> bool {
+        matches!(self, CheckStatus::Unchanged)
+    }
+}
+
+// Status of a conditional request whose page is unchanged (`Config::etags`)
+pub const NOT_MODIFIED: u16 = 304;
+
+// `tcp://host:port` URLs are only connect-probed: no HTTP request, no HTTP validation.
+// An open port is reported as `CheckStatus::Connected` with method "TCP".
+
+// A downloaded page: status code, headers as (name, value) pairs, and body text
+pub type FetchedPage = (u16, Vec<(String, String)>, String);
+
 // Full record of a single website check
 #[derive(Debug)]
 pub struct WebsiteStatus {
     pub url: String,                // website URL
+    pub method: String,             // HTTP method used ("GET", "HEAD", ...)
     pub status: CheckStatus,        // result (success/error)
     pub response_time: Duration,    // how long the request took
-    pub timestamp_utc: String,      // timestamp when check was made
+    pub timestamp_utc: String,      // timestamp when check was made (raw, as fetched)
     pub validation: ValidationReport, // header/body/HTTPS policy validation
+    pub body_fingerprint: Option<String>, // FNV-1a hash of the body (when enabled)
+    pub tls_version: Option<String>,      // negotiated TLS version (https only, e.g. "TLS 1.3")
+    pub tls_cipher: Option<String>,       // negotiated cipher suite (https only)
+    pub healthy: bool,                    // passed the success rule (default: 2xx and validation ok)
+    pub attempts: u32,                    // requests made for this result (>1 means it was retried)
+    pub request_id: String,               // X-Request-Id sent with the (last) request; empty if none was sent
+    pub redirect_chain: Vec<(u16, String)>, // each redirect followed: (3xx status, URL that sent it), in order
 }
 
 impl WebsiteStatus {
@@ -30,58 +83,149 @@ impl WebsiteStatus {
 
     /// Runs a request with a custom validation config.
     pub fn request_with(url: &str, cfg: &Config) -> Self {
-        let (status, response_time, mut report) = Self::do_request(url, cfg);
-
-        // Fetch timestamp per request (old behavior)
-        let timestamp_utc = fetch_network_time_utc().unwrap_or_else(|e| {
-            report.issues.push(format!("Timestamp fetch failed: {}", e));
-            "unknown".to_string()
-        });
-
-        WebsiteStatus {
-            url: url.to_string(),
-            status,
-            response_time,
-            timestamp_utc,
-            validation: report,
+        // Fetch timestamp per request (old behavior), or read the local clock
+        match timestamp_utc(cfg.fetch_timestamp) {
+            Ok(ts) => Self::request_with_client(url, &cfg.method, None, cfg, &HttpClient::new(cfg), &ts),
+            Err(e) => {
+                let mut ws = Self::request_with_client(url, &cfg.method, None, cfg, &HttpClient::new(cfg), "unknown");
+                ws.validation.issues.push(format!("Timestamp fetch failed: {}", e));
+                ws
+            }
         }
     }
 
+    /// Runs a request with a custom rule for what counts as healthy,
+    /// e.g. `|s, r| matches!(s, CheckStatus::Success(200) | CheckStatus::HttpError(301))`.
+    pub fn request_with_predicate(
+        url: &str,
+        cfg: &Config,
+        success_when: impl Fn(&CheckStatus, &ValidationReport) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        let pred: SuccessPredicate = Arc::new(success_when);
+        let cfg = Config {
+            success_when: Some(pred),
+            ..cfg.clone()
+        };
+        Self::request_with(url, &cfg)
+    }
+
     /// Runs a request but uses a pre-fetched timestamp (avoids hitting time API repeatedly).
     pub fn request_with_timestamp(url: &str, cfg: &Config, timestamp_utc: &str) -> Self {
-        let (status, response_time, report) = Self::do_request(url, cfg);
-        WebsiteStatus {
-            url: url.to_string(),
-            status,
-            response_time,
-            timestamp_utc: timestamp_utc.to_string(),
-            validation: report,
-        }
+        Self::request_method_with_timestamp(url, &cfg.method, cfg, timestamp_utc)
+    }
+
+    /// Same as `request_with_timestamp`, with another HTTP method (e.g. "HEAD") in place
+    /// of `cfg.method`. HEAD responses have no body, so body rules are skipped for them.
+    pub fn request_method_with_timestamp(url: &str, method: &str, cfg: &Config, timestamp_utc: &str) -> Self {
+        Self::request_with_client(url, method, None, cfg, &HttpClient::new(cfg), timestamp_utc)
+    }
+
+    /// Same as `request_method_with_timestamp`, reusing `client` (built from the same
+    /// `cfg`) instead of setting up a new one, e.g. for every check of a batch. A `timeout`
+    /// replaces `cfg.timeout` for this request only (e.g. a per-URL override).
+    /// Date header rules are judged at `timestamp_utc` (the local clock if it can't be parsed).
+    pub fn request_with_client(
+        url: &str,
+        method: &str,
+        timeout: Option<Duration>,
+        cfg: &Config,
+        client: &HttpClient,
+        timestamp_utc: &str,
+    ) -> Self {
+        let now = parse_iso8601(timestamp_utc).unwrap_or_else(unix_now);
+        let mut ws = Self::do_request(url, method, timeout.unwrap_or(cfg.timeout), cfg, client, now);
+        ws.timestamp_utc = timestamp_utc.to_string();
+        ws
     }
 
     /// Core request logic: makes the HTTP request, applies validations, but does not timestamp.
-    fn do_request(url: &str, cfg: &Config) -> (CheckStatus, Duration, ValidationReport) {
+    /// `now` (seconds since the Unix epoch) is the time of the check, for date header rules.
+    fn do_request(url: &str, method: &str, timeout: Duration, cfg: &Config, client: &HttpClient, now: u64) -> Self {
+        if let Some(addr) = url.strip_prefix("tcp://") {
+            return Self::tcp_probe(url, addr, timeout, cfg);
+        }
         let mut report = ValidationReport::default();
 
         // Enforce HTTPS policy (records issues if not HTTPS)
         enforce_https_policy(url, &mut report, cfg);
 
-        // Setup HTTP client with 5s timeout
+        // Shared HTTP client (`cfg.timeout`, 5s by default); its setup warnings go in every report
         let start = Instant::now();
-        let agent = ureq::AgentBuilder::new()
-            .timeout(Duration::from_secs(5))
-            .build();
+        let (agent, tls) = match &client.connection {
+            Ok(connection) => connection,
+            Err(e) => {
+                // Unusable client certificate: fail the check without sending anything
+                let mut ws = Self::not_sent(url, method, "", e);
+                ws.validation.header_ok = false;
+                ws.validation.body_ok = false;
+                ws.validation.issues.push(format!("Client certificate error: {}", e));
+                return ws;
+            }
+        };
+        report.issues.extend(client.warnings.iter().cloned());
 
         // Perform request and handle results
-        let (status, response_time) = match agent.get(url).call() {
+        let mut body = None;
+        let method = method.to_ascii_uppercase();
+        let is_head = method == "HEAD";
+        let request_id = next_request_id();
+        let mut redirect_chain = Vec::new();
+        let etag = cfg.etags.as_ref().and_then(|store| store.lock().ok()?.get(url, &method).map(str::to_string));
+        let mut headers = vec![("X-Request-Id", request_id.as_str())];
+        headers.extend(etag.as_deref().map(|etag| ("If-None-Match", etag)));
+        // Everything before this is local setup; the connect starts inside `send_following`
+        let connect_start = start.elapsed();
+        let sent = send_following(agent, &method, url, timeout, cfg, &headers, &mut redirect_chain).map_err(|e| *e);
+        // ureq returns once the status line and headers are in, before any of the body is read
+        let first_byte = start.elapsed();
+        let mut final_url = url.to_string(); // where the (last) response came from
+        let (status, response_time) = match sent {
+            Ok(resp) if redirect_limit_hit(&resp, &redirect_chain, cfg) => {
+                // Redirect limit hit: most likely a loop between URLs
+                report.header_ok = false;
+                report.body_ok = false;
+                report.issues.push(format!(
+                    "Stopped after {} redirects (possible redirect loop)",
+                    cfg.max_redirects
+                ));
+                (CheckStatus::Transport(TOO_MANY_REDIRECTS.to_string()), start.elapsed())
+            }
             Ok(resp) => {
                 let code = resp.status();
-                validate_response(resp, cfg, &mut report); // run validation checks
-                (CheckStatus::Success(code), start.elapsed())
+                final_url = resp.get_url().to_string();
+                enforce_redirect_policy(url, resp.get_url(), &mut report, cfg);
+                if let Some(issue) = unfollowed_redirect(&resp, cfg) {
+                    report.redirect_ok = false;
+                    report.issues.push(issue);
+                }
+                // Remember the version we got, for the next conditional request
+                if let (200, Some(store), Some(new_etag)) = (code, &cfg.etags, resp.header("ETag"))
+                    && let Ok(mut store) = store.lock()
+                {
+                    store.set(url, &method, new_etag);
+                }
+                // 304 Not Modified has no body: the page is the one we saw last time
+                if code == NOT_MODIFIED {
+                    validate_not_modified(&resp, cfg, now, &mut report);
+                    (CheckStatus::Unchanged, start.elapsed())
+                } else {
+                    if is_head {
+                        validate_response_headers(&resp, cfg, now, &mut report);
+                    } else {
+                        body = validate_response(resp, cfg, now, &mut report); // run validation checks
+                    }
+                    (CheckStatus::Success(code), start.elapsed())
+                }
             }
             Err(ureq::Error::Status(code, resp)) => {
                 // Non-2xx status, but still possible to validate headers/body
-                validate_response(resp, cfg, &mut report);
+                final_url = resp.get_url().to_string();
+                enforce_redirect_policy(url, resp.get_url(), &mut report, cfg);
+                if is_head {
+                    validate_response_headers(&resp, cfg, now, &mut report);
+                } else {
+                    body = validate_response(resp, cfg, now, &mut report);
+                }
                 (CheckStatus::HttpError(code), start.elapsed())
             }
             Err(e) => {
@@ -93,7 +237,244 @@ impl WebsiteStatus {
             }
         };
 
-        (status, response_time, report)
+        // Fingerprint the (capped) body so content changes can be spotted across runs
+        let body_fingerprint = if cfg.fingerprint_body {
+            body.as_deref().map(fingerprint)
+        } else {
+            None
+        };
+
+        // TLS details of the host that answered (the connection may have come from the pool);
+        // None for http:// or when no response arrived
+        let tls_info = if matches!(status, CheckStatus::Transport(_)) {
+            None
+        } else {
+            parse_host_port(&final_url).and_then(|(host, _)| tls.info_for(&host))
+        };
+        if matches!(status, CheckStatus::Transport(_)) {
+            report.tls_policy_ok = false; // nothing was negotiated to judge
+        } else {
+            enforce_tls_policy(url, tls_info.as_ref().map(|t| t.version.as_str()), &mut report, cfg);
+        }
+        if cfg.trace_timing && !matches!(status, CheckStatus::Transport(_)) {
+            report.timing = Some(Timing {
+                connect_start_ms: connect_start.as_millis() as u64,
+                ttfb_ms: first_byte.as_millis() as u64,
+                total_ms: response_time.as_millis() as u64,
+            });
+        }
+        report.cap_issues(cfg.max_issues);
+        let healthy = cfg.is_success(&status, &report);
+
+        WebsiteStatus {
+            url: url.to_string(),
+            method,
+            status,
+            response_time,
+            timestamp_utc: String::new(),
+            validation: report,
+            body_fingerprint,
+            tls_version: tls_info.as_ref().map(|t| t.version.clone()),
+            tls_cipher: tls_info.map(|t| t.cipher),
+            healthy,
+            attempts: 1,
+            request_id,
+            redirect_chain,
+        }
+    }
+
+    // Connects to `addr` ("host:port", optionally followed by a path, which is ignored)
+    // and reports whether the port is open, waiting up to `timeout`. Only the
+    // connect time is measured.
+    fn tcp_probe(url: &str, addr: &str, timeout: Duration, cfg: &Config) -> Self {
+        let host_port = addr.split('/').next().unwrap_or(addr);
+        let start = Instant::now();
+        let connected = host_port
+            .to_socket_addrs()
+            .map_err(|e| format!("invalid tcp address '{}': {}", host_port, e))
+            .and_then(|addrs| {
+                // Try each resolved address of the allowed family in turn, like TcpStream::connect
+                let mut last_err = format!("no {} address for '{}'", cfg.ip_version.label(), host_port);
+                for sock in addrs.filter(|a| cfg.ip_version.allows(a)) {
+                    match TcpStream::connect_timeout(&sock, timeout) {
+                        Ok(_) => return Ok(()),
+                        Err(e) => last_err = format!("tcp connect to {} failed: {}", sock, e),
+                    }
+                }
+                Err(last_err)
+            });
+        let response_time = start.elapsed();
+
+        // Nothing HTTP-related to validate, so every category passes unless the connect failed
+        let mut report = ValidationReport {
+            header_ok: true,
+            body_ok: true,
+            https_policy_ok: true,
+            redirect_ok: true,
+            tls_policy_ok: true,
+            ..ValidationReport::default()
+        };
+        let status = match connected {
+            Ok(()) => CheckStatus::Connected,
+            Err(e) => {
+                report.header_ok = false;
+                report.body_ok = false;
+                report.issues.push(format!("Transport error: {}", e));
+                CheckStatus::Transport(e)
+            }
+        };
+        let healthy = cfg.is_success(&status, &report);
+        WebsiteStatus {
+            status,
+            response_time,
+            validation: report,
+            healthy,
+            attempts: 1,
+            ..Self::not_sent(url, "TCP", "", "")
+        }
+    }
+
+    /// Checks `url` once over IPv4 only and once over IPv6 only (same rules otherwise),
+    /// e.g. to see whether a dual-stack host works over just one family.
+    pub fn request_each_ip_version(url: &str, cfg: &Config) -> (Self, Self) {
+        let over = |ip_version| Self::request_with(url, &Config { ip_version, ..cfg.clone() });
+        (over(IpVersion::V4Only), over(IpVersion::V6Only))
+    }
+
+    /// A result for a check that was never sent (e.g. the batch's request budget ran out):
+    /// a transport error with `reason`, zero response time and no attempts.
+    pub fn not_sent(url: &str, method: &str, timestamp_utc: &str, reason: &str) -> Self {
+        WebsiteStatus {
+            url: url.to_string(),
+            method: method.to_ascii_uppercase(),
+            status: CheckStatus::Transport(reason.to_string()),
+            response_time: Duration::ZERO,
+            timestamp_utc: timestamp_utc.to_string(),
+            validation: ValidationReport::default(),
+            body_fingerprint: None,
+            tls_version: None,
+            tls_cipher: None,
+            healthy: false,
+            attempts: 0,
+            request_id: String::new(),
+            redirect_chain: Vec::new(),
+        }
+    }
+
+    /// Downloads `url` once and returns the status code, headers, and (capped) body text,
+    /// decoded as described in `validation::decode_body`. Combine with `validation::evaluate` to try several rule sets
+    /// against the same page without re-downloading it.
+    pub fn fetch_body(url: &str, cfg: &Config) -> Result<FetchedPage, String> {
+        let client = HttpClient::new(cfg);
+        let (agent, _) = client.connection.as_ref().map_err(String::clone)?;
+        let resp = match send_following(agent, &cfg.method, url, cfg.timeout, cfg, &[], &mut Vec::new()).map_err(|e| *e) {
+            Ok(resp) => resp,
+            Err(ureq::Error::Status(_, resp)) => resp, // non-2xx still has headers/body
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let status = resp.status();
+        let headers = response_headers(&resp);
+        let mut buf = Vec::new();
+        resp.into_reader()
+            .take(cfg.max_body_bytes as u64)
+            .read_to_end(&mut buf)
+            .map_err(|e| format!("Failed to read response body: {}", e))?;
+        let (text, _note) = decode_body(&buf, cfg);
+        Ok((status, headers, text))
+    }
+
+    /// The 2xx status code, if the check succeeded.
+    pub fn as_success_code(&self) -> Option<u16> {
+        match self.status {
+            CheckStatus::Success(code) => Some(code),
+            _ => None,
+        }
+    }
+
+    /// The HTTP status code the server answered with (2xx, 304 or error); None for
+    /// transport errors and TCP probes.
+    pub fn http_code(&self) -> Option<u16> {
+        match self.status {
+            CheckStatus::Success(code) | CheckStatus::HttpError(code) => Some(code),
+            CheckStatus::Unchanged => Some(NOT_MODIFIED),
+            CheckStatus::Transport(_) | CheckStatus::Connected => None,
+        }
+    }
+
+    /// The non-2xx status code, if the server answered with an HTTP error.
+    pub fn as_http_error(&self) -> Option<u16> {
+        match self.status {
+            CheckStatus::HttpError(code) => Some(code),
+            _ => None,
+        }
+    }
+
+    /// The error message, if the request failed at the transport level.
+    pub fn transport_detail(&self) -> Option<&str> {
+        match &self.status {
+            CheckStatus::Transport(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// Short name of the status kind: "success", "http_error", "transport", "connected"
+    /// or "unchanged".
+    pub fn kind_str(&self) -> &'static str {
+        match self.status {
+            CheckStatus::Success(_) => "success",
+            CheckStatus::HttpError(_) => "http_error",
+            CheckStatus::Transport(_) => "transport",
+            CheckStatus::Connected => "connected",
+            CheckStatus::Unchanged => "unchanged",
+        }
+    }
+
+    /// Hosts this check talked to: its URL's and each redirect hop's (see `RenderOptions::text`).
+    pub fn hosts(&self) -> Vec<&str> {
+        let urls = std::iter::once(&self.url).chain(self.redirect_chain.iter().map(|(_, url)| url));
+        urls.filter_map(|u| split_host(u)).map(|(_, host, _)| host).filter(|h| !h.is_empty()).collect()
+    }
+
+    /// Compact, grep-friendly summary, e.g. `[ts] https://x.com 200 143ms ok`
+    /// or `[ts] https://y.com TRANSPORT dns error`.
+    pub fn one_line(&self) -> String {
+        self.one_line_colored(false)
+    }
+
+    /// `one_line` with the status code (or TRANSPORT) colored when `color` is true.
+    pub fn one_line_colored(&self, color: bool) -> String {
+        self.render(RenderOptions::default()).one_line(color)
+    }
+
+    /// This result paired with presentation options (timestamp format, ...): a `Display`
+    /// report and `one_line` output like the result's own, but shown as `opts` asks.
+    pub fn render(&self, opts: RenderOptions) -> Rendered<'_> {
+        Rendered { ws: self, opts }
+    }
+
+    // Status code as shown in one-line output ("open" for TCP probes)
+    fn code_label(&self) -> String {
+        match &self.status {
+            CheckStatus::Connected => "open".to_string(),
+            CheckStatus::Success(code) | CheckStatus::HttpError(code) => code.to_string(),
+            CheckStatus::Unchanged => NOT_MODIFIED.to_string(),
+            CheckStatus::Transport(_) => "TRANSPORT".to_string(),
+        }
+    }
+
+    /// True for `tcp://` connect probes (no HTTP status code), open or not.
+    pub fn is_tcp_probe(&self) -> bool {
+        self.url.starts_with("tcp://")
+    }
+
+    // "HEAD " etc. for one-line output; empty for the usual GET
+    fn method_prefix(&self) -> String {
+        if self.method == "GET" || self.is_tcp_probe() {
+            String::new()
+        } else {
+            format!("{} ", self.method)
+        }
     }
 
     /// Print the website status (uses Display implementation)
@@ -102,27 +483,700 @@ impl WebsiteStatus {
     }
 }
 
-// Pretty-print WebsiteStatus for console output
+// Builds the HTTP client used for a check (`cfg.timeout`, rustls via our capturing connector,
+// optional proxy). Problems with the proxy setting are pushed to `issues` and the check
+// falls back to a direct connection. Redirects are followed by `send_following`, not the agent.
+fn build_agent(cfg: &Config, tls: Arc<CapturingTlsConnector>, issues: &mut Vec<String>) -> ureq::Agent {
+    let mut builder = ureq::AgentBuilder::new()
+        .timeout(cfg.timeout)
+        .redirects(0)
+        .tls_connector(tls);
+    if let Some(n) = cfg.max_idle_connections {
+        builder = builder.max_idle_connections(n);
+    }
+    if let Some(n) = cfg.max_idle_connections_per_host {
+        builder = builder.max_idle_connections_per_host(n);
+    }
+    if let Some(proxy) = &cfg.proxy {
+        match ureq::Proxy::new(proxy) {
+            Ok(p) => builder = builder.proxy(p),
+            Err(e) => issues.push(format!(
+                "Warning: invalid proxy '{}' ({}); connecting directly",
+                proxy, e
+            )),
+        }
+    }
+    // A SOCKS proxy replaces the HTTP proxy above when both are set
+    if let Some(socks) = &cfg.socks_proxy {
+        match socks_proxy(socks) {
+            Ok(p) => builder = builder.proxy(p),
+            Err(e) => issues.push(format!(
+                "Warning: unusable SOCKS proxy '{}' ({}); not using it",
+                socks, e
+            )),
+        }
+    }
+    // Host overrides (like /etc/hosts): only the address we connect to changes. The URL keeps
+    // the original hostname, so the Host header and the TLS SNI/certificate check still use it.
+    let mut overrides: Vec<(String, IpAddr)> = Vec::new();
+    for (host, ip) in &cfg.host_overrides {
+        match ip.parse::<IpAddr>() {
+            Ok(addr) => overrides.push((host.to_ascii_lowercase(), addr)),
+            Err(_) => issues.push(format!(
+                "Warning: invalid override IP '{}' for {}; using normal DNS",
+                ip, host
+            )),
+        }
+    }
+    if !overrides.is_empty() || cfg.ip_version != IpVersion::Auto {
+        let ip_version = cfg.ip_version;
+        builder = builder.resolver(move |netloc: &str| resolve(netloc, &overrides, ip_version));
+    }
+    builder.build()
+}
+
+// Parses a `socks4://`, `socks4a://`, `socks5://` or `socks://` (SOCKS5) proxy URL.
+// ureq only speaks SOCKS with its `socks-proxy` feature, enabled by our `socks` feature;
+// without it the proxy is rejected here rather than failing every request.
+fn socks_proxy(raw: &str) -> Result<ureq::Proxy, String> {
+    let scheme = raw.split_once("://").map(|(s, _)| s.to_ascii_lowercase());
+    if !matches!(scheme.as_deref(), Some("socks4" | "socks4a" | "socks5" | "socks")) {
+        return Err("expected socks4://, socks4a://, socks5:// or socks://".to_string());
+    }
+    if !cfg!(feature = "socks") {
+        return Err("built without the `socks` feature".to_string());
+    }
+    ureq::Proxy::new(raw).map_err(|e| e.to_string())
+}
+
+/// Client-side setup shared by the checks of one `Config`: the rustls config (root
+/// certificates, client certificate read from its PEM files) and the ureq agent with its
+/// keep-alive connection pool are built once here instead of for every request.
+/// `Checker` keeps one for its whole pool; clones share the same agent.
+#[derive(Clone)]
+pub struct HttpClient {
+    connection: Result<(ureq::Agent, Arc<CapturingTlsConnector>), String>, // Err: unusable client certificate
+    warnings: Vec<String>, // proxy / host override problems, repeated in each report
+}
+
+impl HttpClient {
+    /// Sets up the client for `cfg`. Checks made with it must use the same `cfg`.
+    pub fn new(cfg: &Config) -> Self {
+        let mut warnings = Vec::new();
+        let connection = tls_client_config(cfg).map(|config| {
+            let tls = Arc::new(CapturingTlsConnector::new(config));
+            (build_agent(cfg, Arc::clone(&tls), &mut warnings), tls)
+        });
+        HttpClient { connection, warnings }
+    }
+}
+
+// rustls config for `cfg`: presents `cfg.client_cert` when set (mutual TLS)
+fn tls_client_config(cfg: &Config) -> Result<Arc<ClientConfig>, String> {
+    let identity = match &cfg.client_cert {
+        Some((cert, key)) => Some(load_client_identity(cert, key)?),
+        None => None,
+    };
+    let config = if cfg.accept_invalid_certs {
+        // Insecure: server certificates are not verified (reported by `enforce_tls_policy`)
+        insecure_client_config(identity)?
+    } else {
+        let roots = rustls::RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        client_config_with_roots(roots, identity)?
+    };
+    Ok(Arc::new(config))
+}
+
+/// One line comparing the results of `request_each_ip_version`,
+/// e.g. "IPv4: ok (200), IPv6: fail (no IPv6 address for example.com:443)".
+pub fn ip_version_summary(v4: &WebsiteStatus, v6: &WebsiteStatus) -> String {
+    let describe = |ws: &WebsiteStatus| {
+        let verdict = if ws.healthy { "ok" } else { "fail" };
+        match &ws.status {
+            CheckStatus::Success(code) | CheckStatus::HttpError(code) => format!("{} ({})", verdict, code),
+            CheckStatus::Unchanged => format!("{} ({} unchanged)", verdict, NOT_MODIFIED),
+            CheckStatus::Connected => format!("{} (open)", verdict),
+            CheckStatus::Transport(e) => format!("{} ({})", verdict, e),
+        }
+    };
+    format!("IPv4: {}, IPv6: {}", describe(v4), describe(v6))
+}
+
+// Resolves ureq's "host:port" (IPv6 hosts in brackets), applying host overrides first
+// and then keeping only addresses of the allowed IP family
+fn resolve(netloc: &str, overrides: &[(String, IpAddr)], ip_version: IpVersion) -> io::Result<Vec<SocketAddr>> {
+    let mut addrs: Vec<SocketAddr> = Vec::new();
+    if let Some((host, port)) = netloc.rsplit_once(':')
+        && let Ok(port) = port.parse::<u16>()
+    {
+        let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+        if let Some((_, ip)) = overrides.iter().find(|(h, _)| *h == host) {
+            addrs.push(SocketAddr::new(*ip, port));
+        }
+    }
+    if addrs.is_empty() {
+        addrs = netloc.to_socket_addrs()?.collect();
+    }
+    addrs.retain(|a| ip_version.allows(a));
+    if addrs.is_empty() {
+        return Err(io::Error::other(format!("no {} address for {}", ip_version.label(), netloc)));
+    }
+    Ok(addrs)
+}
+
+// Adds the configured Accept-Language / Accept-Encoding headers to a request
+fn negotiation_headers(mut req: ureq::Request, cfg: &Config) -> ureq::Request {
+    if let Some(lang) = &cfg.accept_language {
+        req = req.set("Accept-Language", lang);
+    }
+    if let Some(enc) = &cfg.accept_encoding {
+        req = req.set("Accept-Encoding", enc);
+    }
+    req
+}
+
+// Sends `method url` (with the configured headers and body) and follows up to
+// `cfg.max_redirects` redirects the way ureq would, recording each one in `chain` as
+// (status, URL that redirected). 301-303 continue as GET without a body (HEAD stays
+// HEAD); 307/308 repeat the request, but never send the body to another host. Other
+// 3xx codes, unusable Locations and refused 307/308s end the chain (see
+// `unfollowed_redirect`). When the limit is reached the last 3xx response is returned
+// (see `redirect_limit_hit`). Every request waits up to `timeout` and carries `headers`
+// (e.g. X-Request-Id). The error is boxed to keep the Result small; callers unbox it.
+fn send_following(
+    agent: &ureq::Agent,
+    method: &str,
+    url: &str,
+    timeout: Duration,
+    cfg: &Config,
+    headers: &[(&str, &str)],
+    chain: &mut Vec<(u16, String)>,
+) -> Result<ureq::Response, Box<ureq::Error>> {
+    let mut method = method.to_string();
+    let mut url = url.to_string();
+    let json_body = cfg.json_post.as_ref().map(|v| ("application/json".to_string(), v.to_string().into_bytes()));
+    let mut body = json_body.as_ref().or(cfg.request_body.as_ref());
+    loop {
+        // Timeout per request: the agent is shared with checks using other timeouts
+        let mut req = negotiation_headers(agent.request(&method, &url), cfg).timeout(timeout);
+        for &(name, value) in headers {
+            req = req.set(name, value);
+        }
+        let resp = match body {
+            Some((content_type, bytes)) => req.set("Content-Type", content_type).send_bytes(bytes),
+            None => req.call(),
+        }?;
+        let code = resp.status();
+        let next = match resp.header("Location") {
+            Some(location) if is_followed_redirect(code) && cfg.max_redirects > 0 => resolve_location(&url, location),
+            _ => return Ok(resp), // final response
+        };
+        let Some(next) = next.filter(|next| body.is_none() || matches!(code, 301..=303) || same_host(&url, next)) else {
+            return Ok(resp); // see `unfollowed_redirect`
+        };
+        chain.push((code, url.clone()));
+        if chain.len() >= cfg.max_redirects as usize {
+            return Ok(resp);
+        }
+        if matches!(code, 301..=303) {
+            if method != "HEAD" {
+                method = "GET".to_string();
+            }
+            body = None;
+        }
+        url = next;
+    }
+}
+
+// Redirect codes `send_following` follows; other 3xx responses (300, 304, ...) are final
+fn is_followed_redirect(code: u16) -> bool {
+    matches!(code, 301..=303 | 307 | 308)
+}
+
+// True when both URLs name the same host (the port may differ)
+fn same_host(a: &str, b: &str) -> bool {
+    parse_host_port(a).map(|(host, _)| host) == parse_host_port(b).map(|(host, _)| host)
+}
+
+// Why `send_following` stopped on a redirect it would normally follow (the limit
+// aside): an unusable Location, or a 307/308 that would resend the body to another host
+fn unfollowed_redirect(resp: &ureq::Response, cfg: &Config) -> Option<String> {
+    let code = resp.status();
+    let location = resp.header("Location").filter(|_| is_followed_redirect(code) && cfg.max_redirects > 0)?;
+    Some(match resolve_location(resp.get_url(), location) {
+        Some(next) => format!("Not following {} to {}: the request body is not sent to another host", code, next),
+        None => format!("Not following {}: invalid Location '{}'", code, location),
+    })
+}
+
+// True when `send_following` stopped on a redirect because `max_redirects` was reached
+fn redirect_limit_hit(resp: &ureq::Response, chain: &[(u16, String)], cfg: &Config) -> bool {
+    cfg.max_redirects > 0 && chain.len() >= cfg.max_redirects as usize && (300..400).contains(&resp.status())
+}
+
+/// Replaces the host of `url` with a token such as `host-3f9a1c`, keeping the scheme,
+/// port, path and query (user info is dropped). The same host always gets the same
+/// token, so redacted logs can still be correlated. Tokens hide names from casual
+/// readers but aren't secret: a known hostname can be hashed and compared.
+pub fn redact_url(url: &str) -> String {
+    match split_host(url) {
+        Some((scheme, host, tail)) => format!("{}://{}{}", scheme, redact_host(host), tail),
+        None => url.to_string(),
+    }
+}
+
+// `text` with the host of each "scheme://..." in it, and each whole-word occurrence of one
+// of `hosts` (any case), replaced by its token. Words run over letters, digits, '-' and '.',
+// so "a.example.com" is not touched when only "example.com" is known.
+fn redact_hosts_in(text: &str, hosts: &[&str]) -> String {
+    // URLs anywhere in the text, known or not
+    let mut urls_done = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find("://") {
+        let (head, tail) = rest.split_at(i + 3);
+        urls_done.push_str(head);
+        let end = tail
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '?' | '#' | '"' | '\'' | '<' | '>' | ',' | ')'))
+            .unwrap_or(tail.len());
+        let authority = format!("x://{}", &tail[..end]);
+        match split_host(&authority) {
+            Some((_, host, after)) if !host.trim_end_matches('.').is_empty() => {
+                let trimmed = host.trim_end_matches('.');
+                urls_done.push_str(&redact_host(trimmed));
+                urls_done.push_str(&host[trimmed.len()..]);
+                urls_done.push_str(after);
+            }
+            _ => urls_done.push_str(&tail[..end]),
+        }
+        rest = &tail[end..];
+    }
+    urls_done.push_str(rest);
+
+    // Bare mentions of known hosts; bracketed IPv6 literals can't be part of a longer word
+    let is_word_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '.';
+    let mut out = String::with_capacity(urls_done.len());
+    let mut rest = urls_done.as_str();
+    while let Some(start) = rest.find(is_word_char) {
+        out.push_str(&rest[..start]);
+        let run = &rest[start..];
+        let len = run.find(|c: char| !is_word_char(c)).unwrap_or(run.len());
+        let word = match run[..len].trim_end_matches('.') {
+            "" => &run[..len],
+            word => word,
+        };
+        if hosts.iter().any(|h| h.eq_ignore_ascii_case(word)) {
+            out.push_str(&redact_host(word));
+        } else {
+            out.push_str(word);
+        }
+        rest = &run[word.len()..];
+    }
+    out.push_str(rest);
+    for host in hosts.iter().filter(|h| h.starts_with('[')) {
+        out = out.replace(host, &redact_host(host));
+    }
+    out
+}
+
+// Stable token for a hostname (case-insensitive): "host-" plus 6 hex digits of its FNV-1a hash
+fn redact_host(host: &str) -> String {
+    format!("host-{}", &fingerprint(host.to_ascii_lowercase().as_bytes())[..6])
+}
+
+// Splits "scheme://user@host:port/path" into ("scheme", "host", ":port/path").
+// IPv6 hosts keep their brackets. None if there is no "://".
+fn split_host(url: &str) -> Option<(&str, &str, &str)> {
+    let (scheme, rest) = url.split_once("://")?;
+    let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let authority = &rest[..authority_end];
+    let host_start = authority.rfind('@').map_or(0, |i| i + 1);
+    let host_port = &authority[host_start..];
+    let host_len = if host_port.starts_with('[') {
+        host_port.find(']').map_or(host_port.len(), |i| i + 1)
+    } else {
+        host_port.find(':').unwrap_or(host_port.len())
+    };
+    Some((scheme, &host_port[..host_len], &rest[host_start + host_len..]))
+}
+
+/// Resolves a `Location` header against the URL that sent it, as a browser would
+/// (RFC 3986: "//host/x", "/x", "x", "../x", "?q" ...). None if either can't be parsed.
+pub fn resolve_location(base: &str, location: &str) -> Option<String> {
+    let next = url::Url::parse(base).ok()?.join(location).ok()?;
+    Some(next.into())
+}
+
+// Requests sent by this process so far (makes request IDs unique within a run)
+static REQUEST_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// Short ID for correlating a check with server logs: current Unix seconds and pid
+// in hex, plus a per-process counter, e.g. "6710a2c3-1f40-000c".
+fn next_request_id() -> String {
+    let count = REQUEST_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format!("{:x}-{:x}-{:04x}", secs, std::process::id(), count)
+}
+
+// Pretty-print WebsiteStatus for console output; the alternate form (`{:#}`) colors the status line
 impl fmt::Display for WebsiteStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "URL: {}", self.url)?;
-        match &self.status {
-            CheckStatus::Success(code) => writeln!(f, "Status: {} (success)", code)?,
-            CheckStatus::HttpError(code) => writeln!(f, "Status: {} (http error)", code)?,
-            CheckStatus::Transport(err) => writeln!(f, "Transport error: {}", err)?,
-        }
-        writeln!(f, "Response time (ms): {}", self.response_time.as_millis())?;
-        writeln!(f, "Timestamp (UTC): {}", self.timestamp_utc)?;
-        writeln!(f, "Validation overall ok? {}", self.validation.overall_ok())?;
-        writeln!(f, " - Header ok: {}", self.validation.header_ok)?;
-        writeln!(f, " - Body ok: {}", self.validation.body_ok)?;
-        writeln!(f, " - HTTPS policy ok: {}", self.validation.https_policy_ok)?;
-        if !self.validation.issues.is_empty() {
+        fmt::Display::fmt(&self.render(RenderOptions::default()), f)
+    }
+}
+
+// Presentation settings for reports and exports. They come from the run's `Config`
+// when results are shown, so a `WebsiteStatus` only holds what the check found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub timestamp_format: Option<TimestampFormat>, // None: timestamps as fetched
+    pub redact_hosts: bool,                        // hosts shown as `host-xxxxxx` tokens (`Config::redact_hosts`)
+}
+
+impl RenderOptions {
+    pub fn from_config(cfg: &Config) -> Self {
+        RenderOptions {
+            timestamp_format: cfg.timestamp_format,
+            redact_hosts: cfg.redact_hosts,
+        }
+    }
+
+    /// `url` as shown in reports and exports: with `redact_hosts`, its host is
+    /// replaced by a stable token (see `redact_url`).
+    pub fn url(&self, url: &str) -> String {
+        if self.redact_hosts { redact_url(url) } else { url.to_string() }
+    }
+
+    /// `text` (an issue, an error detail, a body preview, ...) as shown in reports: with
+    /// `redact_hosts`, the host of every URL in it and every whole-word mention of one of
+    /// `hosts` (e.g. the checked host in a DNS error) become their tokens.
+    pub fn text(&self, text: &str, hosts: &[&str]) -> String {
+        if self.redact_hosts { redact_hosts_in(text, hosts) } else { text.to_string() }
+    }
+
+    /// `raw` (a check's `timestamp_utc`) in the configured format; unchanged when no
+    /// format is set or it can't be parsed.
+    pub fn timestamp(&self, raw: &str) -> String {
+        match self.timestamp_format {
+            Some(fmt) => format_timestamp(raw, fmt),
+            None => raw.to_string(),
+        }
+    }
+}
+
+// A result with the options to show it with (see `WebsiteStatus::render`)
+pub struct Rendered<'a> {
+    ws: &'a WebsiteStatus,
+    opts: RenderOptions,
+}
+
+impl Rendered<'_> {
+    /// `WebsiteStatus::one_line_colored` with these options.
+    pub fn one_line(&self, color: bool) -> String {
+        let ws = self.ws;
+        let verdict = if ws.healthy { "ok" } else { "fail" };
+        let ts = self.opts.timestamp(&ws.timestamp_utc);
+        match &ws.status {
+            CheckStatus::Success(_) | CheckStatus::HttpError(_) | CheckStatus::Connected | CheckStatus::Unchanged => format!(
+                "[{}] {}{} {} {}ms {}",
+                ts,
+                ws.method_prefix(),
+                self.opts.url(&ws.url),
+                paint_status(&ws.code_label(), &ws.status, color),
+                ws.response_time.as_millis(),
+                verdict
+            ),
+            CheckStatus::Transport(err) => {
+                // Keep multi-line error messages on one line
+                let detail = self.opts.text(&err.split_whitespace().collect::<Vec<_>>().join(" "), &ws.hosts());
+                format!(
+                    "[{}] {}{} {} {}",
+                    ts,
+                    ws.method_prefix(),
+                    self.opts.url(&ws.url),
+                    paint_status("TRANSPORT", &ws.status, color),
+                    detail
+                )
+            }
+        }
+    }
+}
+
+// Same report as `WebsiteStatus`'s Display, shown with `opts`
+impl fmt::Display for Rendered<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ws = self.ws;
+        let hosts = ws.hosts();
+        writeln!(f, "URL: {}", self.opts.url(&ws.url))?;
+        if ws.method != "GET" {
+            writeln!(f, "Method: {}", ws.method)?;
+        }
+        let status_line = match &ws.status {
+            CheckStatus::Connected => "Status: port open (tcp)".to_string(),
+            CheckStatus::Unchanged => format!("Status: {} (unchanged)", NOT_MODIFIED),
+            CheckStatus::Success(code) => format!("Status: {} (success)", code),
+            CheckStatus::HttpError(code) => format!("Status: {} (http error)", code),
+            CheckStatus::Transport(err) => format!("Transport error: {}", self.opts.text(err, &hosts)),
+        };
+        writeln!(f, "{}", paint_status(&status_line, &ws.status, f.alternate()))?;
+        writeln!(f, "Response time (ms): {}", ws.response_time.as_millis())?;
+        writeln!(f, "Timestamp (UTC): {}", self.opts.timestamp(&ws.timestamp_utc))?;
+        if let Some(version) = &ws.tls_version {
+            match &ws.tls_cipher {
+                Some(cipher) => writeln!(f, "TLS: {} ({})", version, cipher)?,
+                None => writeln!(f, "TLS: {}", version)?,
+            }
+        }
+        if let Some(fp) = &ws.body_fingerprint {
+            writeln!(f, "Body fingerprint: {}", fp)?;
+        }
+        if ws.attempts > 1 {
+            writeln!(f, "Attempts: {}", ws.attempts)?;
+        }
+        if !ws.request_id.is_empty() {
+            writeln!(f, "Request ID: {}", ws.request_id)?;
+        }
+        if !ws.redirect_chain.is_empty() {
+            let hops: Vec<String> = ws
+                .redirect_chain
+                .iter()
+                .map(|(code, url)| format!("{} {}", code, self.opts.url(url)))
+                .collect();
+            writeln!(f, "Redirects: {}", hops.join(" -> "))?;
+        }
+        writeln!(f, "Healthy: {}", ws.healthy)?;
+        writeln!(f, "Validation overall ok? {}", ws.validation.overall_ok())?;
+        writeln!(f, " - Header ok: {}", ws.validation.header_ok)?;
+        writeln!(f, " - Body ok: {}", ws.validation.body_ok)?;
+        writeln!(f, " - HTTPS policy ok: {}", ws.validation.https_policy_ok)?;
+        writeln!(f, " - Redirect ok: {}", ws.validation.redirect_ok)?;
+        writeln!(f, " - TLS policy ok: {}", ws.validation.tls_policy_ok)?;
+        if !ws.validation.issues.is_empty() {
             writeln!(f, "Issues:")?;
-            for issue in &self.validation.issues {
-                writeln!(f, " * {}", issue)?;
+            for issue in &ws.validation.issues {
+                writeln!(f, " * {}", self.opts.text(issue, &hosts))?;
             }
         }
+        if let Some(preview) = &ws.validation.body_preview {
+            writeln!(f, "Body preview: {}", self.opts.text(preview, &hosts))?;
+        }
+        if let Some(t) = &ws.validation.timing {
+            writeln!(
+                f,
+                "Timing: connect +{}ms, first byte +{}ms, done +{}ms",
+                t.connect_start_ms, t.ttfb_ms, t.total_ms
+            )?;
+        }
         Ok(())
     }
 }
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(status: CheckStatus) -> WebsiteStatus {
+        WebsiteStatus {
+            status,
+            response_time: Duration::from_millis(10),
+            attempts: 1,
+            ..WebsiteStatus::not_sent("https://example.com", "GET", "2020-01-01T00:00:00Z", "")
+        }
+    }
+
+    #[test]
+    fn accessors_match_status_kind() {
+        let ok = status(CheckStatus::Success(200));
+        assert_eq!(ok.as_success_code(), Some(200));
+        assert_eq!(ok.as_http_error(), None);
+        assert_eq!(ok.transport_detail(), None);
+        assert_eq!(ok.kind_str(), "success");
+
+        let err = status(CheckStatus::HttpError(503));
+        assert_eq!(err.as_success_code(), None);
+        assert_eq!(err.as_http_error(), Some(503));
+        assert_eq!(err.transport_detail(), None);
+        assert_eq!(err.kind_str(), "http_error");
+
+        let transport = status(CheckStatus::Transport("dns failed".into()));
+        assert_eq!(transport.as_success_code(), None);
+        assert_eq!(transport.as_http_error(), None);
+        assert_eq!(transport.transport_detail(), Some("dns failed"));
+        assert_eq!(transport.kind_str(), "transport");
+    }
+
+    #[test]
+    fn redaction_maps_each_host_to_a_stable_token() {
+        let a = redact_url("https://internal.corp.example/health?x=1");
+        let again = redact_url("https://INTERNAL.corp.example/other");
+        let other = redact_url("https://billing.corp.example/health?x=1");
+
+        // Same host (any case) -> same token; scheme, path and query are kept
+        let token = a.strip_prefix("https://").unwrap().split('/').next().unwrap();
+        assert!(token.starts_with("host-") && token.len() == 11, "{}", token);
+        assert_eq!(a, format!("https://{}/health?x=1", token));
+        assert_eq!(again, format!("https://{}/other", token));
+        assert_ne!(other, a);
+
+        // Ports stay, user info goes; IPv6 literals and tcp probes work too
+        assert_eq!(redact_url("http://user:pw@internal.corp.example:8080/"), format!("http://{}:8080/", token));
+        assert!(redact_url("http://[::1]:9000/x").ends_with(":9000/x"));
+        assert!(!redact_url("tcp://db.internal:5432").contains("db.internal"));
+        assert_eq!(redact_url("not a url"), "not a url");
+
+        // Every report form hides the host once the flag is set
+        let mut ws = status(CheckStatus::Transport("dns error: failed to lookup example.com".into()));
+        ws.redirect_chain = vec![(301, "https://example.com/old".into())];
+        ws.validation.issues = vec!["Transport error: example.com unreachable".into()];
+        ws.validation.body_preview = Some("<a href=\"https://cdn.example.com/x\">example.com</a>".into());
+        let redacted = RenderOptions {
+            redact_hosts: true,
+            ..RenderOptions::default()
+        };
+        for out in [ws.render(redacted).to_string(), ws.render(redacted).one_line(false)] {
+            assert!(!out.contains("example.com"), "{}", out);
+            assert!(out.contains(&redact_host("example.com")));
+        }
+        assert_eq!(redacted.url(&ws.url), redact_url("https://example.com"));
+        assert!(ws.to_string().contains("URL: https://example.com\n"));
+    }
+
+    #[test]
+    fn redacted_text_only_replaces_whole_hosts() {
+        let opts = RenderOptions {
+            redact_hosts: true,
+            ..RenderOptions::default()
+        };
+        let token = redact_host("example.com");
+
+        // Whole words only, any case; longer names and unrelated words are kept
+        assert_eq!(
+            opts.text("lookup EXAMPLE.COM failed (api.example.com, example.community)", &["example.com"]),
+            format!("lookup {} failed (api.example.com, example.community)", token)
+        );
+        // A sentence-ending dot is not part of the host
+        assert_eq!(opts.text("cannot reach example.com.", &["example.com"]), format!("cannot reach {}.", token));
+        // Hosts of URLs are redacted even when not known, user info included
+        assert_eq!(
+            opts.text("Redirected off-host: https://example.com/a -> http://u@other.test:8080/b", &[]),
+            format!("Redirected off-host: https://{}/a -> http://{}:8080/b", token, redact_host("other.test"))
+        );
+        // Without the flag the text is untouched
+        assert_eq!(RenderOptions::default().text("example.com", &["example.com"]), "example.com");
+    }
+
+    #[test]
+    fn one_line_format_per_status_kind() {
+        let mut ok = status(CheckStatus::Success(200));
+        ok.healthy = true;
+        assert_eq!(ok.one_line(), "[2020-01-01T00:00:00Z] https://example.com 200 10ms ok");
+
+        let err = status(CheckStatus::HttpError(503));
+        assert_eq!(err.one_line(), "[2020-01-01T00:00:00Z] https://example.com 503 10ms fail");
+
+        let transport = status(CheckStatus::Transport("dns failed:\n  no such host".into()));
+        assert_eq!(
+            transport.one_line(),
+            "[2020-01-01T00:00:00Z] https://example.com TRANSPORT dns failed: no such host"
+        );
+
+        let mut unchanged = status(CheckStatus::Unchanged);
+        unchanged.healthy = true;
+        assert_eq!(unchanged.one_line(), "[2020-01-01T00:00:00Z] https://example.com 304 10ms ok");
+        assert!(unchanged.to_string().contains("Status: 304 (unchanged)\n"));
+        assert_eq!(unchanged.kind_str(), "unchanged");
+    }
+
+    #[test]
+    fn color_only_when_requested() {
+        let ws = status(CheckStatus::HttpError(503));
+        assert!(!ws.one_line().contains('\x1b'));
+        assert!(!ws.one_line_colored(false).contains('\x1b'));
+        assert!(!ws.to_string().contains('\x1b'));
+
+        assert!(ws.one_line_colored(true).contains("\x1b[33m503\x1b[0m"));
+        assert!(format!("{:#}", ws).contains("\x1b[33mStatus: 503 (http error)\x1b[0m"));
+    }
+
+    #[test]
+    fn reports_use_the_configured_timestamp_format() {
+        let mut ws = status(CheckStatus::Success(200));
+        let unix = RenderOptions {
+            timestamp_format: Some(TimestampFormat::UnixSeconds),
+            ..RenderOptions::default()
+        };
+        assert!(ws.render(unix).one_line(false).starts_with("[1577836800] "));
+        assert!(ws.render(unix).to_string().contains("Timestamp (UTC): 1577836800\n"));
+
+        // Without a format the timestamp is shown as fetched
+        assert!(ws.one_line().starts_with("[2020-01-01T00:00:00Z] "));
+        assert!(ws.to_string().contains("Timestamp (UTC): 2020-01-01T00:00:00Z\n"));
+
+        ws.timestamp_utc = "unknown".into();
+        assert!(ws.render(unix).one_line(false).starts_with("[unknown] "));
+    }
+
+    #[test]
+    fn accessors_compose_with_filter_map() {
+        let results = [
+            status(CheckStatus::Success(200)),
+            status(CheckStatus::HttpError(404)),
+            status(CheckStatus::Success(204)),
+        ];
+        let codes: Vec<u16> = results.iter().filter_map(|r| r.as_success_code()).collect();
+        assert_eq!(codes, vec![200, 204]);
+    }
+
+    #[test]
+    fn location_resolves_against_the_redirecting_url() {
+        let base = "http://127.0.0.1:8080/a/b?x=1";
+        let resolve = |base, location| resolve_location(base, location).unwrap();
+        assert_eq!(resolve(base, "https://other.test/"), "https://other.test/");
+        assert_eq!(resolve(base, "//cdn.test/img"), "http://cdn.test/img");
+        assert_eq!(resolve(base, "/login"), "http://127.0.0.1:8080/login");
+        assert_eq!(resolve(base, "c"), "http://127.0.0.1:8080/a/c");
+        assert_eq!(resolve("http://h.test", "next"), "http://h.test/next");
+        assert_eq!(resolve("http://h.test/a/b", "?page=2"), "http://h.test/a/b?page=2");
+        assert_eq!(resolve("http://h.test/a/b/c", "../d"), "http://h.test/a/d");
+        assert_eq!(resolve("http://h.test/a/b", "../../../d"), "http://h.test/d");
+        assert_eq!(resolve_location("not a url", "/x"), None);
+    }
+
+    #[test]
+    fn client_clones_share_one_tls_config() {
+        let client = HttpClient::new(&Config::default());
+        let copy = client.clone();
+        let connector = |c: &HttpClient| Arc::clone(&c.connection.as_ref().unwrap().1);
+        assert!(Arc::ptr_eq(&connector(&client), &connector(&copy)));
+
+        // A bad client certificate is kept as the error every check will report
+        let cfg = Config::builder().client_cert("/nonexistent/cert.pem", "/nonexistent/key.pem").build();
+        let err = HttpClient::new(&cfg).connection.err().unwrap();
+        assert!(err.contains("/nonexistent/cert.pem"), "{}", err);
+    }
+
+    #[test]
+    fn socks_proxy_needs_a_socks_url_and_the_feature() {
+        let err = socks_proxy("http://127.0.0.1:3128").unwrap_err();
+        assert!(err.starts_with("expected socks4://"), "{}", err);
+
+        let parsed = socks_proxy("socks5://127.0.0.1:1080");
+        if cfg!(feature = "socks") {
+            assert!(parsed.is_ok());
+        } else {
+            assert_eq!(parsed.unwrap_err(), "built without the `socks` feature");
+        }
+
+        // An unusable SOCKS proxy is reported and the agent still builds (direct connection)
+        let cfg = Config::builder().socks_proxy("ftp://nope:21").build();
+        let client = HttpClient::new(&cfg);
+        assert!(client.connection.is_ok());
+        assert_eq!(client.warnings.len(), 1);
+        assert!(client.warnings[0].starts_with("Warning: unusable SOCKS proxy 'ftp://nope:21'"), "{:?}", client.warnings);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::stats::Stats;
+use crate::status::{CheckStatus, RenderOptions, WebsiteStatus, NOT_MODIFIED};
+
+// Cell colors for healthy / unhealthy rows
+const GREEN: &str = "#c8f7c5";
+const RED: &str = "#f7c5c5";
+
+/// Renders a minimal self-contained HTML status page: the batch summary at the top,
+/// then one table row per result (URL, status, latency, ok?) with a green or red
+/// verdict cell. Text from results is HTML-escaped; hosts are shown as `render` asks.
+pub fn render_status_html(results: &[WebsiteStatus], stats: &Stats, render: RenderOptions) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Website status</title>\n");
+    out.push_str("<style>table{border-collapse:collapse}th,td{border:1px solid #999;padding:4px 8px}</style>\n");
+    out.push_str("</head>\n<body>\n<h1>Website status</h1>\n");
+    out.push_str(&format!(
+        "<p>{} checked, {} healthy, uptime {:.2}%, avg {:.2} ms</p>\n",
+        stats.total, stats.fully_healthy, stats.uptime_pct, stats.avg_response_ms
+    ));
+
+    out.push_str("<table>\n<tr><th>URL</th><th>Status</th><th>Latency</th><th>OK?</th></tr>\n");
+    for ws in results {
+        let (color, verdict) = if ws.healthy { (GREEN, "yes") } else { (RED, "no") };
+        out.push_str(&format!(
+            "<tr><td>{}</td><td style=\"background:{}\">{}</td><td>{}</td><td style=\"background:{}\">{}</td></tr>\n",
+            escape_html(&render.url(&ws.url)),
+            color,
+            escape_html(&status_label(ws, render)),
+            latency_label(ws),
+            color,
+            verdict
+        ));
+    }
+    out.push_str("</table>\n</body>\n</html>\n");
+    out
+}
+
+// Status cell text: the code, "open" for TCP probes, or the transport error
+fn status_label(ws: &WebsiteStatus, render: RenderOptions) -> String {
+    match &ws.status {
+        CheckStatus::Connected => "open".to_string(),
+        CheckStatus::Success(code) | CheckStatus::HttpError(code) => code.to_string(),
+        CheckStatus::Unchanged => format!("{} unchanged", NOT_MODIFIED),
+        CheckStatus::Transport(err) => format!("transport error: {}", render.text(err, &ws.hosts())),
+    }
+}
+
+// Latency cell text; transport errors have no meaningful response time
+fn latency_label(ws: &WebsiteStatus) -> String {
+    match ws.status {
+        CheckStatus::Transport(_) => "-".to_string(),
+        _ => format!("{} ms", ws.response_time.as_millis()),
+    }
+}
+
+// Escapes the characters that matter inside HTML text
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn result(url: &str, status: CheckStatus, healthy: bool) -> WebsiteStatus {
+        WebsiteStatus {
+            status,
+            healthy,
+            response_time: Duration::from_millis(120),
+            ..WebsiteStatus::not_sent(url, "GET", "2020-01-01T00:00:00Z", "")
+        }
+    }
+
+    #[test]
+    fn html_has_a_row_per_url_and_a_closed_table() {
+        let results = vec![
+            result("https://a.test", CheckStatus::Success(200), true),
+            result("https://b.test/?x=1&y=<2>", CheckStatus::HttpError(503), false),
+            result("https://c.test", CheckStatus::Transport("dns error".into()), false),
+        ];
+        let html = render_status_html(&results, &Stats::compute(&results), RenderOptions::default());
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("3 checked") && html.contains("uptime 33.33%"));
+        assert!(html.contains("https://a.test"));
+        assert!(html.contains("https://b.test/?x=1&amp;y=&lt;2&gt;"));
+        assert!(html.contains("https://c.test"));
+        assert!(html.contains("transport error: dns error"));
+
+        // One header row plus one row per result, every tag closed
+        assert_eq!(html.matches("<table>").count(), 1);
+        assert_eq!(html.matches("</table>").count(), 1);
+        assert_eq!(html.matches("<tr>").count(), 4);
+        assert_eq!(html.matches("</tr>").count(), 4);
+        assert_eq!(html.matches("<td").count(), html.matches("</td>").count());
+        assert!(html.find("<table>").unwrap() < html.find("</table>").unwrap());
+
+        assert_eq!(html.matches(GREEN).count(), 2);
+        assert_eq!(html.matches(RED).count(), 4);
+
+        // Redacted pages show tokens instead of hosts
+        let redacted = RenderOptions {
+            redact_hosts: true,
+            ..RenderOptions::default()
+        };
+        let html = render_status_html(&results, &Stats::compute(&results), redacted);
+        assert!(!html.contains("a.test") && !html.contains("c.test"));
+        assert!(html.contains(&redacted.url("https://a.test")));
+    }
+}
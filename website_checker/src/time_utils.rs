@@ -15,26 +15,419 @@ struct TimeApiResp {
     date_time: String,
 }
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+// How many network time fetches were attempted (lets tests prove none happened)
+static NETWORK_TIME_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of `fetch_network_time_utc*` calls made so far in this process.
+pub fn network_time_calls() -> usize {
+    NETWORK_TIME_CALLS.load(Ordering::Relaxed)
+}
+
+// Spaces out calls to at most `per_sec` per second. Slots are handed out in order,
+// so a burst of callers is queued evenly instead of all hitting the API at once.
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: std::time::Duration, // minimum gap between two calls
+    next: Option<Instant>,         // earliest time the next call may start
+}
+
+impl RateLimiter {
+    /// Limiter allowing `per_sec` calls per second (at least 1).
+    pub fn per_sec(per_sec: u32) -> Self {
+        RateLimiter {
+            interval: std::time::Duration::from_secs(1) / per_sec.max(1),
+            next: None,
+        }
+    }
+
+    /// Reserves the next free slot for a call made at `now` and returns how long
+    /// the caller has to wait for it (zero if the limiter is idle).
+    pub fn reserve(&mut self, now: Instant) -> std::time::Duration {
+        let slot = self.next.map_or(now, |next| next.max(now));
+        self.next = Some(slot + self.interval);
+        slot - now
+    }
+}
+
+// Process-wide limit for time API calls (None = unlimited), see `configure_time_rate_limit`
+static TIME_API_LIMIT: Mutex<Option<RateLimiter>> = Mutex::new(None);
+
+/// Limits time API requests from this process to `per_sec` per second; 0 turns the limit off.
+/// Applies to every `fetch_network_time_utc*` call (e.g. many `request_with` calls in a row),
+/// so bursts don't get the client blocked by timeapi.io.
+pub fn configure_time_rate_limit(per_sec: u32) {
+    *TIME_API_LIMIT.lock().unwrap() = (per_sec > 0).then(|| RateLimiter::per_sec(per_sec));
+}
+
+// Blocks until `limit` allows another call. The lock is only held to reserve a slot,
+// so waiting callers don't block each other.
+fn throttle(limit: &Mutex<Option<RateLimiter>>) {
+    let wait = match limit.lock().unwrap().as_mut() {
+        Some(limiter) => limiter.reserve(Instant::now()),
+        None => return,
+    };
+    if !wait.is_zero() {
+        std::thread::sleep(wait);
+    }
+}
+
+/// Timestamp for a check: from the time API when `fetch` is true,
+/// otherwise from the local clock (no network call).
+pub fn timestamp_utc(fetch: bool) -> Result<String, String> {
+    if fetch {
+        fetch_network_time_utc()
+    } else {
+        Ok(system_time_utc())
+    }
+}
+
+/// Like `timestamp_utc`, but also returns how long getting the timestamp took.
+pub fn timestamp_utc_timed(fetch: bool) -> (Result<String, String>, std::time::Duration) {
+    let start = std::time::Instant::now();
+    let ts = timestamp_utc(fetch);
+    (ts, start.elapsed())
+}
+
+// Time API calls slower than this get a warning (suggesting --local-time)
+pub const SLOW_TIME_API: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Warning text when getting a timestamp took longer than `threshold`, else None.
+pub fn slow_time_warning(elapsed: std::time::Duration, threshold: std::time::Duration) -> Option<String> {
+    (elapsed > threshold).then(|| {
+        format!(
+            "Time API took {} ms (over {} ms); consider --local-time",
+            elapsed.as_millis(),
+            threshold.as_millis()
+        )
+    })
+}
+
+/// Current local system time as RFC 3339 UTC, e.g. "2024-02-29T12:34:56Z".
+pub fn system_time_utc() -> String {
+    format_rfc3339(unix_now())
+}
+
+/// Current local system time as seconds since the Unix epoch.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Formats seconds since the Unix epoch as RFC 3339 UTC.
+pub fn format_rfc3339(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    let (hour, min, sec) = (rem / 3600, rem % 3600 / 60, rem % 60);
+
+    // Days since epoch -> civil date (Howard Hinnant's days_from_civil, inverted)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, hour, min, sec)
+}
+
+// How timestamps are shown in reports and exports. The raw value is always kept as-is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampFormat {
+    Iso8601,     // "2024-02-29T12:34:56Z"
+    UnixSeconds, // "1709210096"
+    Rfc2822,     // "Thu, 29 Feb 2024 12:34:56 +0000"
+}
+
+impl TimestampFormat {
+    /// Parses "iso8601"/"iso", "unix" or "rfc2822" (case-insensitive).
+    pub fn from_name(name: &str) -> Option<TimestampFormat> {
+        match name.to_ascii_lowercase().as_str() {
+            "iso8601" | "iso" => Some(TimestampFormat::Iso8601),
+            "unix" | "unix_seconds" => Some(TimestampFormat::UnixSeconds),
+            "rfc2822" => Some(TimestampFormat::Rfc2822),
+            _ => None,
+        }
+    }
+}
+
+/// Reformats an ISO 8601 timestamp (as returned by the time API or `system_time_utc`).
+/// Anything that can't be parsed (e.g. "unknown") is returned unchanged.
+pub fn format_timestamp(raw_iso: &str, fmt: TimestampFormat) -> String {
+    let Some(secs) = parse_iso8601(raw_iso) else {
+        return raw_iso.to_string();
+    };
+    match fmt {
+        TimestampFormat::Iso8601 => format_rfc3339(secs),
+        TimestampFormat::UnixSeconds => secs.to_string(),
+        TimestampFormat::Rfc2822 => format_rfc2822(secs),
+    }
+}
+
+/// Parses "YYYY-MM-DDTHH:MM:SS" with optional fractional seconds and an optional
+/// "Z" or "+HH:MM"/"-HH:MM" offset (none means UTC) into seconds since the Unix epoch.
+pub fn parse_iso8601(raw: &str) -> Option<u64> {
+    let raw = raw.trim();
+    let b = raw.as_bytes();
+    if b.len() < 19 || b[4] != b'-' || b[7] != b'-' || !matches!(b[10], b'T' | b't' | b' ') || b[13] != b':' || b[16] != b':' {
+        return None;
+    }
+    let num = |range: std::ops::Range<usize>| -> Option<i64> {
+        let part = raw.get(range)?;
+        if part.bytes().all(|c| c.is_ascii_digit()) { part.parse().ok() } else { None }
+    };
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (hour, min, sec) = (num(11..13)?, num(14..16)?, num(17..19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || min > 59 || sec > 60 {
+        return None;
+    }
+
+    // Skip fractional seconds, then read the offset
+    let mut rest = &raw[19..];
+    if let Some(frac) = rest.strip_prefix('.') {
+        let digits = frac.bytes().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return None;
+        }
+        rest = &frac[digits..];
+    }
+    let offset_secs = match rest {
+        "" | "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let (h, m) = rest[1..].split_once(':').unwrap_or((rest.get(1..3)?, rest.get(3..)?));
+            if h.len() != 2 || m.len() != 2 {
+                return None;
+            }
+            sign * (h.parse::<i64>().ok()? * 3600 + m.parse::<i64>().ok()? * 60)
+        }
+    };
+
+    // Civil date -> days since epoch (Howard Hinnant's days_from_civil)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let secs = days * 86_400 + hour * 3600 + min * 60 + sec - offset_secs;
+    u64::try_from(secs).ok()
+}
+
+// Seconds since the Unix epoch as RFC 2822, e.g. "Thu, 29 Feb 2024 12:34:56 +0000"
+fn format_rfc2822(secs: u64) -> String {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"]; // 1970-01-01 was a Thursday
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let iso = format_rfc3339(secs); // "YYYY-MM-DDTHH:MM:SSZ"
+    let month: usize = iso[5..7].parse().unwrap_or(1);
+    format!(
+        "{}, {} {} {} {} +0000",
+        WEEKDAYS[(secs / 86_400 % 7) as usize],
+        &iso[8..10],
+        MONTHS[month - 1],
+        &iso[0..4],
+        &iso[11..19]
+    )
+}
+
+/// Parses an HTTP date (RFC 1123, e.g. "Sun, 06 Nov 1994 08:49:37 GMT", as in `Date`
+/// and `Last-Modified` headers) into seconds since the Unix epoch. "+0000" / "UTC" are
+/// accepted in place of "GMT"; other zones and formats give None.
+pub fn parse_http_date(raw: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let (_weekday, rest) = raw.trim().split_once(", ")?;
+    let parts: Vec<&str> = rest.split_whitespace().collect();
+    let [day, month, year, time, zone] = parts[..] else {
+        return None;
+    };
+    if !matches!(zone, "GMT" | "UTC" | "+0000") || day.len() != 2 || year.len() != 4 || time.len() != 8 {
+        return None;
+    }
+    let month = MONTHS.iter().position(|m| m.eq_ignore_ascii_case(month))? + 1;
+    // Same fields as ISO 8601, so reuse that parser for validation and the date math
+    parse_iso8601(&format!("{}-{:02}-{}T{}Z", year, month, day, time))
+}
+
+// Attempts made by `fetch_network_time_utc` before giving up, and the pause between them
+pub const TIME_API_ATTEMPTS: u32 = 3;
+pub const TIME_API_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(250);
+
 #[cfg(not(test))]
 pub fn fetch_network_time_utc() -> Result<String, String> {
+    fetch_network_time_utc_with(TIME_API_ATTEMPTS, TIME_API_RETRY_DELAY)
+}
+
+/// Like `fetch_network_time_utc`, but also returns the elapsed time of the call
+/// (retries included), so slow and failing time lookups can be told apart.
+pub fn fetch_network_time_utc_timed() -> (Result<String, String>, std::time::Duration) {
+    timestamp_utc_timed(true)
+}
+
+// Fetches the time with up to `attempts` tries (at least one), sleeping `delay` between them.
+// Each attempt has its own 5s timeout; the last error is returned if all fail.
+#[cfg(not(test))]
+pub fn fetch_network_time_utc_with(attempts: u32, delay: Duration) -> Result<String, String> {
+    NETWORK_TIME_CALLS.fetch_add(1, Ordering::Relaxed);
+
     // If TEST_FAKE_TIME is set, return a fixed timestamp (used for integration tests)
     if std::env::var("TEST_FAKE_TIME").is_ok() {
         return Ok("2020-01-01T00:00:00Z".into());
     }
 
-    // Make request to external time API with a 5s timeout
-    match ureq::get(TIME_API).timeout(Duration::from_secs(5)).call() {
-        Ok(resp) => match resp.into_json::<TimeApiResp>() {
-            Ok(v) => Ok(v.date_time), // return parsed timestamp
-            Err(e) => Err(format!("Failed to parse time JSON: {}", e)),
-        },
-        Err(e) => Err(format!("Time request failed: {}", e)),
+    let mut last_err = String::new();
+    for attempt in 1..=attempts.max(1) {
+        if attempt > 1 {
+            std::thread::sleep(delay);
+        }
+        // Make request to external time API with a 5s timeout (waiting for the rate limit first)
+        throttle(&TIME_API_LIMIT);
+        match ureq::get(TIME_API).timeout(Duration::from_secs(5)).call() {
+            Ok(resp) => match resp.into_json::<TimeApiResp>() {
+                Ok(v) => return Ok(v.date_time), // return parsed timestamp
+                Err(e) => last_err = format!("Failed to parse time JSON: {}", e),
+            },
+            Err(e) => last_err = format!("Time request failed: {}", e),
+        }
     }
+    Err(format!("{} (after {} attempts)", last_err, attempts.max(1)))
 }
 
 // --- Test-only stub (used for unit tests within this crate) ---
 #[cfg(test)]
 pub fn fetch_network_time_utc() -> Result<String, String> {
+    NETWORK_TIME_CALLS.fetch_add(1, Ordering::Relaxed);
     // Always returns a fixed value during tests
     Ok("2020-01-01T00:00:00Z".into())
 }
+
+#[cfg(test)]
+pub fn fetch_network_time_utc_with(_attempts: u32, _delay: std::time::Duration) -> Result<String, String> {
+    fetch_network_time_utc()
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_epoch_seconds_as_rfc3339() {
+        assert_eq!(format_rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_rfc3339(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_rfc3339(1_709_210_096), "2024-02-29T12:34:56Z");
+        assert_eq!(format_rfc3339(1_735_689_599), "2024-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn formats_raw_timestamps() {
+        // Shape returned by timeapi.io: no offset, 7 fractional digits
+        let raw = "2024-02-29T12:34:56.1234567";
+        assert_eq!(format_timestamp(raw, TimestampFormat::Iso8601), "2024-02-29T12:34:56Z");
+        assert_eq!(format_timestamp(raw, TimestampFormat::UnixSeconds), "1709210096");
+        assert_eq!(format_timestamp(raw, TimestampFormat::Rfc2822), "Thu, 29 Feb 2024 12:34:56 +0000");
+
+        // Offsets are converted to UTC
+        assert_eq!(format_timestamp("2024-02-29T14:34:56+02:00", TimestampFormat::UnixSeconds), "1709210096");
+        assert_eq!(format_timestamp("1970-01-01T00:00:00Z", TimestampFormat::Rfc2822), "Thu, 01 Jan 1970 00:00:00 +0000");
+    }
+
+    #[test]
+    fn parses_http_dates() {
+        assert_eq!(parse_http_date("Thu, 29 Feb 2024 12:34:56 GMT"), Some(1_709_210_096));
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(784_111_777));
+        // Round-trips our own RFC 2822 output
+        assert_eq!(parse_http_date(&format_rfc2822(1_709_210_096)), Some(1_709_210_096));
+
+        for bad in ["", "yesterday", "Thu, 29 Feb 2024 12:34:56 PST", "Thu, 29 Foo 2024 12:34:56 GMT", "Thu, 30 Feb 2024 25:00:00 GMT"] {
+            assert_eq!(parse_http_date(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn unparseable_timestamps_pass_through() {
+        for raw in ["unknown", "", "2024-13-01T00:00:00Z", "2024-02-29 noon", "2024-02-29T12:34:56+2"] {
+            assert_eq!(format_timestamp(raw, TimestampFormat::UnixSeconds), raw);
+        }
+        assert_eq!(TimestampFormat::from_name("UNIX"), Some(TimestampFormat::UnixSeconds));
+        assert_eq!(TimestampFormat::from_name("epoch"), None);
+    }
+
+    #[test]
+    fn rate_limiter_queues_a_burst_evenly() {
+        let ms = std::time::Duration::from_millis;
+        let mut limiter = RateLimiter::per_sec(5); // one call every 200ms
+        let t0 = Instant::now();
+
+        // Four calls at the same moment get consecutive slots
+        let waits: Vec<_> = (0..4).map(|_| limiter.reserve(t0)).collect();
+        assert_eq!(waits, vec![ms(0), ms(200), ms(400), ms(600)]);
+
+        // After the queue drains, a call goes straight through
+        assert_eq!(limiter.reserve(t0 + ms(2000)), ms(0));
+        assert_eq!(limiter.reserve(t0 + ms(2100)), ms(100));
+    }
+
+    #[test]
+    fn throttle_spaces_out_concurrent_callers() {
+        let limit = std::sync::Arc::new(Mutex::new(Some(RateLimiter::per_sec(20)))); // 50ms apart
+        let start = Instant::now();
+        let handles: Vec<_> = (0..5)
+            .map(|_| {
+                let limit = std::sync::Arc::clone(&limit);
+                std::thread::spawn(move || {
+                    throttle(&limit);
+                    start.elapsed()
+                })
+            })
+            .collect();
+        let mut times: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        times.sort();
+        // Slots at 0, 50, 100, 150, 200ms: the last caller waited for four others
+        assert!(times[4] >= std::time::Duration::from_millis(200), "times: {:?}", times);
+
+        // No limit configured: no waiting
+        let unlimited = Mutex::new(None);
+        let t = Instant::now();
+        for _ in 0..100 {
+            throttle(&unlimited);
+        }
+        assert!(t.elapsed() < std::time::Duration::from_millis(50));
+    }
+
+    #[test]
+    fn local_timestamp_skips_the_network() {
+        let before = network_time_calls();
+        let ts = timestamp_utc(false).unwrap();
+        assert_eq!(network_time_calls(), before);
+        assert_eq!(ts.len(), 20);
+        assert!(ts.ends_with('Z'));
+    }
+
+    #[test]
+    fn timed_fetch_reports_elapsed_and_warns_when_slow() {
+        let (ts, elapsed) = fetch_network_time_utc_timed();
+        assert_eq!(ts.unwrap(), "2020-01-01T00:00:00Z");
+        assert!(elapsed < SLOW_TIME_API);
+
+        let ms = std::time::Duration::from_millis;
+        assert_eq!(slow_time_warning(ms(500), ms(2000)), None);
+        assert_eq!(
+            slow_time_warning(ms(2500), ms(2000)).unwrap(),
+            "Time API took 2500 ms (over 2000 ms); consider --local-time"
+        );
+    }
+}
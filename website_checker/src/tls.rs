@@ -0,0 +1,283 @@
+// TLS support for checks.
+//
+// ureq (with its default `tls` feature) uses rustls, but does not expose the
+// negotiated session. To report the TLS version and cipher we plug our own
+// connector into ureq (`AgentBuilder::tls_connector`). It performs the same
+// rustls handshake ureq would, and remembers what was negotiated.
+// This assumes the rustls backend; with another backend the fields stay None.
+// Client certificates (mutual TLS, `Config::client_cert`) and disabled certificate
+// verification (`Config::accept_invalid_certs`) also go through this connector,
+// so they need the rustls backend (ureq's default `tls` feature) too.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::{ClientConfig, ClientConnection, DigitallySignedStruct, ProtocolVersion, SignatureScheme, StreamOwned};
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use ureq::{ReadWrite, TlsConnector};
+
+// What was negotiated for a TLS connection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsInfo {
+    pub version: String, // e.g. "TLS 1.3"
+    pub cipher: String,  // e.g. "TLS13_AES_128_GCM_SHA256"
+}
+
+// TLS protocol versions, ordered oldest to newest (usable as a policy floor)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TlsVersion {
+    Tls10,
+    Tls11,
+    Tls12,
+    Tls13,
+}
+
+impl TlsVersion {
+    /// Parses the names produced by `version_name` ("TLS 1.2", "TLS 1.3", ...).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "TLS 1.0" => Some(TlsVersion::Tls10),
+            "TLS 1.1" => Some(TlsVersion::Tls11),
+            "TLS 1.2" => Some(TlsVersion::Tls12),
+            "TLS 1.3" => Some(TlsVersion::Tls13),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TlsVersion::Tls10 => "TLS 1.0",
+            TlsVersion::Tls11 => "TLS 1.1",
+            TlsVersion::Tls12 => "TLS 1.2",
+            TlsVersion::Tls13 => "TLS 1.3",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// rustls client config equivalent to ureq's default (TLS 1.2/1.3, webpki roots).
+pub fn default_client_config() -> ClientConfig {
+    let roots = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    client_config_with_roots(roots, None).expect("config without a client certificate is always valid")
+}
+
+// A client certificate chain and its private key, for mutual TLS
+pub type ClientIdentity = (Vec<CertificateDer<'static>>, PrivateKeyDer<'static>);
+
+/// TLS 1.2/1.3 client config trusting `roots`, authenticating with `identity` when given.
+/// Fails if the key doesn't suit the certificate.
+pub fn client_config_with_roots(
+    roots: rustls::RootCertStore,
+    identity: Option<ClientIdentity>,
+) -> Result<ClientConfig, String> {
+    let builder = ClientConfig::builder_with_provider(rustls::crypto::ring::default_provider().into())
+        .with_protocol_versions(&[&rustls::version::TLS12, &rustls::version::TLS13])
+        .expect("ring supports TLS 1.2 and 1.3")
+        .with_root_certificates(roots);
+    match identity {
+        Some((certs, key)) => builder
+            .with_client_auth_cert(certs, key)
+            .map_err(|e| format!("client certificate rejected: {}", e)),
+        None => Ok(builder.with_no_client_auth()),
+    }
+}
+
+/// INSECURE: like `client_config_with_roots`, but accepts any server certificate
+/// (self-signed, expired, wrong host). Handshake signatures are still checked, so
+/// the session is encrypted, but the server's identity is NOT verified.
+/// Only meant for staging hosts behind `Config::accept_invalid_certs`.
+pub fn insecure_client_config(identity: Option<ClientIdentity>) -> Result<ClientConfig, String> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(Arc::clone(&provider))
+        .with_protocol_versions(&[&rustls::version::TLS12, &rustls::version::TLS13])
+        .expect("ring supports TLS 1.2 and 1.3")
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyServerCert(provider)));
+    match identity {
+        Some((certs, key)) => builder
+            .with_client_auth_cert(certs, key)
+            .map_err(|e| format!("client certificate rejected: {}", e)),
+        None => Ok(builder.with_no_client_auth()),
+    }
+}
+
+// Certificate verifier that trusts every server certificate (see `insecure_client_config`)
+#[derive(Debug)]
+struct AcceptAnyServerCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyServerCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Loads a PEM certificate chain and PEM private key (PKCS#8, PKCS#1 or SEC1) for mutual TLS.
+pub fn load_client_identity(cert_path: &Path, key_path: &Path) -> Result<ClientIdentity, String> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|iter| iter.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("failed to load client certificate {}: {}", cert_path.display(), e))?;
+    if certs.is_empty() {
+        return Err(format!("no certificate found in {}", cert_path.display()));
+    }
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .map_err(|e| format!("failed to load client key {}: {}", key_path.display(), e))?;
+    Ok((certs, key))
+}
+
+/// Human-readable protocol name ("TLS 1.2", "TLS 1.3", ...).
+pub fn version_name(v: ProtocolVersion) -> String {
+    match v {
+        ProtocolVersion::TLSv1_0 => "TLS 1.0".into(),
+        ProtocolVersion::TLSv1_1 => "TLS 1.1".into(),
+        ProtocolVersion::TLSv1_2 => "TLS 1.2".into(),
+        ProtocolVersion::TLSv1_3 => "TLS 1.3".into(),
+        other => format!("{:?}", other),
+    }
+}
+
+// ureq TLS connector that records the version/cipher of the last handshake, overall and
+// per host. Agents reuse pooled connections without a new handshake, so a check looks up
+// its host (`info_for`) rather than relying on the last handshake being its own.
+pub struct CapturingTlsConnector {
+    config: Arc<ClientConfig>,
+    last: Mutex<Option<TlsInfo>>,
+    by_host: Mutex<HashMap<String, TlsInfo>>, // lowercase host -> its latest handshake
+}
+
+impl CapturingTlsConnector {
+    /// Connector using the given rustls config.
+    pub fn new(config: Arc<ClientConfig>) -> Self {
+        CapturingTlsConnector {
+            config,
+            last: Mutex::new(None),
+            by_host: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Connector using the same settings as ureq's default TLS setup.
+    pub fn with_default_config() -> Self {
+        Self::new(Arc::new(default_client_config()))
+    }
+
+    /// Connector that presents the client certificate at `cert_path` / `key_path`
+    /// (PEM files) to servers requiring mutual TLS. Errors name the file that failed.
+    pub fn with_client_cert(cert_path: &Path, key_path: &Path) -> Result<Self, String> {
+        let roots = rustls::RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        let identity = load_client_identity(cert_path, key_path)?;
+        Ok(Self::new(Arc::new(client_config_with_roots(roots, Some(identity))?)))
+    }
+
+    /// Version/cipher of the most recent handshake (None if none happened).
+    pub fn last_info(&self) -> Option<TlsInfo> {
+        self.last.lock().unwrap().clone()
+    }
+
+    /// Version/cipher of the most recent handshake with `host` (IPv6 without brackets).
+    pub fn info_for(&self, host: &str) -> Option<TlsInfo> {
+        self.by_host.lock().unwrap().get(&host.to_ascii_lowercase()).cloned()
+    }
+}
+
+impl TlsConnector for CapturingTlsConnector {
+    fn connect(&self, dns_name: &str, mut io: Box<dyn ReadWrite>) -> Result<Box<dyn ReadWrite>, ureq::Error> {
+        // rustls doesn't accept IPv6 literals with brackets
+        let dns_name = dns_name
+            .strip_prefix('[')
+            .and_then(|d| d.strip_suffix(']'))
+            .unwrap_or(dns_name);
+
+        // ureq only lets outside code build transport errors from io::Error
+        let sni = ServerName::try_from(dns_name)
+            .map_err(|e| io::Error::other(format!("invalid TLS server name '{}': {}", dns_name, e)))?
+            .to_owned();
+        let mut conn = ClientConnection::new(Arc::clone(&self.config), sni)
+            .map_err(|e| io::Error::other(format!("tls connection creation failed: {}", e)))?;
+
+        // Drive the handshake to completion, then record what was negotiated
+        conn.complete_io(&mut io)
+            .map_err(|e| io::Error::new(e.kind(), format!("tls connection init failed: {}", e)))?;
+        if let (Some(version), Some(suite)) = (conn.protocol_version(), conn.negotiated_cipher_suite()) {
+            let info = TlsInfo {
+                version: version_name(version),
+                cipher: format!("{:?}", suite.suite()),
+            };
+            self.by_host.lock().unwrap().insert(dns_name.to_ascii_lowercase(), info.clone());
+            *self.last.lock().unwrap() = Some(info);
+        }
+
+        Ok(Box::new(TlsStream(StreamOwned::new(conn, io))))
+    }
+}
+
+// Encrypted stream handed back to ureq
+struct TlsStream(StreamOwned<ClientConnection, Box<dyn ReadWrite>>);
+
+impl ReadWrite for TlsStream {
+    fn socket(&self) -> Option<&TcpStream> {
+        self.0.get_ref().socket()
+    }
+}
+
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl fmt::Debug for TlsStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TlsStream").finish()
+    }
+}
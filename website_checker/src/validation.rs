@@ -1,19 +1,163 @@
-use std::io::Read;
+use std::io::{self, Read};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::sync::Arc;
 use ureq;
 
+use crate::etag_store::SharedEtags;
+use crate::status::CheckStatus;
+use crate::time_utils::{parse_http_date, unix_now, TimestampFormat};
+use crate::tls::TlsVersion;
+
 // Holds results of validation checks on headers, body, and HTTPS policy
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct ValidationReport {
     pub header_ok: bool,
     pub body_ok: bool,
     pub https_policy_ok: bool,
+    pub redirect_ok: bool,   // redirects stayed on the requested host (when enforced)
+    pub tls_policy_ok: bool, // negotiated TLS version met the configured floor
     pub issues: Vec<String>, // detailed issues found
+    pub suppressed_issues: usize, // issues dropped because of `Config.max_issues`
+    pub body_preview: Option<String>, // start of the body when body rules failed (`Config.capture_body_preview`)
+    pub timing: Option<Timing>,       // request phases when `Config.trace_timing` is set and a response arrived
+}
+
+// Mini-trace of one request, in milliseconds since the check started
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub connect_start_ms: u64, // client ready, about to connect (TLS setup happens before this)
+    pub ttfb_ms: u64,          // first byte of the response: status line and headers received
+    pub total_ms: u64,         // body fully read
 }
 
 impl ValidationReport {
     // Overall pass/fail: true only if all categories pass
     pub fn overall_ok(&self) -> bool {
-        self.header_ok && self.body_ok && self.https_policy_ok
+        self.header_ok && self.body_ok && self.https_policy_ok && self.redirect_ok && self.tls_policy_ok
+    }
+
+    // One-line reason for a failed report, e.g. "header_ok=false, body_ok=true, ...".
+    // Returns None when the report passes overall.
+    pub fn failure_summary(&self) -> Option<String> {
+        if self.overall_ok() {
+            return None;
+        }
+        Some(format!(
+            "header_ok={}, body_ok={}, https_policy_ok={}, redirect_ok={}, tls_policy_ok={}",
+            self.header_ok, self.body_ok, self.https_policy_ok, self.redirect_ok, self.tls_policy_ok
+        ))
+    }
+
+    /// Keeps at most `max` issues. When some are dropped, the last one kept is a
+    /// "N more issues suppressed" note. Safe to call repeatedly as more issues are added.
+    pub fn cap_issues(&mut self, max: Option<usize>) {
+        let Some(max) = max else {
+            return;
+        };
+        if self.suppressed_issues > 0 {
+            // Take out the previous note (wherever later issues put it); a new one is added below
+            let note = suppressed_note(self.suppressed_issues);
+            if let Some(i) = self.issues.iter().position(|issue| *issue == note) {
+                self.issues.remove(i);
+            }
+        }
+        if self.suppressed_issues == 0 && self.issues.len() <= max {
+            return;
+        }
+        let keep = max.saturating_sub(1); // leave room for the note
+        if self.issues.len() > keep {
+            self.suppressed_issues += self.issues.len() - keep;
+            self.issues.truncate(keep);
+        }
+        self.issues.push(suppressed_note(self.suppressed_issues));
+    }
+
+    /// True if some issues were dropped by `cap_issues`.
+    pub fn issues_truncated(&self) -> bool {
+        self.suppressed_issues > 0
+    }
+
+    /// Issues bucketed by message prefix into (header, body, policy), for grouped reports.
+    /// Anything else (Info notes, transport errors, the "suppressed" note) is left out.
+    pub fn categorized(&self) -> (Vec<&str>, Vec<&str>, Vec<&str>) {
+        let has_prefix = |issue: &str, prefixes: &[&str]| prefixes.iter().any(|p| issue.starts_with(p));
+        let (mut header, mut body, mut policy) = (Vec::new(), Vec::new(), Vec::new());
+        for issue in self.issues.iter().map(String::as_str) {
+            if has_prefix(issue, HEADER_ISSUE_PREFIXES) {
+                header.push(issue);
+            } else if has_prefix(issue, BODY_ISSUE_PREFIXES) {
+                body.push(issue);
+            } else if has_prefix(issue, POLICY_ISSUE_PREFIXES) {
+                policy.push(issue);
+            }
+        }
+        (header, body, policy)
+    }
+}
+
+// Note added by `cap_issues` in place of the dropped issues
+fn suppressed_note(count: usize) -> String {
+    format!("{} more issues suppressed", count)
+}
+
+// Message prefixes used by `ValidationReport::categorized`; keep in sync with the issue texts below
+const HEADER_ISSUE_PREFIXES: &[&str] = &["Missing header", "Header ", "Content-Type not allowed"];
+const BODY_ISSUE_PREFIXES: &[&str] = &["Body ", "Title ", "JSON ", "Failed to read response body"];
+const POLICY_ISSUE_PREFIXES: &[&str] = &[
+    "HTTPS required",
+    "Redirected off-host",
+    "TLS version",
+    "Warning: TLS version",
+    "Warning: certificate verification",
+];
+
+// Caller-defined rule for when a check counts as healthy
+pub type SuccessPredicate = Arc<dyn Fn(&CheckStatus, &ValidationReport) -> bool + Send + Sync>;
+
+/// Default notion of healthy: a 2xx status (or an open port for a TCP probe)
+/// and every validation passed.
+pub fn default_success(status: &CheckStatus, report: &ValidationReport) -> bool {
+    status.is_success() && report.overall_ok()
+}
+
+// Which IP family a check may connect over (for dual-stack debugging)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IpVersion {
+    #[default]
+    Auto,   // whatever the resolver returns
+    V4Only, // only IPv4 addresses
+    V6Only, // only IPv6 addresses
+}
+
+impl IpVersion {
+    /// Parses "auto", "4"/"v4"/"ipv4" or "6"/"v6"/"ipv6" (case-insensitive).
+    pub fn from_name(name: &str) -> Option<IpVersion> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Some(IpVersion::Auto),
+            "4" | "v4" | "ipv4" => Some(IpVersion::V4Only),
+            "6" | "v6" | "ipv6" => Some(IpVersion::V6Only),
+            _ => None,
+        }
+    }
+
+    /// True if a connection to `addr` is allowed.
+    pub fn allows(self, addr: &SocketAddr) -> bool {
+        match self {
+            IpVersion::Auto => true,
+            IpVersion::V4Only => addr.is_ipv4(),
+            IpVersion::V6Only => addr.is_ipv6(),
+        }
+    }
+
+    /// "IPv4", "IPv6", or "IP" for Auto.
+    pub fn label(self) -> &'static str {
+        match self {
+            IpVersion::Auto => "IP",
+            IpVersion::V4Only => "IPv4",
+            IpVersion::V6Only => "IPv6",
+        }
     }
 }
 
@@ -23,16 +167,114 @@ pub struct Config {
     // HTTPS policy
     pub https_required: bool,
 
+    // Redirect policy: fail if redirects end up on a different host
+    pub same_host_redirect_only: bool,
+    pub max_redirects: u32, // redirects to follow; hitting the limit is a "too many redirects" error (0 = don't follow)
+
+    // TLS policy: fail https checks that negotiate an older version than this.
+    // Needs the rustls backend (see `tls`); ureq's rustls setup only offers 1.2 and 1.3,
+    // so in practice this matters for a TLS 1.3 floor.
+    pub min_tls_version: Option<TlsVersion>,
+
     // Header validation rules
     pub required_headers: Vec<&'static str>,         // must exist
     pub content_type_allow: Vec<&'static str>,       // allowlist
     pub header_equals: Vec<(&'static str, String)>,  // exact matches
     pub header_contains: Vec<(&'static str, String)>,// substring matches
+    pub header_fresh: Vec<(&'static str, u64)>,      // date headers (e.g. Last-Modified) at most this many seconds old
 
     // Body validation rules
     pub max_body_bytes: usize,       // max body size to read
+    pub max_body_fraction: Option<f64>, // read only this share of Content-Length (still capped by max_body_bytes)
     pub body_contains_all: Vec<String>, // must contain all
     pub body_contains_any: Vec<String>, // must contain at least one
+    pub body_exactly_one_of: Vec<String>, // must contain exactly one (mutually exclusive states)
+    pub body_equals_any: Vec<String>,   // trimmed body must be exactly one of these (e.g. "OK")
+    pub body_matches_glob: Vec<String>, // each glob must match somewhere in one line (see `glob_match_in`)
+    pub title_equals: Option<String>,   // <title> must be exactly this (trimmed)
+    pub title_contains: Option<String>, // <title> must contain this
+    pub json_required_fields: Vec<String>, // JSON pointers that must exist (JSON responses only)
+    pub json_field_equals: Vec<(String, serde_json::Value)>, // JSON pointer -> exact expected value
+    pub body_encoding: Option<String>,  // decode non-UTF-8 bodies with this ("latin1")
+    pub body_json_normalize: bool,      // pretty-print JSON bodies before running text rules
+
+    // Headers-only check: never read the body, body rules count as passing
+    pub skip_body: bool,
+
+    // Take timestamps from the network time API (true) or the local clock (false)
+    pub fetch_timestamp: bool,
+    pub timestamp_format: Option<TimestampFormat>, // how timestamps are shown in reports and snapshots (None: as fetched)
+
+    // Hash the first `max_body_bytes` of the body for change detection
+    pub fingerprint_body: bool,
+
+    // Conditional requests: send `If-None-Match` with the URL's last ETag and record
+    // the ETag of each 200 response. A 304 answer is reported as `CheckStatus::Unchanged`.
+    pub etags: Option<SharedEtags>,
+
+    // Custom health rule (None = `default_success`)
+    pub success_when: Option<SuccessPredicate>,
+
+    // Keep at most this many issues per report (None = unlimited)
+    pub max_issues: Option<usize>,
+
+    // On a failed body check, keep this many bytes of the body in `ValidationReport.body_preview` (0 = off)
+    pub capture_body_preview: usize,
+
+    // Hide hostnames in reports and exports (Display, one-line, JSON) behind stable
+    // `host-xxxxxx` tokens, so logs can be shared. Applied when results are shown
+    // (`RenderOptions::from_config`); results themselves keep the real URLs.
+    pub redact_hosts: bool,
+
+    // Record connect start, first byte and completion times in `ValidationReport.timing`.
+    // The body is read to the end (up to `max_body_bytes`) even without body rules.
+    pub trace_timing: bool,
+
+    // Overall limit for one request (connect, send and read), per hop when following redirects
+    pub timeout: Duration,
+
+    // Idle keep-alive connections kept by the HTTP client (total / per host).
+    // None keeps ureq's defaults (100 total, 1 per host). The client is shared by every
+    // check of a `Checker` (see `status::HttpClient`), so repeated checks of a host reuse
+    // its connections; Some(0) disables keep-alive reuse.
+    pub max_idle_connections: Option<usize>,
+    pub max_idle_connections_per_host: Option<usize>,
+
+    // Send requests through this proxy (e.g. "http://proxy:3128")
+    pub proxy: Option<String>,
+
+    // Send requests through a SOCKS4/5 proxy instead (e.g. "socks5://127.0.0.1:1080" for an
+    // SSH tunnel); wins over `proxy` when both are set. Needs the crate's `socks` feature.
+    pub socks_proxy: Option<String>,
+
+    // Client certificate and private key (PEM files) for servers requiring mutual TLS.
+    // Needs the rustls TLS backend (see `tls.rs`).
+    pub client_cert: Option<(PathBuf, PathBuf)>,
+
+    // INSECURE: skip server certificate verification (self-signed staging hosts).
+    // Anyone on the network path could impersonate the server; every https check
+    // made this way gets a "Warning: certificate verification disabled" issue.
+    pub accept_invalid_certs: bool,
+
+    // Request to send. `method` is the default for checks that don't name one
+    // (`request_with`, `Checker::run`); targets with their own method (`run_targets`,
+    // `UrlEntry::methods`) keep it. `request_body` is sent as (Content-Type, bytes), and
+    // `json_post` as an `application/json` body in its place, e.g. for POST smoke tests.
+    pub method: String,
+    pub request_body: Option<(String, Vec<u8>)>,
+    pub json_post: Option<serde_json::Value>,
+
+    // Request headers for content negotiation. Without `accept_encoding` the client asks
+    // for gzip; gzip bodies are decompressed before body rules run either way.
+    pub accept_language: Option<String>, // e.g. "de-DE,de;q=0.9" to check a localized page
+    pub accept_encoding: Option<String>, // e.g. "identity" to ask for an uncompressed body
+
+    // Connect to a fixed IP for a host, e.g. ("api.example.com", "10.0.0.5"), to test
+    // before a DNS cutover. Host header and TLS SNI keep the original hostname.
+    pub host_overrides: Vec<(String, String)>,
+
+    // Connect only over this IP family (resolved addresses of the other family are dropped)
+    pub ip_version: IpVersion,
 }
 
 // Default validation configuration
@@ -40,17 +282,357 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             https_required: true,
+            same_host_redirect_only: false,
+            max_redirects: 5,
+            min_tls_version: None,
             required_headers: vec!["Content-Type"],
             content_type_allow: vec!["text/html", "application/json"],
             header_equals: vec![],
             header_contains: vec![],
+            header_fresh: vec![],
             max_body_bytes: 64 * 1024, // 64 KB
+            max_body_fraction: None,
             body_contains_all: vec![],
             body_contains_any: vec![],
+            body_exactly_one_of: vec![],
+            body_equals_any: vec![],
+            body_matches_glob: vec![],
+            title_equals: None,
+            title_contains: None,
+            json_required_fields: vec![],
+            json_field_equals: vec![],
+            body_encoding: None,
+            body_json_normalize: false,
+            skip_body: false,
+            fetch_timestamp: true,
+            timestamp_format: None,
+            fingerprint_body: false,
+            etags: None,
+            success_when: None,
+            max_issues: None,
+            capture_body_preview: 0,
+            redact_hosts: false,
+            trace_timing: false,
+            timeout: Duration::from_secs(5),
+            max_idle_connections: None,
+            max_idle_connections_per_host: None,
+            proxy: None,
+            socks_proxy: None,
+            client_cert: None,
+            accept_invalid_certs: false,
+            method: "GET".to_string(),
+            request_body: None,
+            json_post: None,
+            accept_language: None,
+            accept_encoding: None,
+            host_overrides: vec![],
+            ip_version: IpVersion::Auto,
         }
     }
 }
 
+impl Config {
+    /// Decides whether a finished check is healthy, using `success_when` if set.
+    pub fn is_success(&self, status: &CheckStatus, report: &ValidationReport) -> bool {
+        match &self.success_when {
+            Some(pred) => pred(status, report),
+            None => default_success(status, report),
+        }
+    }
+
+    /// True when any rule needs the response body to be read.
+    pub fn needs_body(&self) -> bool {
+        !self.body_contains_all.is_empty()
+            || !self.body_contains_any.is_empty()
+            || !self.body_exactly_one_of.is_empty()
+            || !self.body_equals_any.is_empty()
+            || !self.body_matches_glob.is_empty()
+            || self.title_equals.is_some()
+            || self.title_contains.is_some()
+            || self.has_json_rules()
+            || self.fingerprint_body
+    }
+
+    /// True when JSON-only rules (`json_required_fields`, `json_field_equals`) are set.
+    pub fn has_json_rules(&self) -> bool {
+        !self.json_required_fields.is_empty() || !self.json_field_equals.is_empty()
+    }
+}
+
+// Chainable way to build a Config, starting from `Config::default()`:
+// `Config::builder().https_required(false).require_header("X-Foo").body_must_contain("hello").build()`
+#[derive(Clone, Default)]
+pub struct ConfigBuilder {
+    cfg: Config,
+}
+
+impl Config {
+    /// Starts a builder from the default config.
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+}
+
+impl ConfigBuilder {
+    pub fn https_required(mut self, on: bool) -> Self {
+        self.cfg.https_required = on;
+        self
+    }
+
+    pub fn same_host_redirect_only(mut self, on: bool) -> Self {
+        self.cfg.same_host_redirect_only = on;
+        self
+    }
+
+    pub fn max_redirects(mut self, n: u32) -> Self {
+        self.cfg.max_redirects = n;
+        self
+    }
+
+    pub fn min_tls_version(mut self, v: TlsVersion) -> Self {
+        self.cfg.min_tls_version = Some(v);
+        self
+    }
+
+    /// Adds a header that must be present.
+    pub fn require_header(mut self, name: &'static str) -> Self {
+        self.cfg.required_headers.push(name);
+        self
+    }
+
+    /// Replaces the Content-Type allowlist (empty = allow anything).
+    pub fn content_type_allow(mut self, types: Vec<&'static str>) -> Self {
+        self.cfg.content_type_allow = types;
+        self
+    }
+
+    pub fn header_equals(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.cfg.header_equals.push((name, value.into()));
+        self
+    }
+
+    pub fn header_contains(mut self, name: &'static str, needle: impl Into<String>) -> Self {
+        self.cfg.header_contains.push((name, needle.into()));
+        self
+    }
+
+    /// Requires date header `name` (e.g. "Date", "Last-Modified") to be at most `max_age_secs` old.
+    pub fn header_fresh(mut self, name: &'static str, max_age_secs: u64) -> Self {
+        self.cfg.header_fresh.push((name, max_age_secs));
+        self
+    }
+
+    pub fn max_body_bytes(mut self, n: usize) -> Self {
+        self.cfg.max_body_bytes = n;
+        self
+    }
+
+    /// Reads only `fraction` (0.0-1.0) of the advertised Content-Length.
+    pub fn max_body_fraction(mut self, fraction: f64) -> Self {
+        self.cfg.max_body_fraction = Some(fraction);
+        self
+    }
+
+    /// Adds a token the body must contain (ALL-of rule).
+    pub fn body_must_contain(mut self, token: impl Into<String>) -> Self {
+        self.cfg.body_contains_all.push(token.into());
+        self
+    }
+
+    /// Adds a token to the ANY-of rule.
+    pub fn body_contains_any(mut self, token: impl Into<String>) -> Self {
+        self.cfg.body_contains_any.push(token.into());
+        self
+    }
+
+    /// Adds a token to the exactly-one-of rule (e.g. "logged in" / "logged out").
+    pub fn body_exactly_one_of(mut self, token: impl Into<String>) -> Self {
+        self.cfg.body_exactly_one_of.push(token.into());
+        self
+    }
+
+    /// Adds an accepted exact body (compared after trimming whitespace).
+    pub fn body_equals_any(mut self, body: impl Into<String>) -> Self {
+        self.cfg.body_equals_any.push(body.into());
+        self
+    }
+
+    /// Adds a glob (`*`, `?`, `\\` escapes) the body must match.
+    pub fn body_matches_glob(mut self, pattern: impl Into<String>) -> Self {
+        self.cfg.body_matches_glob.push(pattern.into());
+        self
+    }
+
+    pub fn title_equals(mut self, title: impl Into<String>) -> Self {
+        self.cfg.title_equals = Some(title.into());
+        self
+    }
+
+    pub fn title_contains(mut self, needle: impl Into<String>) -> Self {
+        self.cfg.title_contains = Some(needle.into());
+        self
+    }
+
+    /// Adds a JSON pointer (e.g. "/data/status") that must exist in a JSON response.
+    pub fn require_json_field(mut self, pointer: impl Into<String>) -> Self {
+        self.cfg.json_required_fields.push(pointer.into());
+        self
+    }
+
+    /// The JSON value at `pointer` must equal `value` (JSON responses only).
+    pub fn json_field_equals(mut self, pointer: impl Into<String>, value: serde_json::Value) -> Self {
+        self.cfg.json_field_equals.push((pointer.into(), value));
+        self
+    }
+
+    pub fn body_encoding(mut self, encoding: impl Into<String>) -> Self {
+        self.cfg.body_encoding = Some(encoding.into());
+        self
+    }
+
+    pub fn body_json_normalize(mut self, on: bool) -> Self {
+        self.cfg.body_json_normalize = on;
+        self
+    }
+
+    pub fn skip_body(mut self, on: bool) -> Self {
+        self.cfg.skip_body = on;
+        self
+    }
+
+    pub fn fetch_timestamp(mut self, on: bool) -> Self {
+        self.cfg.fetch_timestamp = on;
+        self
+    }
+
+    pub fn timestamp_format(mut self, fmt: TimestampFormat) -> Self {
+        self.cfg.timestamp_format = Some(fmt);
+        self
+    }
+
+    pub fn fingerprint_body(mut self, on: bool) -> Self {
+        self.cfg.fingerprint_body = on;
+        self
+    }
+
+    /// Sends conditional requests using (and updating) `store`.
+    pub fn etags(mut self, store: SharedEtags) -> Self {
+        self.cfg.etags = Some(store);
+        self
+    }
+
+    pub fn success_when(
+        mut self,
+        pred: impl Fn(&CheckStatus, &ValidationReport) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.cfg.success_when = Some(Arc::new(pred));
+        self
+    }
+
+    pub fn max_issues(mut self, max: usize) -> Self {
+        self.cfg.max_issues = Some(max);
+        self
+    }
+
+    /// Keeps the first `bytes` of the body in the report when body rules fail.
+    pub fn capture_body_preview(mut self, bytes: usize) -> Self {
+        self.cfg.capture_body_preview = bytes;
+        self
+    }
+
+    /// Replaces hostnames in all output with stable tokens (see `status::redact_url`).
+    pub fn redact_hosts(mut self, on: bool) -> Self {
+        self.cfg.redact_hosts = on;
+        self
+    }
+
+    /// Records a per-request timing trace (`ValidationReport.timing`).
+    pub fn trace_timing(mut self, on: bool) -> Self {
+        self.cfg.trace_timing = on;
+        self
+    }
+
+    /// Sets the request timeout (default 5s).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.cfg.timeout = timeout;
+        self
+    }
+
+    pub fn max_idle_connections(mut self, total: usize, per_host: usize) -> Self {
+        self.cfg.max_idle_connections = Some(total);
+        self.cfg.max_idle_connections_per_host = Some(per_host);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.cfg.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Routes requests through a SOCKS proxy, e.g. "socks5://127.0.0.1:1080".
+    pub fn socks_proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.cfg.socks_proxy = Some(proxy.into());
+        self
+    }
+
+    /// Authenticates with this PEM certificate and key (mutual TLS).
+    pub fn client_cert(mut self, cert: impl Into<PathBuf>, key: impl Into<PathBuf>) -> Self {
+        self.cfg.client_cert = Some((cert.into(), key.into()));
+        self
+    }
+
+    /// INSECURE: accept any server certificate (self-signed, expired, wrong host).
+    /// Only for staging; results carry a warning issue.
+    pub fn accept_invalid_certs(mut self, on: bool) -> Self {
+        self.cfg.accept_invalid_certs = on;
+        self
+    }
+
+    /// Method for checks that don't name their own (default "GET").
+    pub fn method(mut self, method: impl Into<String>) -> Self {
+        self.cfg.method = method.into();
+        self
+    }
+
+    /// Sends `body` with the given Content-Type on every check.
+    pub fn request_body(mut self, content_type: impl Into<String>, body: impl Into<Vec<u8>>) -> Self {
+        self.cfg.request_body = Some((content_type.into(), body.into()));
+        self
+    }
+
+    /// POSTs `payload` as `application/json`. Pair with `json_field_equals` to check the reply.
+    pub fn json_post(mut self, payload: serde_json::Value) -> Self {
+        self.cfg.json_post = Some(payload);
+        self.method("POST")
+    }
+
+    pub fn accept_language(mut self, value: impl Into<String>) -> Self {
+        self.cfg.accept_language = Some(value.into());
+        self
+    }
+
+    pub fn accept_encoding(mut self, value: impl Into<String>) -> Self {
+        self.cfg.accept_encoding = Some(value.into());
+        self
+    }
+
+    pub fn ip_version(mut self, v: IpVersion) -> Self {
+        self.cfg.ip_version = v;
+        self
+    }
+
+    /// Connects to `ip` whenever `host` is requested (Host header and SNI unchanged).
+    pub fn host_override(mut self, host: impl Into<String>, ip: impl Into<String>) -> Self {
+        self.cfg.host_overrides.push((host.into(), ip.into()));
+        self
+    }
+
+    /// Finishes the builder.
+    pub fn build(self) -> Config {
+        self.cfg
+    }
+}
+
 /// Enforce HTTPS-only policy (records an issue if violated).
 pub fn enforce_https_policy(url: &str, report: &mut ValidationReport, cfg: &Config) {
     if !cfg.https_required {
@@ -65,27 +647,193 @@ pub fn enforce_https_policy(url: &str, report: &mut ValidationReport, cfg: &Conf
     }
 }
 
-/// Validate response headers and (optionally) body.
-pub fn validate_response(resp: ureq::Response, cfg: &Config, report: &mut ValidationReport) {
+/// Extract `(host, port)` from a URL or bare `host[:port]`.
+/// Handles hostnames, IPv4 addresses and bracketed IPv6 (`[::1]:8443`, returned as `::1`).
+/// Hosts are lowercased; without an explicit port, `http` uses 80 and anything else 443.
+pub fn parse_host_port(url: &str) -> Option<(String, u16)> {
+    let (scheme, rest) = match url.split_once("://") {
+        Some((scheme, rest)) => (scheme.to_ascii_lowercase(), rest),
+        None => (String::new(), url),
+    };
+    let default_port = if scheme == "http" { 80 } else { 443 };
+
+    // Authority ends at the first path/query/fragment character; drop any userinfo
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority.rsplit('@').next()?;
+
+    let (host, port) = if let Some(bracketed) = host_port.strip_prefix('[') {
+        // IPv6: "[addr]" or "[addr]:port"
+        let (host, after) = bracketed.split_once(']')?;
+        match after.strip_prefix(':') {
+            Some(p) => (host, p.parse().ok()?),
+            None if after.is_empty() => (host, default_port),
+            None => return None,
+        }
+    } else {
+        match host_port.split_once(':') {
+            Some((host, p)) => (host, p.parse().ok()?),
+            None => (host_port, default_port),
+        }
+    };
+
+    if host.is_empty() {
+        None
+    } else {
+        Some((host.to_ascii_lowercase(), port))
+    }
+}
+
+/// Enforce that redirects did not leave the requested host (records an issue if violated).
+/// `final_url` is the URL of the response after any redirects were followed.
+pub fn enforce_redirect_policy(url: &str, final_url: &str, report: &mut ValidationReport, cfg: &Config) {
+    report.redirect_ok = true;
+    if !cfg.same_host_redirect_only {
+        return;
+    }
+    let requested = parse_host_port(url).map(|(host, _)| host);
+    let landed = parse_host_port(final_url).map(|(host, _)| host);
+    if requested != landed {
+        report.redirect_ok = false;
+        report.issues.push(format!(
+            "Redirected off-host: {} -> {}",
+            requested.unwrap_or_default(),
+            landed.unwrap_or_default()
+        ));
+    }
+}
+
+/// Case-insensitive lookup of the first value of header `name`.
+pub fn header_value<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// All response headers as `(name, value)` pairs (repeated headers appear once per value).
+pub fn response_headers(resp: &ureq::Response) -> Vec<(String, String)> {
+    let mut headers = Vec::new();
+    for name in resp.headers_names() {
+        for value in resp.all(&name) {
+            headers.push((name.clone(), value.to_string()));
+        }
+    }
+    headers
+}
+
+/// Evaluate the header and body rules of `cfg` against an already fetched response.
+/// Pure (no network), so several rule sets can be run against one download.
+/// URL-level policies (HTTPS, redirects) need the URL and are reported as passing here.
+/// Date header rules are judged against the clock; see `evaluate_at`.
+pub fn evaluate(headers: &[(String, String)], body: &str, cfg: &Config) -> ValidationReport {
+    evaluate_at(headers, body, cfg, unix_now())
+}
+
+/// Same as `evaluate`, judging date header rules at `now` (seconds since the Unix epoch),
+/// e.g. the time the response was fetched.
+pub fn evaluate_at(headers: &[(String, String)], body: &str, cfg: &Config, now: u64) -> ValidationReport {
+    let mut report = ValidationReport {
+        https_policy_ok: true,
+        redirect_ok: true,
+        tls_policy_ok: true,
+        ..ValidationReport::default()
+    };
+    validate_headers(headers, cfg, now, &mut report);
+    let content_type = header_value(headers, "Content-Type");
+    let body = normalize_json_body(body.to_string(), content_type, cfg, &mut report);
+    apply_body_rules(&body, content_type, cfg, &mut report);
+    report
+}
+
+/// Enforce the minimum TLS version for https URLs (records an issue if violated).
+/// `negotiated` is the version name reported by the TLS connector, if known.
+/// An unknown version passes, with a warning issue, since it can't be judged.
+/// Also warns when `accept_invalid_certs` switched certificate verification off.
+pub fn enforce_tls_policy(url: &str, negotiated: Option<&str>, report: &mut ValidationReport, cfg: &Config) {
+    report.tls_policy_ok = true;
+    if !url.starts_with("https://") {
+        return; // no TLS involved
+    }
+    if cfg.accept_invalid_certs {
+        report
+            .issues
+            .push("Warning: certificate verification disabled (accept_invalid_certs); server identity not checked".to_string());
+    }
+    let Some(min) = cfg.min_tls_version else {
+        return;
+    };
+    match negotiated.and_then(TlsVersion::from_name) {
+        Some(v) if v < min => {
+            report.tls_policy_ok = false;
+            report.issues.push(format!("TLS version {} is below required {}", v, min));
+        }
+        Some(_) => {}
+        None => report.issues.push(format!(
+            "Warning: TLS version could not be determined; minimum {} not verified",
+            min
+        )),
+    }
+}
+
+/// Validate response headers and (optionally) body. `now` is the time of the check
+/// (seconds since the Unix epoch), which date header rules are judged against.
+/// Returns the body bytes that were read, if the body was needed at all.
+pub fn validate_response(resp: ureq::Response, cfg: &Config, now: u64, report: &mut ValidationReport) -> Option<Vec<u8>> {
     // First check headers
-    validate_headers(&resp, cfg, report);
+    let headers = response_headers(&resp);
+    validate_headers(&headers, cfg, now, report);
 
-    // Check body only if rules are configured
-    let need_body = !cfg.body_contains_all.is_empty() || !cfg.body_contains_any.is_empty();
-    if need_body {
-        validate_body(resp, cfg, report);
+    // Read the body only if rules are configured (or it needs fingerprinting),
+    // unless the config asks for a headers-only check
+    if cfg.needs_body() && !cfg.skip_body {
+        validate_body(resp, &headers, cfg, report)
     } else {
         report.body_ok = true;
+        if cfg.trace_timing {
+            // Read (and drop) the body anyway so the trace covers the whole response
+            let _ = io::copy(&mut resp.into_reader().take(cfg.max_body_bytes as u64), &mut io::sink());
+        }
+        None
     }
 }
 
+/// Validate only the response headers, for responses without a body (HEAD).
+/// Body rules are skipped and count as passing.
+pub fn validate_response_headers(resp: &ureq::Response, cfg: &Config, now: u64, report: &mut ValidationReport) {
+    validate_headers(&response_headers(resp), cfg, now, report);
+    report.body_ok = true;
+    if cfg.needs_body() {
+        report.issues.push("Info: no body to check; body rules skipped".to_string());
+    }
+}
+
+/// Validate a 304 Not Modified answer to a conditional request. It has no body and
+/// usually no Content-Type, so the header and body rules that describe the full page
+/// are skipped; date freshness still applies.
+pub fn validate_not_modified(resp: &ureq::Response, cfg: &Config, now: u64, report: &mut ValidationReport) {
+    let (fresh_ok, fresh_issues) = check_header_freshness(&response_headers(resp), &cfg.header_fresh, now);
+    report.issues.extend(fresh_issues);
+    report.header_ok = fresh_ok;
+    report.body_ok = true;
+}
+
+/// Stable 64-bit FNV-1a hash of `bytes`, as 16 lowercase hex digits.
+pub fn fingerprint(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325; // FNV offset basis
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3); // FNV prime
+    }
+    format!("{:016x}", hash)
+}
+
 // Header validation helper
-fn validate_headers(resp: &ureq::Response, cfg: &Config, report: &mut ValidationReport) {
+fn validate_headers(headers: &[(String, String)], cfg: &Config, now: u64, report: &mut ValidationReport) {
     let mut ok = true;
 
     // Check required headers exist
     for &h in &cfg.required_headers {
-        if resp.header(h).is_none() {
+        if header_value(headers, h).is_none() {
             ok = false;
             report.issues.push(format!("Missing header: {}", h));
         }
@@ -93,7 +841,7 @@ fn validate_headers(resp: &ureq::Response, cfg: &Config, report: &mut Validation
 
     // Check Content-Type allowlist
     if !cfg.content_type_allow.is_empty() {
-        match resp.header("Content-Type") {
+        match header_value(headers, "Content-Type") {
             Some(ct) => {
                 let lower = ct.to_ascii_lowercase();
                 if !cfg.content_type_allow.iter()
@@ -112,7 +860,7 @@ fn validate_headers(resp: &ureq::Response, cfg: &Config, report: &mut Validation
 
     // Exact header matches
     for (name, expected) in &cfg.header_equals {
-        match resp.header(name) {
+        match header_value(headers, name) {
             Some(v) if v == expected => {}
             Some(v) => {
                 ok = false;
@@ -130,7 +878,7 @@ fn validate_headers(resp: &ureq::Response, cfg: &Config, report: &mut Validation
 
     // Header substring matches
     for (name, needle) in &cfg.header_contains {
-        match resp.header(name) {
+        match header_value(headers, name) {
             Some(v) if v.contains(needle) => {}
             Some(v) => {
                 ok = false;
@@ -146,7 +894,35 @@ fn validate_headers(resp: &ureq::Response, cfg: &Config, report: &mut Validation
         }
     }
 
-    report.header_ok = ok;
+    // Date headers that must be recent, judged at the time of the check
+    let (fresh_ok, fresh_issues) = check_header_freshness(headers, &cfg.header_fresh, now);
+    report.issues.extend(fresh_issues);
+
+    report.header_ok = ok && fresh_ok;
+}
+
+/// Checks each `(header, max_age_secs)` rule against `now` (seconds since the Unix epoch):
+/// the header must be an HTTP date no older than the limit. Dates slightly in the
+/// future (clock skew) count as fresh.
+pub fn check_header_freshness(headers: &[(String, String)], rules: &[(&str, u64)], now: u64) -> (bool, Vec<String>) {
+    let mut issues = Vec::new();
+    for &(name, max_age) in rules {
+        match header_value(headers, name) {
+            Some(v) => match parse_http_date(v) {
+                Some(date) if now.saturating_sub(date) <= max_age => {}
+                Some(date) => issues.push(format!(
+                    "Header {} is stale: {} s old, allowed {} s ('{}')",
+                    name,
+                    now - date,
+                    max_age,
+                    v
+                )),
+                None => issues.push(format!("Header {} is not a valid HTTP date: '{}'", name, v)),
+            },
+            None => issues.push(format!("Missing header: {}", name)),
+        }
+    }
+    (issues.is_empty(), issues)
 }
 
 /// Check if `needle` appears in text as a standalone word.
@@ -185,8 +961,29 @@ fn contains_token(text: &str, needle: &str) -> bool {
     false
 }
 
-/// Validate body text according to config (ALL-of and ANY-of rules).
+/// Extract the text between the first `<title>` and `</title>` (case-insensitive), trimmed.
+pub fn extract_title(text: &str) -> Option<&str> {
+    // ASCII lowercasing keeps byte offsets identical to the original text
+    let lower = text.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let content_start = open + lower[open..].find('>')? + 1;
+    let content_end = content_start + lower[content_start..].find("</title")?;
+    Some(text[content_start..content_end].trim())
+}
+
+/// Validate body text according to config (ALL-of, ANY-of and exactly-one-of rules).
 pub fn check_body_text(text: &str, cfg: &Config) -> (bool, Vec<String>) {
+    let (ok, mut issues) = check_text_rules(text, cfg);
+
+    // <title> rules
+    let (title_ok, title_issues) = check_title_rules(text, cfg);
+    issues.extend(title_issues);
+
+    (ok && title_ok, issues)
+}
+
+// The plain text rules of `check_body_text`, without the <title> rules
+fn check_text_rules(text: &str, cfg: &Config) -> (bool, Vec<String>) {
     let mut issues = Vec::new();
 
     // ALL-of rules
@@ -196,6 +993,25 @@ pub fn check_body_text(text: &str, cfg: &Config) -> (bool, Vec<String>) {
         }
     }
 
+    // Exact-body rule. A body that filled the byte cap may have been cut short, so it never matches.
+    if !cfg.body_equals_any.is_empty() {
+        if text.len() >= cfg.max_body_bytes {
+            issues.push(format!(
+                "Body reached the {}-byte cap; cannot match any of: {:?}",
+                cfg.max_body_bytes, cfg.body_equals_any
+            ));
+        } else if !cfg.body_equals_any.iter().any(|b| b == text.trim()) {
+            issues.push(format!("Body did not equal any of: {:?}", cfg.body_equals_any));
+        }
+    }
+
+    // Glob rules (dynamic parts like order IDs)
+    for pattern in &cfg.body_matches_glob {
+        if !glob_match_in(text, pattern) {
+            issues.push(format!("Body did not match pattern: '{}'", pattern));
+        }
+    }
+
     // ANY-of rules
     let mut ok = issues.is_empty();
     if !cfg.body_contains_any.is_empty() {
@@ -206,23 +1022,379 @@ pub fn check_body_text(text: &str, cfg: &Config) -> (bool, Vec<String>) {
         ok = ok && any_hit;
     }
 
+    // Exactly-one-of rule: the states are mutually exclusive, so none or several is an issue
+    if !cfg.body_exactly_one_of.is_empty() {
+        let hits: Vec<&String> = cfg.body_exactly_one_of.iter().filter(|n| contains_token(text, n)).collect();
+        if hits.len() != 1 {
+            issues.push(format!(
+                "Body contained {} of {:?}, expected exactly one{}",
+                hits.len(),
+                cfg.body_exactly_one_of,
+                if hits.is_empty() { String::new() } else { format!(" (found {:?})", hits) }
+            ));
+            ok = false;
+        }
+    }
+
+    (ok, issues)
+}
+
+// `title_equals` / `title_contains` rules on an HTML body
+fn check_title_rules(text: &str, cfg: &Config) -> (bool, Vec<String>) {
+    let mut ok = true;
+    let mut issues = Vec::new();
+    // <title> rules
+    if cfg.title_equals.is_some() || cfg.title_contains.is_some() {
+        match extract_title(text) {
+            Some(title) => {
+                if let Some(expected) = &cfg.title_equals
+                    && title != expected
+                {
+                    ok = false;
+                    issues.push(format!("Title mismatch: got '{}', expected '{}'", title, expected));
+                }
+                if let Some(needle) = &cfg.title_contains
+                    && !title.contains(needle.as_str())
+                {
+                    ok = false;
+                    issues.push(format!("Title does not contain '{}': got '{}'", needle, title));
+                }
+            }
+            None => {
+                ok = false;
+                issues.push("Body missing <title> element".to_string());
+            }
+        }
+    }
+
     (ok, issues)
 }
 
-// Body validation helper: reads body and applies text checks
-fn validate_body(resp: ureq::Response, cfg: &Config, report: &mut ValidationReport) {
-    let mut reader = resp.into_reader().take(cfg.max_body_bytes as u64);
+// True for JSON content types ("application/json", "application/problem+json", ...)
+fn is_json_content_type(content_type: &str) -> bool {
+    content_type.to_ascii_lowercase().contains("json")
+}
+
+/// JSON rules: the body must parse, every `json_required_fields` pointer
+/// (e.g. "/data/status") must be present, and every `json_field_equals` value must match.
+pub fn check_json_rules(text: &str, cfg: &Config) -> (bool, Vec<String>) {
+    let value = match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(v) => v,
+        Err(e) => return (false, vec![format!("Body is not valid JSON: {}", e)]),
+    };
+    let mut issues: Vec<String> = cfg
+        .json_required_fields
+        .iter()
+        .filter(|pointer| value.pointer(pointer).is_none())
+        .map(|pointer| format!("JSON field missing: '{}'", pointer))
+        .collect();
+    for (pointer, expected) in &cfg.json_field_equals {
+        match value.pointer(pointer) {
+            Some(actual) if actual == expected => {}
+            Some(actual) => issues.push(format!(
+                "JSON field '{}' mismatch: got {}, expected {}",
+                pointer, actual, expected
+            )),
+            None => issues.push(format!("JSON field missing: '{}'", pointer)),
+        }
+    }
+    (issues.is_empty(), issues)
+}
+
+/// Expected JSON type of a field in a `check_json_shape` spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonType {
+    Number,
+    String,
+    Bool,
+    Array,
+    Object,
+}
+
+impl JsonType {
+    /// Type of `value` (None for `null`, which matches no spec type).
+    pub fn of(value: &serde_json::Value) -> Option<JsonType> {
+        use serde_json::Value;
+        match value {
+            Value::Number(_) => Some(JsonType::Number),
+            Value::String(_) => Some(JsonType::String),
+            Value::Bool(_) => Some(JsonType::Bool),
+            Value::Array(_) => Some(JsonType::Array),
+            Value::Object(_) => Some(JsonType::Object),
+            Value::Null => None,
+        }
+    }
+
+    /// Parses the spec names "number", "string", "bool" (or "boolean"), "array" and "object".
+    pub fn from_name(name: &str) -> Option<JsonType> {
+        match name.to_ascii_lowercase().as_str() {
+            "number" => Some(JsonType::Number),
+            "string" => Some(JsonType::String),
+            "bool" | "boolean" => Some(JsonType::Bool),
+            "array" => Some(JsonType::Array),
+            "object" => Some(JsonType::Object),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            JsonType::Number => "number",
+            JsonType::String => "string",
+            JsonType::Bool => "bool",
+            JsonType::Array => "array",
+            JsonType::Object => "object",
+        }
+    }
+}
+
+/// Contract check: `body` must be JSON with every `spec` field present and of the given type.
+/// Field names are top-level keys, or JSON pointers when they start with '/' ("/data/id").
+/// Returns (ok, one issue per missing or mistyped field).
+pub fn check_json_shape(body: &str, spec: &[(String, JsonType)]) -> (bool, Vec<String>) {
+    let value = match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(v) => v,
+        Err(e) => return (false, vec![format!("Body is not valid JSON: {}", e)]),
+    };
+    let mut issues = Vec::new();
+    for (field, expected) in spec {
+        let found = if field.starts_with('/') { value.pointer(field) } else { value.get(field) };
+        match found {
+            None => issues.push(format!("JSON field missing: '{}' (expected {})", field, expected.name())),
+            Some(v) => {
+                let actual = JsonType::of(v).map_or("null", JsonType::name);
+                if actual != expected.name() {
+                    issues.push(format!("JSON field '{}' is {}, expected {}", field, actual, expected.name()));
+                }
+            }
+        }
+    }
+    (issues.is_empty(), issues)
+}
+
+// One piece of a parsed glob pattern
+#[derive(Clone, Copy, PartialEq)]
+enum GlobToken {
+    Literal(char),
+    AnyChar, // ?
+    AnyRun,  // *
+}
+
+/// True if `pattern` matches some part of a single line of `text`.
+/// `*` matches any run of characters (possibly empty) and `?` exactly one character,
+/// but neither crosses a line break; `\*`, `\?` and `\\` match a literal `*`, `?` and `\`.
+/// The match is not anchored: `order-*-confirmed` matches "... order-123-confirmed ...".
+pub fn glob_match_in(text: &str, pattern: &str) -> bool {
+    let mut tokens = vec![GlobToken::AnyRun]; // unanchored start
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '*' => GlobToken::AnyRun,
+            '?' => GlobToken::AnyChar,
+            '\\' => GlobToken::Literal(chars.next().unwrap_or('\\')),
+            other => GlobToken::Literal(other),
+        });
+    }
+    tokens.push(GlobToken::AnyRun); // unanchored end
+
+    text.lines().any(|line| {
+        let line: Vec<char> = line.chars().collect();
+        glob_full_match(&tokens, &line)
+    })
+}
+
+// Whole-string wildcard match with single-star backtracking
+fn glob_full_match(tokens: &[GlobToken], s: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut last_star: Option<(usize, usize)> = None; // (token index, text index) to retry from
+    while t < s.len() {
+        match tokens.get(p) {
+            Some(GlobToken::AnyRun) => {
+                last_star = Some((p, t));
+                p += 1;
+            }
+            Some(GlobToken::AnyChar) => {
+                p += 1;
+                t += 1;
+            }
+            Some(GlobToken::Literal(c)) if *c == s[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match last_star {
+                // Let the last `*` swallow one more character and try again
+                Some((sp, st)) => {
+                    last_star = Some((sp, st + 1));
+                    p = sp + 1;
+                    t = st + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    tokens[p..].iter().all(|tok| *tok == GlobToken::AnyRun)
+}
+
+// Body validation helper: reads body and applies text checks.
+// Returns the bytes read (None if reading failed).
+fn validate_body(
+    resp: ureq::Response,
+    headers: &[(String, String)],
+    cfg: &Config,
+    report: &mut ValidationReport,
+) -> Option<Vec<u8>> {
+    let limit = body_read_limit(headers, cfg);
+    let mut reader = resp.into_reader().take(limit as u64);
     let mut buf = Vec::new();
     if let Err(e) = reader.read_to_end(&mut buf) {
         report.body_ok = false;
         report.issues.push(format!("Failed to read response body: {}", e));
+        return None;
+    }
+
+    apply_body_bytes(&buf, header_value(headers, "Content-Type"), cfg, report);
+    Some(buf)
+}
+
+// How many body bytes to read: `max_body_bytes`, or less when `max_body_fraction`
+// is set and the server sent a Content-Length
+fn body_read_limit(headers: &[(String, String)], cfg: &Config) -> usize {
+    let advertised = header_value(headers, "Content-Length").and_then(|v| v.trim().parse::<u64>().ok());
+    match (cfg.max_body_fraction, advertised) {
+        (Some(fraction), Some(len)) => {
+            let share = (len as f64 * fraction.clamp(0.0, 1.0)).ceil() as usize;
+            share.min(cfg.max_body_bytes)
+        }
+        _ => cfg.max_body_bytes, // unknown length (e.g. chunked): plain byte cap
+    }
+}
+
+// Decodes raw body bytes (skipping binary content) and runs the text rules
+fn apply_body_bytes(bytes: &[u8], content_type: Option<&str>, cfg: &Config, report: &mut ValidationReport) {
+    if let Some(ct) = content_type.filter(|ct| is_binary_content_type(ct)) {
+        report.body_ok = true;
+        report.issues.push(format!("Info: binary body ({}); text rules skipped", ct));
         return;
     }
 
-    let text = String::from_utf8_lossy(&buf);
-    let (ok, issues) = check_body_text(&text, cfg);
+    let (text, note) = decode_body(bytes, cfg);
+    if let Some(note) = note {
+        report.issues.push(note);
+    }
+    let text = normalize_json_body(text, content_type, cfg, report);
+    apply_body_rules(&text, content_type, cfg, report);
+}
+
+// With `body_json_normalize`, re-formats JSON bodies (pretty-printed) so text rules
+// don't depend on the server's whitespace. Bodies that don't parse are kept as-is.
+fn normalize_json_body(text: String, content_type: Option<&str>, cfg: &Config, report: &mut ValidationReport) -> String {
+    if !cfg.body_json_normalize || !content_type.is_some_and(is_json_content_type) {
+        return text;
+    }
+    match serde_json::from_str::<serde_json::Value>(&text) {
+        Ok(value) => serde_json::to_string_pretty(&value).unwrap_or(text),
+        Err(e) => {
+            report
+                .issues
+                .push(format!("Info: JSON body did not parse ({}); rules use the raw text", e));
+            text
+        }
+    }
+}
+
+/// True for content types that are not text (images, audio, video, archives, ...).
+pub fn is_binary_content_type(content_type: &str) -> bool {
+    let ct = content_type.to_ascii_lowercase();
+    ["image/", "audio/", "video/", "font/"].iter().any(|p| ct.starts_with(p))
+        || [
+            "application/octet-stream",
+            "application/pdf",
+            "application/zip",
+            "application/gzip",
+            "application/wasm",
+        ]
+        .iter()
+        .any(|p| ct.starts_with(p))
+}
+
+/// Decodes body bytes to text. Valid UTF-8 is used as-is; otherwise `cfg.body_encoding`
+/// ("latin1"/"iso-8859-1") decides, falling back to lossy UTF-8.
+/// Returns the text plus an informational note when the body was not UTF-8.
+pub fn decode_body(bytes: &[u8], cfg: &Config) -> (String, Option<String>) {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => return (text.to_string(), None),
+        // Only an incomplete character at the end: the byte cap cut it in half
+        Err(e) if e.error_len().is_none() => {
+            let valid = std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default();
+            return (valid.to_string(), None);
+        }
+        Err(_) => {}
+    }
+    match cfg.body_encoding.as_deref().map(str::to_ascii_lowercase).as_deref() {
+        Some("latin1" | "latin-1" | "iso-8859-1") => {
+            // Every Latin-1 byte maps directly to the Unicode code point of the same value
+            (bytes.iter().map(|&b| b as char).collect(), None)
+        }
+        _ => (
+            String::from_utf8_lossy(bytes).into_owned(),
+            Some("Info: body is not valid UTF-8; decoded lossily (set body_encoding to match)".to_string()),
+        ),
+    }
+}
+
+// Runs the body rules that fit the response's Content-Type and records the outcome:
+// text rules always; JSON rules only for JSON (an issue otherwise); title rules
+// for anything but JSON (a JSON body has no <title>, so they are skipped with a note).
+fn apply_body_rules(text: &str, content_type: Option<&str>, cfg: &Config, report: &mut ValidationReport) {
+    let is_json = content_type.is_some_and(is_json_content_type);
+    let has_title_rules = cfg.title_equals.is_some() || cfg.title_contains.is_some();
+
+    let (mut ok, mut issues) = check_text_rules(text, cfg);
+
+    if is_json {
+        if has_title_rules {
+            issues.push("Info: JSON response; title rules skipped".to_string());
+        }
+        if cfg.has_json_rules() {
+            let (json_ok, json_issues) = check_json_rules(text, cfg);
+            ok = ok && json_ok;
+            issues.extend(json_issues);
+        }
+    } else {
+        if has_title_rules {
+            let (title_ok, title_issues) = check_title_rules(text, cfg);
+            ok = ok && title_ok;
+            issues.extend(title_issues);
+        }
+        if cfg.has_json_rules() {
+            ok = false;
+            issues.push(format!(
+                "JSON rules configured but response is {}",
+                content_type.unwrap_or("missing a Content-Type")
+            ));
+        }
+    }
+
     report.body_ok = ok;
     report.issues.extend(issues);
+    report.cap_issues(cfg.max_issues);
+    if !ok && cfg.capture_body_preview > 0 {
+        report.body_preview = Some(body_preview(text.as_bytes(), cfg.capture_body_preview));
+    }
+}
+
+/// First `max_bytes` of a body as printable text: invalid UTF-8 is replaced, control
+/// characters are escaped (`\n`, `\u{1b}`, ...) and a cut-off body ends in "...".
+pub fn body_preview(bytes: &[u8], max_bytes: usize) -> String {
+    let end = bytes.len().min(max_bytes);
+    let mut preview: String = String::from_utf8_lossy(&bytes[..end])
+        .chars()
+        .map(|c| if c.is_control() { c.escape_default().to_string() } else { c.to_string() })
+        .collect();
+    if end < bytes.len() {
+        preview.push_str("...");
+    }
+    preview
 }
 
 // --- Unit Tests ---
@@ -246,6 +1418,147 @@ mod tests {
     }
 
     #[test]
+    fn failure_summary_only_when_not_ok() {
+        let passing = ValidationReport {
+            header_ok: true,
+            body_ok: true,
+            https_policy_ok: true,
+            redirect_ok: true,
+            tls_policy_ok: true,
+            ..ValidationReport::default()
+        };
+        assert_eq!(passing.failure_summary(), None);
+
+        let bad_headers = ValidationReport {
+            header_ok: false,
+            ..passing.clone()
+        };
+        assert_eq!(
+            bad_headers.failure_summary().as_deref(),
+            Some("header_ok=false, body_ok=true, https_policy_ok=true, redirect_ok=true, tls_policy_ok=true")
+        );
+
+        let bad_policy_and_body = ValidationReport {
+            body_ok: false,
+            https_policy_ok: false,
+            ..passing.clone()
+        };
+        assert_eq!(
+            bad_policy_and_body.failure_summary().as_deref(),
+            Some("header_ok=true, body_ok=false, https_policy_ok=false, redirect_ok=true, tls_policy_ok=true")
+        );
+
+        // Default report (nothing checked yet) fails everything
+        assert_eq!(
+            ValidationReport::default().failure_summary().as_deref(),
+            Some("header_ok=false, body_ok=false, https_policy_ok=false, redirect_ok=false, tls_policy_ok=false")
+        );
+    }
+
+    #[test]
+    fn parse_host_port_handles_all_host_forms() {
+        assert_eq!(parse_host_port("https://[::1]:8443/health"), Some(("::1".into(), 8443)));
+        assert_eq!(parse_host_port("[::1]:8443"), Some(("::1".into(), 8443)));
+        assert_eq!(parse_host_port("https://[2001:db8::1]/"), Some(("2001:db8::1".into(), 443)));
+        assert_eq!(parse_host_port("example.com"), Some(("example.com".into(), 443)));
+        assert_eq!(parse_host_port("example.com:8080"), Some(("example.com".into(), 8080)));
+        assert_eq!(parse_host_port("http://Example.COM/path?q=1"), Some(("example.com".into(), 80)));
+        assert_eq!(parse_host_port("http://user:pw@10.0.0.1:81/"), Some(("10.0.0.1".into(), 81)));
+
+        // Malformed inputs
+        assert_eq!(parse_host_port("https://[::1"), None);
+        assert_eq!(parse_host_port("https://host:notaport/"), None);
+        assert_eq!(parse_host_port("https:///path"), None);
+    }
+
+    #[test]
+    fn disabled_cert_verification_is_always_reported() {
+        let cfg = Config::builder().accept_invalid_certs(true).build();
+        let mut report = ValidationReport::default();
+        enforce_tls_policy("https://staging.test", Some("TLS 1.3"), &mut report, &cfg);
+        assert!(report.tls_policy_ok);
+        assert!(report.issues.iter().any(|s| s.starts_with("Warning: certificate verification disabled")));
+
+        // No TLS on plain http, so nothing to warn about
+        let mut http = ValidationReport::default();
+        enforce_tls_policy("http://staging.test", None, &mut http, &cfg);
+        assert!(http.issues.is_empty());
+    }
+
+    #[test]
+    fn tls_policy_enforces_minimum_version() {
+        let cfg = Config {
+            min_tls_version: Some(TlsVersion::Tls12),
+            ..Config::default()
+        };
+
+        let mut modern = ValidationReport::default();
+        enforce_tls_policy("https://example.com", Some("TLS 1.3"), &mut modern, &cfg);
+        assert!(modern.tls_policy_ok);
+        assert!(modern.issues.is_empty());
+
+        let mut old = ValidationReport::default();
+        enforce_tls_policy("https://example.com", Some("TLS 1.1"), &mut old, &cfg);
+        assert!(!old.tls_policy_ok);
+        assert!(old.issues.iter().any(|s| s.contains("TLS 1.1 is below required TLS 1.2")));
+
+        // Unknown version: pass, but say so
+        let mut unknown = ValidationReport::default();
+        enforce_tls_policy("https://example.com", None, &mut unknown, &cfg);
+        assert!(unknown.tls_policy_ok);
+        assert!(unknown.issues.iter().any(|s| s.starts_with("Warning: TLS version could not be determined")));
+
+        // Plain http and no floor configured are both fine
+        let mut http = ValidationReport::default();
+        enforce_tls_policy("http://example.com", None, &mut http, &cfg);
+        assert!(http.tls_policy_ok && http.issues.is_empty());
+        let mut no_floor = ValidationReport::default();
+        enforce_tls_policy("https://example.com", Some("TLS 1.0"), &mut no_floor, &Config::default());
+        assert!(no_floor.tls_policy_ok);
+    }
+
+    #[test]
+    fn redirect_policy_compares_hosts() {
+        let cfg = Config {
+            same_host_redirect_only: true,
+            ..Config::default()
+        };
+
+        let mut same = ValidationReport::default();
+        enforce_redirect_policy("https://Example.com/a", "https://example.com:443/b", &mut same, &cfg);
+        assert!(same.redirect_ok);
+        assert!(same.issues.is_empty());
+
+        let mut off = ValidationReport::default();
+        enforce_redirect_policy("https://example.com/", "https://evil.test/", &mut off, &cfg);
+        assert!(!off.redirect_ok);
+        assert!(off.issues.iter().any(|s| s.contains("Redirected off-host")));
+
+        // Policy off: any redirect is fine
+        let mut relaxed = ValidationReport::default();
+        enforce_redirect_policy("https://example.com/", "https://evil.test/", &mut relaxed, &Config::default());
+        assert!(relaxed.redirect_ok);
+    }
+
+    #[test]
+    fn body_equals_any_requires_an_exact_trimmed_match() {
+        let cfg = Config::builder().body_equals_any("OK").body_equals_any("healthy").build();
+        assert_eq!(check_body_text("OK", &cfg), (true, vec![]));
+        assert_eq!(check_body_text("healthy\n", &cfg), (true, vec![]));
+
+        let (ok, issues) = check_body_text("OK, mostly", &cfg);
+        assert!(!ok);
+        assert_eq!(issues, vec!["Body did not equal any of: [\"OK\", \"healthy\"]".to_string()]);
+
+        // A body cut off at the cap can't count as an exact match
+        let capped = Config::builder().body_equals_any("OK").max_body_bytes(2).build();
+        let (ok, issues) = check_body_text("OK", &capped);
+        assert!(!ok);
+        assert!(issues[0].starts_with("Body reached the 2-byte cap"));
+    }
+
+    #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn body_text_all_and_any_modes() {
         let mut cfg = Config::default();
         cfg.body_contains_all = vec!["Welcome".into(), "Home".into()];
@@ -273,4 +1586,413 @@ mod tests {
         assert!(!ok4);
         assert!(issues4.iter().any(|s| s.contains("ANY of")));
     }
+
+    #[test]
+    fn header_freshness_checks_date_age() {
+        let now = 1_709_210_096; // Thu, 29 Feb 2024 12:34:56 GMT
+        let rules = [("Last-Modified", 600)];
+        let with = |v: &str| vec![("last-modified".to_string(), v.to_string())];
+
+        // Five minutes old: fresh
+        let (ok, issues) = check_header_freshness(&with("Thu, 29 Feb 2024 12:29:56 GMT"), &rules, now);
+        assert!(ok, "{:?}", issues);
+
+        // An hour old: stale
+        let (ok, issues) = check_header_freshness(&with("Thu, 29 Feb 2024 11:34:56 GMT"), &rules, now);
+        assert!(!ok);
+        assert_eq!(
+            issues,
+            vec!["Header Last-Modified is stale: 3600 s old, allowed 600 s ('Thu, 29 Feb 2024 11:34:56 GMT')"]
+        );
+
+        // Not a date at all
+        let (ok, issues) = check_header_freshness(&with("yesterday"), &rules, now);
+        assert!(!ok);
+        assert_eq!(issues, vec!["Header Last-Modified is not a valid HTTP date: 'yesterday'"]);
+
+        let (ok, issues) = check_header_freshness(&[], &rules, now);
+        assert!(!ok);
+        assert_eq!(issues, vec!["Missing header: Last-Modified"]);
+
+        // Wired into header validation (against the clock, so use an ancient date)
+        let cfg = Config::builder().https_required(false).header_fresh("Date", 60).build();
+        let report = evaluate(&[("Date".into(), "Sun, 06 Nov 1994 08:49:37 GMT".into())], "", &cfg);
+        assert!(!report.header_ok);
+        assert!(report.issues.iter().any(|i| i.starts_with("Header Date is stale")), "{:?}", report.issues);
+
+        // ...or against the time of the check, however long ago that was
+        let date = [("Date".into(), "Thu, 29 Feb 2024 12:34:26 GMT".into())];
+        let stale_at = |now| evaluate_at(&date, "", &cfg, now).issues.iter().any(|i| i.starts_with("Header Date is stale"));
+        assert!(!stale_at(now));
+        assert!(stale_at(now + 60));
+    }
+
+    #[test]
+    fn body_exactly_one_of_rejects_none_and_several() {
+        let cfg = Config::builder().body_exactly_one_of("Logged in").body_exactly_one_of("Logged out").build();
+
+        let (ok, issues) = check_body_text("<p>Logged in as bob</p>", &cfg);
+        assert!(ok);
+        assert!(issues.is_empty());
+
+        let (ok, issues) = check_body_text("<p>Maintenance</p>", &cfg);
+        assert!(!ok);
+        assert_eq!(issues, vec![r#"Body contained 0 of ["Logged in", "Logged out"], expected exactly one"#]);
+
+        let (ok, issues) = check_body_text("Logged in ... Logged out", &cfg);
+        assert!(!ok);
+        assert!(issues[0].starts_with("Body contained 2 of"), "{:?}", issues);
+        assert!(issues[0].ends_with(r#"(found ["Logged in", "Logged out"])"#), "{:?}", issues);
+    }
+
+    #[test]
+    fn title_rules_check_title_text() {
+        let cfg = Config {
+            title_equals: Some("Example Domain".into()),
+            title_contains: Some("Example".into()),
+            ..Config::default()
+        };
+
+        let html = "<html><HEAD><Title lang=\"en\"> Example Domain </TITLE></head></html>";
+        assert_eq!(extract_title(html), Some("Example Domain"));
+        let (ok, issues) = check_body_text(html, &cfg);
+        assert!(ok);
+        assert!(issues.is_empty());
+
+        let (ok, issues) = check_body_text("<title>Other Site</title>", &cfg);
+        assert!(!ok);
+        assert!(issues.iter().any(|s| s.contains("Title mismatch")));
+        assert!(issues.iter().any(|s| s.contains("Title does not contain 'Example'")));
+
+        let (ok, issues) = check_body_text("<html><body>no title</body></html>", &cfg);
+        assert!(!ok);
+        assert!(issues.iter().any(|s| s.contains("missing <title>")));
+        assert_eq!(extract_title("<title>unterminated"), None);
+    }
+
+    #[test]
+    fn evaluate_runs_several_rule_sets_offline() {
+        let headers = vec![
+            ("content-type".to_string(), "text/html; charset=utf-8".to_string()),
+            ("X-Frame-Options".to_string(), "DENY".to_string()),
+        ];
+        let body = "<title>Status</title> all systems operational";
+
+        let relaxed = Config {
+            body_contains_all: vec!["operational".into()],
+            ..Config::default()
+        };
+        let report = evaluate(&headers, body, &relaxed);
+        assert!(report.overall_ok(), "issues: {:?}", report.issues);
+
+        let strict = Config {
+            header_equals: vec![("X-Frame-Options", "SAMEORIGIN".into())],
+            body_contains_all: vec!["degraded".into()],
+            ..Config::default()
+        };
+        let report = evaluate(&headers, body, &strict);
+        assert!(!report.header_ok);
+        assert!(!report.body_ok);
+        assert!(report.issues.iter().any(|s| s.contains("Header X-Frame-Options mismatch")));
+        assert!(report.issues.iter().any(|s| s.contains("'degraded'")));
+    }
+
+    #[test]
+    fn non_utf8_bodies_are_decoded_or_noted() {
+        let latin1 = b"<p>Caf\xe9 ouvert</p>"; // "Café" in Latin-1, invalid as UTF-8
+        let mut cfg = Config {
+            body_contains_all: vec!["Café".into()],
+            ..Config::default()
+        };
+
+        // No encoding configured: lossy decode, rule fails, and the report says why
+        let mut report = ValidationReport::default();
+        apply_body_bytes(latin1, Some("text/html"), &cfg, &mut report);
+        assert!(!report.body_ok);
+        assert!(report.issues.iter().any(|s| s.contains("not valid UTF-8")));
+
+        // Latin-1 configured: decoded correctly, rule passes, no note
+        cfg.body_encoding = Some("latin1".into());
+        let mut report = ValidationReport::default();
+        apply_body_bytes(latin1, Some("text/html"), &cfg, &mut report);
+        assert!(report.body_ok, "issues: {:?}", report.issues);
+        assert!(report.issues.is_empty());
+
+        // UTF-8 cut in the middle of a character by the byte cap is not "invalid"
+        let (text, note) = decode_body("héllo".as_bytes()[..2].as_ref(), &Config::default());
+        assert_eq!((text.as_str(), note), ("h", None));
+
+        // Binary content: text rules are skipped and noted
+        let mut report = ValidationReport::default();
+        apply_body_bytes(&[0x89, b'P', b'N', b'G', 0xff], Some("image/png"), &cfg, &mut report);
+        assert!(report.body_ok);
+        assert!(report.issues.iter().any(|s| s.contains("binary body (image/png)")));
+    }
+
+    #[test]
+    fn builder_overrides_only_what_is_set() {
+        let cfg = Config::builder()
+            .https_required(false)
+            .require_header("X-Foo")
+            .body_must_contain("hello")
+            .max_body_bytes(1 << 20)
+            .build();
+
+        assert!(!cfg.https_required);
+        assert_eq!(cfg.required_headers, vec!["Content-Type", "X-Foo"]);
+        assert_eq!(cfg.body_contains_all, vec!["hello".to_string()]);
+        assert_eq!(cfg.max_body_bytes, 1 << 20);
+
+        // Everything else keeps the defaults
+        let default = Config::default();
+        assert_eq!(cfg.content_type_allow, default.content_type_allow);
+        assert!(cfg.body_contains_any.is_empty());
+        assert!(!cfg.fingerprint_body);
+        assert!(cfg.success_when.is_none());
+
+        // The built config behaves like a hand-written one
+        let (ok, _) = check_body_text("say hello", &cfg);
+        assert!(ok);
+    }
+
+    #[test]
+    fn json_normalize_matches_minified_and_pretty_bodies() {
+        let headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+        let cfg = Config::builder()
+            .https_required(false)
+            .body_must_contain(r#""error": "none""#)
+            .build();
+        let minified = r#"{"status":"up","error":"none"}"#;
+        let pretty = "{\n  \"status\": \"up\",\n  \"error\": \"none\"\n}";
+
+        // Without normalization only the pretty body matches
+        assert!(!evaluate(&headers, minified, &cfg).body_ok);
+        assert!(evaluate(&headers, pretty, &cfg).body_ok);
+
+        let cfg = Config { body_json_normalize: true, ..cfg };
+        assert!(evaluate(&headers, minified, &cfg).body_ok);
+        assert!(evaluate(&headers, pretty, &cfg).body_ok);
+
+        // Unparseable JSON falls back to the raw text, with a note
+        let report = evaluate(&headers, r#"{"error":"none""#, &cfg);
+        assert!(!report.body_ok);
+        assert!(report.issues.iter().any(|i| i.contains("did not parse")));
+    }
+
+    #[test]
+    fn categorized_buckets_issues_by_prefix() {
+        let report = ValidationReport {
+            issues: vec![
+                "Missing header: X-Frame-Options".into(),
+                "Body missing required text: 'Welcome'".into(),
+                "HTTPS required by policy, but URL is not https".into(),
+                "Header Server mismatch: got 'apache', expected 'nginx'".into(),
+                "Title mismatch: got 'Shop', expected 'Home'".into(),
+                "TLS version TLS 1.1 is below required TLS 1.2".into(),
+                "Info: binary body (image/png); text rules skipped".into(),
+            ],
+            ..ValidationReport::default()
+        };
+        let (header, body, policy) = report.categorized();
+        assert_eq!(header, vec!["Missing header: X-Frame-Options", "Header Server mismatch: got 'apache', expected 'nginx'"]);
+        assert_eq!(body, vec!["Body missing required text: 'Welcome'", "Title mismatch: got 'Shop', expected 'Home'"]);
+        assert_eq!(
+            policy,
+            vec!["HTTPS required by policy, but URL is not https", "TLS version TLS 1.1 is below required TLS 1.2"]
+        );
+    }
+
+    #[test]
+    fn body_preview_only_on_failure() {
+        let headers = [("Content-Type".to_string(), "text/html".to_string())];
+        let cfg = Config::builder()
+            .https_required(false)
+            .body_must_contain("Welcome")
+            .capture_body_preview(12)
+            .build();
+
+        let report = evaluate(&headers, "<p>Welcome</p>", &cfg);
+        assert!(report.body_ok);
+        assert_eq!(report.body_preview, None, "no preview when the body passes");
+
+        let report = evaluate(&headers, "<h1>502 Bad\tGateway</h1>\n", &cfg);
+        assert!(!report.body_ok);
+        assert_eq!(report.body_preview.as_deref(), Some("<h1>502 Bad\\t..."));
+
+        // Off by default
+        let report = evaluate(&headers, "oops", &Config { capture_body_preview: 0, ..cfg });
+        assert_eq!(report.body_preview, None);
+
+        // Short bodies aren't marked as cut; a split UTF-8 character is replaced
+        assert_eq!(body_preview(b"down\x1b", 64), "down\\u{1b}");
+        assert_eq!(body_preview("caf\u{e9}".as_bytes(), 4), "caf\u{fffd}...");
+    }
+
+    #[test]
+    fn max_issues_truncates_with_a_note() {
+        let cfg = Config::builder()
+            .https_required(false)
+            .body_must_contain("alpha")
+            .body_must_contain("beta")
+            .body_must_contain("gamma")
+            .body_must_contain("delta")
+            .max_issues(2)
+            .build();
+        let headers = vec![("Content-Type".to_string(), "text/html".to_string())];
+
+        let mut report = evaluate(&headers, "nothing here", &cfg);
+        assert!(!report.body_ok);
+        assert!(report.issues_truncated());
+        assert_eq!(report.issues.len(), 2);
+        assert!(report.issues[0].contains("alpha"), "{:?}", report.issues);
+        assert_eq!(report.issues[1], "3 more issues suppressed");
+
+        // Later issues keep counting instead of stacking notes
+        report.issues.push("Late issue".into());
+        report.cap_issues(cfg.max_issues);
+        assert_eq!(report.issues.len(), 2);
+        assert!(report.issues[0].contains("alpha"), "{:?}", report.issues);
+        assert_eq!(report.issues[1], "4 more issues suppressed");
+
+        // Within the cap nothing changes
+        let mut short = ValidationReport { issues: vec!["one".into(), "two".into()], ..ValidationReport::default() };
+        short.cap_issues(Some(2));
+        assert_eq!(short.issues, vec!["one", "two"]);
+        assert!(!short.issues_truncated());
+
+        // No cap: everything is kept
+        let uncapped = Config { max_issues: None, ..cfg };
+        let report = evaluate(&headers, "nothing here", &uncapped);
+        assert!(!report.issues_truncated());
+        assert_eq!(report.issues.len(), 4);
+    }
+
+    #[test]
+    fn glob_star_question_and_escapes() {
+        let page = "<p>Thanks!</p>\n<div id=\"order-8841-confirmed\">Rated 5* by buyers</div>";
+
+        // `*` spans any run of characters within a line
+        assert!(glob_match_in(page, "order-*-confirmed"));
+        assert!(glob_match_in(page, "order-*"));
+        assert!(!glob_match_in(page, "order-*-cancelled"));
+        // ...but not across lines
+        assert!(!glob_match_in(page, "Thanks*order"));
+
+        // `?` is exactly one character
+        assert!(glob_match_in(page, "order-88??-confirmed"));
+        assert!(!glob_match_in(page, "order-88?-confirmed"));
+
+        // Escaped `*` is a literal asterisk
+        assert!(glob_match_in(page, r"Rated 5\* by"));
+        assert!(!glob_match_in("Rated 50 by", r"Rated 5\* by"));
+        assert!(glob_match_in("Rated 50 by", "Rated 5* by"));
+
+        // Wired into the body rules
+        let cfg = Config::builder()
+            .body_matches_glob("order-*-confirmed")
+            .body_matches_glob("invoice-????")
+            .build();
+        let (ok, issues) = check_body_text(page, &cfg);
+        assert!(!ok);
+        assert_eq!(issues, vec!["Body did not match pattern: 'invoice-????'".to_string()]);
+    }
+
+    #[test]
+    fn body_fraction_uses_content_length() {
+        let cfg = Config::builder().max_body_fraction(0.1).max_body_bytes(5_000).build();
+        let with_len = |len: &str| vec![("Content-Length".to_string(), len.to_string())];
+
+        assert_eq!(body_read_limit(&with_len("20000"), &cfg), 2_000);
+        assert_eq!(body_read_limit(&with_len("1000000"), &cfg), 5_000); // still capped
+        assert_eq!(body_read_limit(&[], &cfg), 5_000); // unknown length
+        assert_eq!(body_read_limit(&with_len("20000"), &Config::default()), 64 * 1024);
+    }
+
+    #[test]
+    fn body_rules_follow_content_type() {
+        let json = vec![("Content-Type".to_string(), "application/json; charset=utf-8".to_string())];
+        let html = vec![("Content-Type".to_string(), "text/html".to_string())];
+        let json_body = r#"{"data":{"status":"up"}}"#;
+        let html_body = "<html><head><title>Status</title></head></html>";
+
+        // JSON rules on a JSON response
+        let json_cfg = Config::builder().require_json_field("/data/status").build();
+        assert!(evaluate(&json, json_body, &json_cfg).body_ok);
+        let missing = Config::builder().require_json_field("/data/version").build();
+        let report = evaluate(&json, json_body, &missing);
+        assert!(!report.body_ok);
+        assert!(report.issues.contains(&"JSON field missing: '/data/version'".to_string()));
+
+        // JSON rules on an HTML response: flagged, not silently passed
+        let report = evaluate(&html, html_body, &json_cfg);
+        assert!(!report.body_ok);
+        assert!(report.issues.contains(&"JSON rules configured but response is text/html".to_string()));
+
+        // Title rules on HTML apply; on JSON they are skipped with a note
+        let title_cfg = Config::builder().title_equals("Status").build();
+        assert!(evaluate(&html, html_body, &title_cfg).body_ok);
+        let report = evaluate(&json, json_body, &title_cfg);
+        assert!(report.body_ok);
+        assert!(report.issues.iter().any(|i| i.contains("title rules skipped")));
+
+        // Text rules apply to both
+        let text_cfg = Config::builder().body_must_contain("up").build();
+        assert!(evaluate(&json, json_body, &text_cfg).body_ok);
+        assert!(!evaluate(&html, html_body, &text_cfg).body_ok);
+    }
+
+    #[test]
+    fn json_shape_reports_missing_and_mistyped_fields() {
+        let spec = vec![
+            ("id".to_string(), JsonType::Number),
+            ("name".to_string(), JsonType::String),
+            ("/meta/tags".to_string(), JsonType::Array),
+        ];
+        let (ok, issues) = check_json_shape(r#"{"id":7,"name":"api","meta":{"tags":["a"]}}"#, &spec);
+        assert!(ok, "{:?}", issues);
+
+        let (ok, issues) = check_json_shape(r#"{"id":"7","meta":{"tags":null}}"#, &spec);
+        assert!(!ok);
+        assert_eq!(
+            issues,
+            vec![
+                "JSON field 'id' is string, expected number",
+                "JSON field missing: 'name' (expected string)",
+                "JSON field '/meta/tags' is null, expected array",
+            ]
+        );
+
+        let (ok, issues) = check_json_shape("<html>", &spec);
+        assert!(!ok);
+        assert!(issues[0].starts_with("Body is not valid JSON"));
+
+        assert_eq!(JsonType::from_name("Boolean"), Some(JsonType::Bool));
+        assert_eq!(JsonType::from_name("date"), None);
+    }
+
+    #[test]
+    fn ip_version_filters_addresses() {
+        let v4: SocketAddr = "127.0.0.1:80".parse().unwrap();
+        let v6: SocketAddr = "[::1]:80".parse().unwrap();
+        assert!(IpVersion::Auto.allows(&v4) && IpVersion::Auto.allows(&v6));
+        assert!(IpVersion::V4Only.allows(&v4) && !IpVersion::V4Only.allows(&v6));
+        assert!(!IpVersion::V6Only.allows(&v4) && IpVersion::V6Only.allows(&v6));
+
+        assert_eq!(IpVersion::from_name("4"), Some(IpVersion::V4Only));
+        assert_eq!(IpVersion::from_name("IPv6"), Some(IpVersion::V6Only));
+        assert_eq!(IpVersion::from_name("auto"), Some(IpVersion::Auto));
+        assert_eq!(IpVersion::from_name("5"), None);
+    }
+
+    #[test]
+    fn fingerprint_is_stable_and_content_sensitive() {
+        let a = fingerprint(b"<html>hello</html>");
+        let b = fingerprint(b"<html>hello</html>");
+        let c = fingerprint(b"<html>hullo</html>");
+        assert_eq!(a, b, "identical bodies must hash the same");
+        assert_ne!(a, c, "different bodies must hash differently");
+        assert_eq!(a.len(), 16);
+        // Known FNV-1a 64 value for the empty input (the offset basis)
+        assert_eq!(fingerprint(b""), "cbf29ce484222325");
+    }
 }
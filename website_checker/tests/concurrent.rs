@@ -1,5 +1,17 @@
-use website_checker::concurrent::check_many;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use website_checker::concurrent::{
+    check_many, check_many_with, check_many_with_timeouts, BatchConfig, Checker, BUDGET_EXHAUSTED,
+    DEADLINE_EXCEEDED, WORKER_LOST,
+};
 use website_checker::status::{CheckStatus, WebsiteStatus};
+use website_checker::validation::Config;
+use website_checker::{run_checks, run_checks_with};
 
 /// Helper: run sequentially using the same API for comparison.
 fn check_sequential(urls: &[String]) -> Vec<WebsiteStatus> {
@@ -51,3 +63,388 @@ fn concurrent_preserves_input_order() {
     assert_eq!(conc[0].url, urls[0]);
     assert_eq!(conc[1].url, urls[1]);
 }
+
+/// Helper: a local server answering `conns` connections with a small 200 page.
+fn start_ok_server(conns: usize) -> (String, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind ephemeral port");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        for _ in 0..conns {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                );
+            }
+        }
+    });
+    (url, handle)
+}
+
+#[test]
+fn checker_pool_is_reusable_across_runs() {
+    let (ok_url, server) = start_ok_server(2);
+    let urls = vec![
+        format!("{}/first", ok_url),
+        "http://definitely-not-a-real-host.invalid".to_string(),
+        format!("{}/second", ok_url),
+    ];
+
+    let checker = Checker::new(BatchConfig {
+        workers: 2,
+        max_retries: 0,
+        cfg: Config { https_required: false, ..Config::default() },
+        ..BatchConfig::default()
+    });
+
+    // First run: the two local URLs succeed, the invalid host fails; order preserved
+    let first = checker.run(urls.clone());
+    assert_eq!(first.iter().map(|r| r.url.as_str()).collect::<Vec<_>>(), urls);
+    assert!(matches!(first[0].status, CheckStatus::Success(200)));
+    assert!(matches!(first[1].status, CheckStatus::Transport(_)));
+    assert!(matches!(first[2].status, CheckStatus::Success(200)));
+    server.join().unwrap();
+
+    // Second run on the same pool (server is gone now, so everything is a transport error)
+    let second = checker.run(urls.clone());
+    assert_eq!(second.len(), 3);
+    assert_eq!(second[2].url, urls[2]);
+    assert!(second.iter().all(|r| matches!(r.status, CheckStatus::Transport(_))));
+
+    drop(checker); // workers are joined cleanly
+}
+
+/// Helper: a local server that handles each of `conns` connections on its own thread,
+/// records when it was accepted, and answers after `delay`.
+fn start_timing_server(conns: usize, delay: Duration) -> (String, Arc<Mutex<Vec<Instant>>>, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind ephemeral port");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let accepted = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&accepted);
+    let handle = thread::spawn(move || {
+        let mut handlers = Vec::new();
+        for _ in 0..conns {
+            if let Ok((mut stream, _)) = listener.accept() {
+                seen.lock().unwrap().push(Instant::now());
+                handlers.push(thread::spawn(move || {
+                    let mut buf = [0u8; 1024];
+                    let _ = stream.read(&mut buf);
+                    thread::sleep(delay);
+                    let _ = stream.write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                    );
+                }));
+            }
+        }
+        for h in handlers {
+            let _ = h.join();
+        }
+    });
+    (url, accepted, handle)
+}
+
+#[test]
+fn stagger_spreads_first_requests() {
+    let (url, accepted, server) = start_timing_server(3, Duration::from_millis(400));
+    let urls: Vec<String> = (0..3).map(|i| format!("{}/{}", url, i)).collect();
+
+    let batch = BatchConfig {
+        workers: 3,
+        max_retries: 0,
+        stagger: Duration::from_millis(150),
+        cfg: Config { https_required: false, ..Config::default() },
+        ..BatchConfig::default()
+    };
+    let results = website_checker::concurrent::check_many_with(urls, &batch);
+    server.join().unwrap();
+
+    assert!(results.iter().all(|r| matches!(r.status, CheckStatus::Success(200))));
+    let mut times = accepted.lock().unwrap().clone();
+    times.sort();
+    assert_eq!(times.len(), 3);
+    // Workers 0, 1, 2 start at roughly 0ms, 150ms, 300ms
+    assert!(times[1] - times[0] >= Duration::from_millis(100), "gaps: {:?}", times);
+    assert!(times[2] - times[1] >= Duration::from_millis(100), "gaps: {:?}", times);
+}
+
+#[test]
+fn run_checks_returns_results_and_stats() {
+    let (ok_url, server) = start_ok_server(2);
+    let urls = vec![
+        format!("{}/a", ok_url),
+        "http://definitely-not-a-real-host.invalid".to_string(),
+        format!("{}/b", ok_url),
+    ];
+    let cfg = Config { https_required: false, ..Config::default() };
+
+    let (results, stats) = run_checks(urls.clone(), 3, 0, cfg);
+    server.join().unwrap();
+
+    assert_eq!(results.iter().map(|r| r.url.as_str()).collect::<Vec<_>>(), urls);
+    assert_eq!(stats.total, 3);
+    assert_eq!(stats.successes, 2);
+    assert_eq!(stats.transport_errors, 1);
+    assert!(results[0].healthy && results[2].healthy);
+
+    // Empty input: nothing checked, zeroed stats
+    let (results, stats) = run_checks(Vec::new(), 4, 1, Config::default());
+    assert!(results.is_empty());
+    assert_eq!(stats.total, 0);
+}
+
+#[test]
+fn many_requests_to_one_host_with_idle_limits() {
+    const N: usize = 40;
+    let (ok_url, server) = start_ok_server(N);
+    let urls: Vec<String> = (0..N).map(|i| format!("{}/page{}", ok_url, i)).collect();
+
+    let batch = BatchConfig {
+        workers: 8,
+        max_retries: 0,
+        cfg: Config::builder()
+            .https_required(false)
+            .max_idle_connections(4, 1)
+            .build(),
+        ..BatchConfig::default()
+    };
+    let results = check_many_with(urls, &batch);
+    server.join().unwrap();
+
+    assert_eq!(results.len(), N);
+    assert!(
+        results.iter().all(|r| matches!(r.status, CheckStatus::Success(200))),
+        "every request should complete"
+    );
+}
+
+/// Helper: a keep-alive server answering every request on a connection until the
+/// client closes it. Counts the connections it accepted.
+fn start_keep_alive_server() -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind ephemeral port");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&accepted);
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            counter.fetch_add(1, Ordering::SeqCst);
+            thread::spawn(move || {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    // Request line and headers, up to the blank line (GET: no body)
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).unwrap_or(0) > 0 && line != "\r\n" {
+                        line.clear();
+                    }
+                    if line != "\r\n" {
+                        break; // client closed the connection
+                    }
+                    let ok = b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 2\r\n\r\nok";
+                    if stream.write_all(ok).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    (url, accepted)
+}
+
+#[test]
+fn checker_reuses_connections_across_runs() {
+    let pool = |cfg| Checker::new(BatchConfig { workers: 1, max_retries: 0, cfg, ..BatchConfig::default() });
+    let base = || Config::builder().https_required(false).fetch_timestamp(false);
+
+    // One client for the whole pool: later runs reuse the first connection
+    let (url, accepted) = start_keep_alive_server();
+    let checker = pool(base().build());
+    for _ in 0..3 {
+        assert!(matches!(checker.run(vec![format!("{}/x", url)])[0].status, CheckStatus::Success(200)));
+    }
+    assert_eq!(accepted.load(Ordering::SeqCst), 1);
+
+    // With no idle connections kept, every check connects again
+    let (url, accepted) = start_keep_alive_server();
+    let checker = pool(base().max_idle_connections(0, 0).build());
+    for _ in 0..3 {
+        assert!(matches!(checker.run(vec![format!("{}/x", url)])[0].status, CheckStatus::Success(200)));
+    }
+    assert_eq!(accepted.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn one_result_per_url_and_method() {
+    let (ok_url, server) = start_ok_server(2);
+    let checker = Checker::new(BatchConfig {
+        workers: 2,
+        max_retries: 0,
+        cfg: Config::builder()
+            .https_required(false)
+            .body_must_contain("ok")
+            .build(),
+        ..BatchConfig::default()
+    });
+
+    let results = checker.run_targets(vec![
+        (ok_url.clone(), "GET".to_string()),
+        (ok_url.clone(), "HEAD".to_string()),
+    ]);
+    server.join().unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].method, "GET");
+    assert_eq!(results[1].method, "HEAD");
+    for ws in &results {
+        assert_eq!(ws.url, ok_url);
+        assert!(matches!(ws.status, CheckStatus::Success(200)));
+        assert!(ws.healthy, "{} should pass: {:?}", ws.method, ws.validation.issues);
+    }
+    // HEAD has no body, so the body rule was skipped rather than failed
+    assert!(results[1].validation.issues.iter().any(|i| i.contains("body rules skipped")));
+}
+
+#[test]
+fn request_budget_caps_a_batch() {
+    let (ok_url, server) = start_ok_server(2);
+    let urls: Vec<String> = (0..5).map(|i| format!("{}/page{}", ok_url, i)).collect();
+
+    let results = check_many_with(
+        urls.clone(),
+        &BatchConfig {
+            workers: 3,
+            max_retries: 2,
+            max_total_requests: Some(2),
+            cfg: Config { https_required: false, ..Config::default() },
+            ..BatchConfig::default()
+        },
+    );
+    server.join().unwrap();
+
+    // Order preserved, and only two requests were sent
+    assert_eq!(results.iter().map(|r| r.url.as_str()).collect::<Vec<_>>(), urls);
+    let sent = results.iter().filter(|r| matches!(r.status, CheckStatus::Success(200))).count();
+    assert_eq!(sent, 2);
+    for r in results.iter().filter(|r| !matches!(r.status, CheckStatus::Success(_))) {
+        assert!(matches!(&r.status, CheckStatus::Transport(e) if e == BUDGET_EXHAUSTED));
+        assert_eq!(r.attempts, 0);
+    }
+}
+
+#[test]
+fn per_host_limit_caps_concurrent_requests() {
+    const N: usize = 12;
+    let (url, accepted, server) = start_timing_server(N, Duration::from_millis(100));
+    let urls: Vec<String> = (0..N).map(|i| format!("{}/path{}", url, i)).collect();
+
+    let results = check_many_with(
+        urls,
+        &BatchConfig {
+            workers: N,
+            max_retries: 0,
+            max_per_host: Some(2),
+            cfg: Config { https_required: false, ..Config::default() },
+            ..BatchConfig::default()
+        },
+    );
+    server.join().unwrap();
+    assert!(results.iter().all(|r| matches!(r.status, CheckStatus::Success(200))));
+
+    // Each request is held for 100ms, so with 2 slots no 100ms window
+    // (minus some slack) may contain more than 2 accepted connections
+    let mut times = accepted.lock().unwrap().clone();
+    times.sort();
+    assert_eq!(times.len(), N);
+    for w in times.windows(3) {
+        assert!(w[2] - w[0] >= Duration::from_millis(80), "more than 2 concurrent: {:?}", times);
+    }
+}
+
+#[test]
+fn deadline_returns_partial_stats_on_time() {
+    let (fast_url, fast_server) = start_ok_server(1);
+    let (slow_url, _accepted, slow_server) = start_timing_server(2, Duration::from_secs(2));
+    let urls = vec![
+        format!("{}/fast", fast_url),
+        format!("{}/slow1", slow_url),
+        format!("{}/slow2", slow_url),
+    ];
+
+    let started = Instant::now();
+    let (results, stats) = run_checks_with(
+        urls.clone(),
+        &BatchConfig {
+            workers: 3,
+            max_retries: 0,
+            deadline: Some(Duration::from_millis(500)),
+            // Local clock: the deadline includes getting the batch timestamp
+            cfg: Config { https_required: false, fetch_timestamp: false, ..Config::default() },
+            ..BatchConfig::default()
+        },
+    );
+    let elapsed = started.elapsed();
+    fast_server.join().unwrap();
+
+    // Returned at the deadline instead of waiting ~2s for the slow server
+    assert!(elapsed < Duration::from_millis(1500), "took {:?}", elapsed);
+    assert_eq!(results.iter().map(|r| r.url.as_str()).collect::<Vec<_>>(), urls);
+    assert!(matches!(results[0].status, CheckStatus::Success(200)));
+    for r in &results[1..] {
+        assert!(matches!(&r.status, CheckStatus::Transport(e) if e == DEADLINE_EXCEEDED));
+        assert!(r.response_time >= Duration::from_millis(500));
+    }
+
+    // Stats cover every URL, counting the stragglers as transport errors
+    assert_eq!(stats.total, 3);
+    assert_eq!(stats.successes, 1);
+    assert_eq!(stats.transport_errors, 2);
+    slow_server.join().unwrap();
+}
+
+#[test]
+fn per_url_timeout_overrides_the_default() {
+    // Both requests hit a server that answers after 600ms; the default timeout is 300ms
+    let (url, _accepted, server) = start_timing_server(2, Duration::from_millis(600));
+    let urls = vec![
+        (format!("{}/default", url), None),
+        (format!("{}/patient", url), Some(Duration::from_secs(3))),
+    ];
+
+    let batch = BatchConfig {
+        workers: 2,
+        max_retries: 0,
+        cfg: Config {
+            https_required: false,
+            fetch_timestamp: false,
+            timeout: Duration::from_millis(300),
+            ..Config::default()
+        },
+        ..BatchConfig::default()
+    };
+    let results = check_many_with_timeouts(urls, &batch);
+    server.join().unwrap();
+
+    assert!(matches!(results[0].status, CheckStatus::Transport(_)), "{:?}", results[0].status);
+    assert!(matches!(results[1].status, CheckStatus::Success(200)), "{:?}", results[1].status);
+}
+
+#[test]
+fn dead_worker_yields_a_transport_result() {
+    let (ok_url, server) = start_ok_server(1);
+    let checker = Checker::new(BatchConfig {
+        workers: 2,
+        max_retries: 0,
+        cfg: Config::builder()
+            .https_required(false)
+            .fetch_timestamp(false)
+            .success_when(|_, _| panic!("broken health rule"))
+            .build(),
+        ..BatchConfig::default()
+    });
+
+    // The worker panics inside the check: the batch still returns one result per URL
+    let results = checker.run(vec![format!("{}/boom", ok_url)]);
+    server.join().unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(matches!(&results[0].status, CheckStatus::Transport(e) if e == WORKER_LOST), "{:?}", results[0].status);
+    assert!(!results[0].healthy);
+}
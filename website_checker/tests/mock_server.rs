@@ -1,23 +1,29 @@
 // tests/mock_server.rs
 //! Integration tests using a tiny mock HTTP server built with `std::net::TcpListener`.
-//! No extra dependencies required.
+//! Only `flate2` (dev-dependency) is used, to build gzip-encoded responses.
 
 use std::io::{Read, Write};
 use std::net::TcpListener;
 use std::thread;
 use std::time::{Duration, Instant};
 
-use website_checker::status::{CheckStatus, WebsiteStatus};
-use website_checker::validation::Config;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use website_checker::sink::{emit_batch, WebhookSink};
+use website_checker::stats::Stats;
+use website_checker::etag_store::EtagStore;
+use website_checker::status::{ip_version_summary, CheckStatus, RenderOptions, WebsiteStatus, TOO_MANY_REDIRECTS};
+use website_checker::validation::{evaluate, fingerprint, Config, IpVersion};
 
 /// Start a one-shot mock server that accepts exactly one connection and replies
 /// with `response`. If `delay` is Some(d), the server sleeps `d` before writing.
 ///
 /// Returns the base URL (e.g. "http://127.0.0.1:54321") and the join handle.
 fn start_mock_server(
-    response: &'static str,
+    response: impl Into<String>,
     delay: Option<Duration>,
 ) -> (String, thread::JoinHandle<()>) {
+    let response = response.into();
     let listener = TcpListener::bind("127.0.0.1:0").expect("bind ephemeral port");
     let addr = listener.local_addr().unwrap();
     let url = format!("http://{}", addr);
@@ -71,6 +77,7 @@ fn partial_response() -> &'static str {
 }
 
 /// Helper: make a Config that disables the HTTPS policy (since mock server is http://)
+#[allow(clippy::field_reassign_with_default)]
 fn cfg_no_https() -> Config {
     let mut cfg = Config::default();
     cfg.https_required = false;
@@ -100,6 +107,25 @@ fn mock_200_ok_and_body_validation() {
     handle.join().unwrap();
 }
 
+#[test]
+fn mock_glob_rule_checks_the_real_body() {
+    // A glob is the only body rule, so the body is read for it alone
+    let mut cfg = cfg_no_https();
+    cfg.body_matches_glob = vec!["hello w?r*".into()];
+    let (url, handle) = start_mock_server(ok_response_html(), None);
+    let ws = WebsiteStatus::request_with(&url, &cfg);
+    handle.join().unwrap();
+    assert!(ws.validation.body_ok, "{:?}", ws.validation.issues);
+
+    cfg.body_matches_glob = vec!["goodbye *".into()];
+    let (url, handle) = start_mock_server(ok_response_html(), None);
+    let ws = WebsiteStatus::request_with(&url, &cfg);
+    handle.join().unwrap();
+    assert!(!ws.validation.body_ok);
+    assert!(!ws.healthy);
+    assert!(ws.validation.issues.iter().any(|i| i == "Body did not match pattern: 'goodbye *'"), "{:?}", ws.validation.issues);
+}
+
 #[test]
 fn mock_404_maps_to_http_error() {
     let (url, handle) = start_mock_server(not_found_response(), None);
@@ -163,3 +189,827 @@ fn mock_partial_response_is_transport_error() {
 
     handle.join().unwrap();
 }
+
+#[test]
+fn mock_cross_host_redirect_fails_same_host_policy() {
+    // Final target, reached through "localhost" instead of "127.0.0.1"
+    let (target, target_handle) = start_mock_server(ok_response_html(), None);
+    let target = target.replace("127.0.0.1", "localhost");
+
+    let redirect = format!(
+        "HTTP/1.1 302 Found\r\nLocation: {}/\r\nContent-Length: 0\r\n\r\n",
+        target
+    );
+    let (url, handle) = start_mock_server(redirect, None);
+
+    let mut cfg = cfg_no_https();
+    cfg.same_host_redirect_only = true;
+    let ws = WebsiteStatus::request_with(&url, &cfg);
+
+    match ws.status {
+        CheckStatus::Success(code) => assert_eq!(code, 200),
+        other => panic!("expected redirect to be followed to a 200, got {:?}", other),
+    }
+    assert!(!ws.validation.redirect_ok, "cross-host redirect should be flagged");
+    assert!(!ws.validation.overall_ok());
+    assert!(ws.validation.issues.iter().any(|s| s.contains("Redirected off-host")));
+
+    handle.join().unwrap();
+    target_handle.join().unwrap();
+}
+
+#[test]
+fn mock_cross_host_307_does_not_resend_the_body() {
+    // Nothing listens on the target: the request must not be repeated there at all
+    let redirect = "HTTP/1.1 307 Temporary Redirect\r\nLocation: http://localhost:9/upload\r\nContent-Length: 0\r\n\r\n";
+    let (url, handle) = start_mock_server(redirect, None);
+
+    let mut cfg = cfg_no_https();
+    cfg.method = "POST".to_string();
+    cfg.request_body = Some(("text/plain".to_string(), b"secret".to_vec()));
+    let ws = WebsiteStatus::request_with(&url, &cfg);
+
+    assert!(matches!(ws.status, CheckStatus::Success(307)), "{:?}", ws.status);
+    assert!(ws.redirect_chain.is_empty());
+    assert!(!ws.validation.redirect_ok);
+    assert!(
+        ws.validation.issues.iter().any(|s| s.starts_with("Not following 307 to http://localhost:9/upload")),
+        "{:?}",
+        ws.validation.issues
+    );
+
+    handle.join().unwrap();
+}
+
+#[test]
+fn mock_redirect_loop_is_too_many_redirects() {
+    // Every request is redirected back to the same path
+    let redirect_loop = "HTTP/1.1 302 Found\r\nLocation: /loop\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    let (url, handle) = start_recording_server(redirect_loop, 3);
+
+    let cfg = Config {
+        max_redirects: 3,
+        ..cfg_no_https()
+    };
+    let ws = WebsiteStatus::request_with(&url, &cfg);
+    let requests = handle.join().unwrap();
+
+    assert!(ws.status.is_too_many_redirects(), "got {:?}", ws.status);
+    assert!(matches!(&ws.status, CheckStatus::Transport(e) if e == TOO_MANY_REDIRECTS));
+    assert!(ws.validation.issues.iter().any(|s| s.contains("possible redirect loop")));
+    assert_eq!(requests.len(), 3, "stops at the redirect limit");
+}
+
+#[test]
+fn mock_body_fingerprint_tracks_content() {
+    let mut cfg = cfg_no_https();
+    cfg.fingerprint_body = true;
+
+    let (url1, h1) = start_mock_server(ok_response_html(), None);
+    let first = WebsiteStatus::request_with(&url1, &cfg);
+    let (url2, h2) = start_mock_server(ok_response_html(), None);
+    let second = WebsiteStatus::request_with(&url2, &cfg);
+    let (url3, h3) = start_mock_server(not_found_response(), None);
+    let other = WebsiteStatus::request_with(&url3, &cfg);
+
+    assert!(first.body_fingerprint.is_some());
+    assert_eq!(first.body_fingerprint, second.body_fingerprint);
+    assert_ne!(first.body_fingerprint, other.body_fingerprint);
+
+    // Fingerprinting is opt-in
+    let (url4, h4) = start_mock_server(ok_response_html(), None);
+    let plain = WebsiteStatus::request_with(&url4, &cfg_no_https());
+    assert!(plain.body_fingerprint.is_none());
+
+    for h in [h1, h2, h3, h4] {
+        h.join().unwrap();
+    }
+}
+
+#[test]
+fn mock_fetch_once_evaluate_many() {
+    let (url, handle) = start_mock_server(ok_response_html(), None);
+    let (status, headers, body) =
+        WebsiteStatus::fetch_body(&url, &cfg_no_https()).expect("fetch should succeed");
+    handle.join().unwrap(); // server is gone; everything below is offline
+
+    assert_eq!(status, 200);
+    assert_eq!(body, "hello world");
+
+    let mut wants_world = cfg_no_https();
+    wants_world.body_contains_all = vec!["world".into()];
+    assert!(evaluate(&headers, &body, &wants_world).overall_ok());
+
+    let mut wants_moon = cfg_no_https();
+    wants_moon.body_contains_all = vec!["moon".into()];
+    wants_moon.header_equals = vec![("X-Frame-Options", "DENY".into())];
+    let report = evaluate(&headers, &body, &wants_moon);
+    assert!(report.header_ok);
+    assert!(!report.body_ok);
+}
+
+#[test]
+fn mock_custom_success_predicate_accepts_404() {
+    // Default rule: a 404 is not healthy
+    let (url, handle) = start_mock_server(not_found_response(), None);
+    let ws = WebsiteStatus::request_with(&url, &cfg_no_https());
+    assert!(!ws.healthy);
+    handle.join().unwrap();
+
+    // Custom rule: "404 is expected here" (e.g. a deleted page that must stay gone)
+    let (url, handle) = start_mock_server(not_found_response(), None);
+    let ws = WebsiteStatus::request_with_predicate(&url, &cfg_no_https(), |status, report| {
+        matches!(status, CheckStatus::HttpError(404)) && report.header_ok
+    });
+    assert!(matches!(ws.status, CheckStatus::HttpError(404)));
+    assert!(ws.healthy, "predicate should accept the 404");
+    handle.join().unwrap();
+}
+
+#[test]
+fn mock_default_success_requires_2xx_and_valid() {
+    let (url, handle) = start_mock_server(ok_response_html(), None);
+    let ws = WebsiteStatus::request_with(&url, &cfg_no_https());
+    assert!(ws.healthy);
+    handle.join().unwrap();
+}
+
+/// Like `start_mock_server`, but answers up to `conns` connections with `response` and
+/// the join handle returns the requests received, in order. Each request is read whole
+/// (the header block plus a Content-Length body, if any).
+fn start_recording_server(
+    response: impl AsRef<[u8]> + Send + 'static,
+    conns: usize,
+) -> (String, thread::JoinHandle<Vec<CapturedRequest>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind ephemeral port");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let mut requests = Vec::new();
+        for _ in 0..conns {
+            let Ok((mut stream, _peer)) = listener.accept() else {
+                break;
+            };
+            let raw = read_request(&mut stream);
+            let _ = stream.write_all(response.as_ref());
+            let _ = stream.flush();
+            requests.push(CapturedRequest {
+                raw: String::from_utf8_lossy(&raw).into_owned(),
+            });
+        }
+        requests
+    });
+    (url, handle)
+}
+
+#[test]
+fn mock_proxy_receives_the_request() {
+    // The mock plays the proxy; the target host doesn't exist, so success means we went via the proxy
+    let (proxy_url, handle) = start_recording_server(ok_response_html(), 1);
+    let cfg = Config {
+        proxy: Some(proxy_url),
+        ..cfg_no_https()
+    };
+    let ws = WebsiteStatus::request_with("http://target.invalid/page", &cfg);
+    assert!(matches!(ws.status, CheckStatus::Success(200)), "got {:?}", ws.status);
+
+    // Proxied HTTP requests carry the absolute URL in the request line
+    let request = handle.join().unwrap().remove(0).raw;
+    assert!(
+        request.starts_with("GET http://target.invalid/page HTTP/1.1"),
+        "unexpected request: {}",
+        request
+    );
+}
+
+#[test]
+fn mock_invalid_proxy_falls_back_to_direct() {
+    let (url, handle) = start_mock_server(ok_response_html(), None);
+    let cfg = Config {
+        proxy: Some("ftp://not-a-proxy:21".into()),
+        ..cfg_no_https()
+    };
+    let ws = WebsiteStatus::request_with(&url, &cfg);
+    assert!(matches!(ws.status, CheckStatus::Success(200)));
+    assert!(ws.validation.issues.iter().any(|i| i.contains("invalid proxy")));
+    assert!(ws.validation.overall_ok());
+    handle.join().unwrap();
+}
+
+#[test]
+fn mock_host_override_keeps_host_header() {
+    // The hostname doesn't resolve; the override sends it to the mock on 127.0.0.1
+    let (mock_url, handle) = start_recording_server(ok_response_html(), 1);
+    let port = mock_url.rsplit(':').next().unwrap();
+    let cfg = Config {
+        host_overrides: vec![("api.staging.invalid".into(), "127.0.0.1".into())],
+        ..cfg_no_https()
+    };
+    let ws = WebsiteStatus::request_with(&format!("http://api.staging.invalid:{}/", port), &cfg);
+    assert!(matches!(ws.status, CheckStatus::Success(200)), "got {:?}", ws.status);
+
+    let request = handle.join().unwrap().remove(0).raw.to_ascii_lowercase();
+    assert!(
+        request.contains(&format!("\r\nhost: api.staging.invalid:{}\r\n", port)),
+        "Host header should keep the original name: {}",
+        request
+    );
+}
+
+#[test]
+fn mock_body_fraction_reads_start_of_large_page() {
+    // 10_000 byte body; "MARKER" sits near the end, well past the first 10%
+    let body = format!("{}MARKER{}", "a".repeat(9_000), "b".repeat(994));
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    let (url, handle) = start_mock_server(response, None);
+    let cfg = Config {
+        max_body_fraction: Some(0.1),
+        fingerprint_body: true,
+        body_contains_all: vec!["MARKER".into()],
+        ..cfg_no_https()
+    };
+    let ws = WebsiteStatus::request_with(&url, &cfg);
+    handle.join().unwrap();
+
+    assert!(matches!(ws.status, CheckStatus::Success(200)));
+    // Only the first 1_000 bytes were read
+    assert_eq!(ws.body_fingerprint.as_deref(), Some(fingerprint(&body.as_bytes()[..1_000]).as_str()));
+    assert!(!ws.validation.body_ok, "marker lies beyond the sampled part");
+}
+
+#[test]
+fn mock_skip_body_ignores_body_rules() {
+    let cfg = Config {
+        body_contains_all: vec!["not on the page".into()],
+        ..cfg_no_https()
+    };
+
+    // Normally the missing text fails the body check
+    let (url, handle) = start_mock_server(ok_response_html(), None);
+    assert!(!WebsiteStatus::request_with(&url, &cfg).validation.body_ok);
+    handle.join().unwrap();
+
+    // Headers-only: body rules are ignored
+    let (url, handle) = start_mock_server(ok_response_html(), None);
+    let ws = WebsiteStatus::request_with(&url, &Config { skip_body: true, ..cfg });
+    assert!(ws.validation.body_ok);
+    assert!(ws.validation.overall_ok());
+    assert!(ws.healthy);
+    handle.join().unwrap();
+}
+
+/// A request as the mock server received it: the raw bytes (request line,
+/// headers and any body), for asserting on what the client sent.
+struct CapturedRequest {
+    raw: String,
+}
+
+impl CapturedRequest {
+    /// First request line, e.g. "GET /page HTTP/1.1".
+    fn request_line(&self) -> &str {
+        self.raw.lines().next().unwrap_or("")
+    }
+
+    /// Value of header `name` (case-insensitive), if it was sent.
+    fn header(&self, name: &str) -> Option<&str> {
+        self.raw
+            .split("\r\n\r\n")
+            .next()?
+            .lines()
+            .skip(1)
+            .filter_map(|l| l.split_once(':'))
+            .find(|(k, _)| k.trim().eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.trim())
+    }
+}
+
+/// Read one full request from `stream`: the header block plus a Content-Length body, if any.
+fn read_request(stream: &mut std::net::TcpStream) -> Vec<u8> {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+    let mut raw = Vec::new();
+    let mut buf = [0u8; 1024];
+    loop {
+        let n = stream.read(&mut buf).unwrap_or(0);
+        raw.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&raw);
+        let complete = text.split_once("\r\n\r\n").is_some_and(|(head, body)| {
+            let len = head
+                .lines()
+                .filter_map(|l| l.split_once(':'))
+                .find(|(k, _)| k.trim().eq_ignore_ascii_case("content-length"))
+                .and_then(|(_, v)| v.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            body.len() >= len
+        });
+        if n == 0 || complete {
+            break;
+        }
+    }
+    raw
+}
+
+/// Start a mock server that answers its n-th connection with `responses[n]`,
+/// e.g. a chain of redirects followed by the final page. Responses should send
+/// `Connection: close` so each request arrives on a new connection.
+fn start_sequence_server(responses: Vec<String>) -> (String, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind ephemeral port");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        for response in responses {
+            let Ok((mut stream, _peer)) = listener.accept() else {
+                return;
+            };
+            read_request(&mut stream);
+            let _ = stream.write_all(response.as_bytes());
+            let _ = stream.flush();
+        }
+    });
+    (url, handle)
+}
+
+/// Start a one-shot mock JSON API that answers with the request body it received,
+/// e.g. a POSTed `{"name":"x"}` comes straight back as an `application/json` response.
+fn start_echo_server() -> (String, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind ephemeral port");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        if let Ok((mut stream, _peer)) = listener.accept() {
+            let raw = read_request(&mut stream);
+            let text = String::from_utf8_lossy(&raw);
+            let body = text.split_once("\r\n\r\n").map_or("", |(_, b)| b);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes());
+            let _ = stream.flush();
+        }
+    });
+    (url, handle)
+}
+
+#[test]
+fn json_post_round_trip() {
+    let (mock_url, handle) = start_echo_server();
+    let cfg = Config::builder()
+        .https_required(false)
+        .json_post(serde_json::json!({"name": "checker", "count": 3}))
+        .json_field_equals("/name", serde_json::json!("checker"))
+        .json_field_equals("/count", serde_json::json!(3))
+        .build();
+    let ws = WebsiteStatus::request_with(&mock_url, &cfg);
+    handle.join().unwrap();
+
+    assert_eq!(ws.method, "POST");
+    assert!(matches!(ws.status, CheckStatus::Success(200)));
+    assert!(ws.healthy, "round trip should pass: {:?}", ws.validation.issues);
+
+    // A different expected value fails with a typed mismatch
+    let (mock_url, handle) = start_echo_server();
+    let cfg = Config::builder()
+        .https_required(false)
+        .json_post(serde_json::json!({"name": "checker"}))
+        .json_field_equals("/name", serde_json::json!("other"))
+        .build();
+    let ws = WebsiteStatus::request_with(&mock_url, &cfg);
+    handle.join().unwrap();
+    assert!(!ws.validation.body_ok);
+    assert!(ws
+        .validation
+        .issues
+        .contains(&r#"JSON field '/name' mismatch: got "checker", expected "other""#.to_string()));
+}
+
+#[test]
+fn json_post_sends_method_content_type_and_body() {
+    let (mock_url, handle) = start_recording_server(ok_response_html(), 2);
+    let cfg = Config::builder()
+        .https_required(false)
+        .json_post(serde_json::json!({"id": 7}))
+        .build();
+    assert_eq!(cfg.json_post, Some(serde_json::json!({"id": 7})));
+    assert_eq!(cfg.method, "POST");
+    WebsiteStatus::request_with(&format!("{}/api", mock_url), &cfg);
+
+    // POST is only the default: a target with its own method keeps it
+    let put = WebsiteStatus::request_method_with_timestamp(&format!("{}/api", mock_url), "PUT", &cfg, "");
+    assert_eq!(put.method, "PUT");
+
+    let requests = handle.join().unwrap();
+    assert_eq!(requests[0].request_line(), "POST /api HTTP/1.1");
+    assert_eq!(requests[0].header("Content-Type"), Some("application/json"));
+    assert!(requests[0].raw.ends_with(r#"{"id":7}"#));
+    assert_eq!(requests[1].request_line(), "PUT /api HTTP/1.1");
+    assert!(requests[1].raw.ends_with(r#"{"id":7}"#));
+}
+
+#[test]
+fn mock_captures_request_headers() {
+    let (mock_url, handle) = start_recording_server(ok_response_html(), 2);
+    let first = WebsiteStatus::request_with(&format!("{}/one", mock_url), &cfg_no_https());
+    let second = WebsiteStatus::request_with(&format!("{}/two", mock_url), &cfg_no_https());
+
+    let captured = handle.join().unwrap();
+    assert_eq!(captured.len(), 2);
+    assert_eq!(captured[0].request_line(), "GET /one HTTP/1.1");
+    assert_eq!(captured[1].request_line(), "GET /two HTTP/1.1");
+    assert!(captured[0].header("user-agent").is_some_and(|ua| ua.starts_with("ureq/")));
+    assert_eq!(captured[0].header("Host"), Some(&mock_url["http://".len()..]));
+    assert_eq!(captured[1].header("Authorization"), None);
+
+    // Each request carried its own ID, and the results recorded the same ones
+    assert_eq!(captured[0].header("X-Request-Id"), Some(first.request_id.as_str()));
+    assert_eq!(captured[1].header("X-Request-Id"), Some(second.request_id.as_str()));
+    assert_ne!(first.request_id, second.request_id);
+}
+
+#[test]
+fn mock_sees_the_request_id() {
+    let (mock_url, handle) = start_recording_server(ok_response_html(), 1);
+    let ws = WebsiteStatus::request_with(&mock_url, &cfg_no_https());
+    assert!(matches!(ws.status, CheckStatus::Success(200)), "got {:?}", ws.status);
+    assert!(!ws.request_id.is_empty());
+
+    // The header the server received is the ID stored on the result
+    let request = handle.join().unwrap().remove(0).raw;
+    let sent = request
+        .lines()
+        .find_map(|l| l.strip_prefix("X-Request-Id: "))
+        .expect("X-Request-Id header missing");
+    assert_eq!(sent.trim(), ws.request_id);
+
+    // IDs are unique per request
+    let (mock_url, handle) = start_recording_server(ok_response_html(), 1);
+    let again = WebsiteStatus::request_with(&mock_url, &cfg_no_https());
+    handle.join().unwrap();
+    assert_ne!(again.request_id, ws.request_id);
+}
+
+// gzip-compresses `text` for mock responses
+fn gzip(text: &str) -> Vec<u8> {
+    let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+    gz.write_all(text.as_bytes()).unwrap();
+    gz.finish().unwrap()
+}
+
+#[test]
+fn mock_accept_headers_and_gzip_body() {
+    let page = "<html>Willkommen</html>";
+    let compressed = gzip(page);
+    let mut gzip_response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        compressed.len()
+    )
+    .into_bytes();
+    gzip_response.extend_from_slice(&compressed);
+
+    // Asked for gzip, got gzip: the body is decompressed before the body rules run
+    let cfg = Config {
+        accept_language: Some("de-DE".into()),
+        accept_encoding: Some("gzip".into()),
+        body_contains_all: vec!["Willkommen".into()],
+        ..cfg_no_https()
+    };
+    let (url, handle) = start_recording_server(gzip_response, 1);
+    let ws = WebsiteStatus::request_with(&url, &cfg);
+    let request = handle.join().unwrap().remove(0);
+    assert_eq!(request.header("Accept-Language"), Some("de-DE"));
+    assert_eq!(request.header("Accept-Encoding"), Some("gzip"));
+    assert!(ws.validation.body_ok, "issues: {:?}", ws.validation.issues);
+
+    // Asked for identity, got a plain body: read as-is
+    let plain_response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        page.len(),
+        page
+    );
+    let cfg = Config {
+        accept_encoding: Some("identity".into()),
+        ..cfg
+    };
+    let (url, handle) = start_recording_server(plain_response, 1);
+    let ws = WebsiteStatus::request_with(&url, &cfg);
+    assert_eq!(handle.join().unwrap()[0].header("Accept-Encoding"), Some("identity"));
+    assert!(ws.validation.body_ok, "issues: {:?}", ws.validation.issues);
+}
+
+#[test]
+fn webhook_receives_failures_as_json() {
+    let (hook_url, handle) = start_recording_server("HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n", 2);
+    let mut failing = WebsiteStatus {
+        status: CheckStatus::HttpError(503),
+        healthy: false,
+        ..WebsiteStatus::not_sent("https://down.test/", "GET", "2024-01-01T00:00:00Z", "")
+    };
+    failing.validation.issues = vec!["Missing header: X-Frame-Options".to_string()];
+    let healthy = WebsiteStatus {
+        status: CheckStatus::Success(200),
+        healthy: true,
+        ..WebsiteStatus::not_sent("https://up.test/", "GET", "2024-01-01T00:00:00Z", "")
+    };
+
+    let mut sink = WebhookSink::new(format!("{}/hook", hook_url), RenderOptions::default());
+    let results = vec![healthy, failing];
+    emit_batch(&mut sink, &results, &Stats::compute(&results));
+    sink.wait();
+    assert_eq!(sink.last_error(), None);
+
+    // The same failures next cycle aren't sent again; different ones are
+    emit_batch(&mut sink, &results, &Stats::compute(&results));
+    let also_down = WebsiteStatus {
+        status: CheckStatus::Transport("refused".into()),
+        ..WebsiteStatus::not_sent("https://other.test/", "GET", "2024-01-01T00:05:00Z", "")
+    };
+    let changed = vec![also_down];
+    emit_batch(&mut sink, &changed, &Stats::compute(&changed));
+    sink.wait();
+    let requests = handle.join().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].raw.contains("https://other.test/"));
+
+    // One POST per batch, carrying only the unhealthy result
+    let req = &requests[0];
+    assert_eq!(req.request_line(), "POST /hook HTTP/1.1");
+    let body = req.raw.split_once("\r\n\r\n").unwrap().1;
+    let json: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"failures": [{
+            "url": "https://down.test/",
+            "status": "http_error",
+            "code": 503,
+            "issues": ["Missing header: X-Frame-Options"],
+            "timestamp": "2024-01-01T00:00:00Z",
+        }]})
+    );
+
+    // A healthy batch sends nothing; an unreachable webhook is reported, not fatal
+    let mut sink = WebhookSink::new("http://127.0.0.1:9/hook", RenderOptions::default());
+    emit_batch(&mut sink, &results[..1], &Stats::compute(&results[..1]));
+    assert_eq!(sink.last_error(), None);
+    emit_batch(&mut sink, &results, &Stats::compute(&results));
+    sink.wait();
+    assert!(sink.last_error().is_some());
+}
+
+#[test]
+fn tcp_probe_reports_open_and_closed_ports() {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind ephemeral port");
+    let open = format!("tcp://{}", listener.local_addr().unwrap());
+
+    // A port that was just released: nothing listens there any more
+    let closed = {
+        let gone = TcpListener::bind("127.0.0.1:0").expect("bind ephemeral port");
+        format!("tcp://{}", gone.local_addr().unwrap())
+    };
+
+    // https_required is on by default, but doesn't apply to tcp:// probes
+    let cfg = Config { fetch_timestamp: false, ..Config::default() };
+    let ws = WebsiteStatus::request_with(&open, &cfg);
+    assert!(matches!(ws.status, CheckStatus::Connected), "got {:?}", ws.status);
+    assert!(ws.status.is_success() && ws.as_success_code().is_none());
+    assert!(ws.healthy && ws.is_tcp_probe(), "issues: {:?}", ws.validation.issues);
+    assert!(ws.response_time < Duration::from_secs(1));
+    assert!(ws.one_line().contains(&format!("{} open", open)));
+    drop(listener);
+
+    let ws = WebsiteStatus::request_with(&closed, &cfg);
+    assert!(matches!(&ws.status, CheckStatus::Transport(e) if e.contains("tcp connect")), "got {:?}", ws.status);
+    assert!(!ws.healthy && ws.is_tcp_probe());
+
+    // A port is required
+    let ws = WebsiteStatus::request_with("tcp://127.0.0.1", &cfg);
+    assert!(matches!(&ws.status, CheckStatus::Transport(e) if e.contains("invalid tcp address")));
+}
+
+#[test]
+fn ip_version_selects_the_address_family() {
+    // A one-shot server on the IPv4 loopback (start_mock_server binds 127.0.0.1)
+    let (v4_url, handle) = start_mock_server(ok_response_html(), None);
+    let v6_only = Config::builder().https_required(false).ip_version(IpVersion::V6Only).build();
+    let ws = WebsiteStatus::request_with(&v4_url, &v6_only);
+    assert!(matches!(&ws.status, CheckStatus::Transport(_)), "got {:?}", ws.status);
+    assert!(ws.validation.issues.iter().any(|i| i.contains("no IPv6 address")), "{:?}", ws.validation.issues);
+
+    let v4_only = Config { ip_version: IpVersion::V4Only, ..v6_only.clone() };
+    assert!(matches!(WebsiteStatus::request_with(&v4_url, &v4_only).status, CheckStatus::Success(200)));
+    handle.join().unwrap();
+
+    // Both families in one go: only IPv4 works for this server
+    let (v4_url, handle) = start_mock_server(ok_response_html(), None);
+    let (v4, v6) = WebsiteStatus::request_each_ip_version(&v4_url, &cfg_no_https());
+    handle.join().unwrap();
+    assert!(v4.healthy && !v6.healthy);
+    let summary = ip_version_summary(&v4, &v6);
+    assert!(summary.starts_with("IPv4: ok (200), IPv6: fail ("), "{}", summary);
+
+    // And the reverse on the IPv6 loopback, if this machine has one
+    let Ok(listener) = TcpListener::bind("[::1]:0") else {
+        return;
+    };
+    let v6_url = format!("http://[::1]:{}/", listener.local_addr().unwrap().port());
+    let server = thread::spawn(move || {
+        if let Ok((mut stream, _)) = listener.accept() {
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf);
+            let _ = stream.write_all(ok_response_html().as_bytes());
+        }
+    });
+    let ws = WebsiteStatus::request_with(&v6_url, &v4_only);
+    assert!(ws.validation.issues.iter().any(|i| i.contains("no IPv4 address")), "{:?}", ws.validation.issues);
+    assert!(matches!(WebsiteStatus::request_with(&v6_url, &v6_only).status, CheckStatus::Success(200)));
+    server.join().unwrap();
+}
+
+#[test]
+fn redirect_chain_records_each_hop() {
+    let redirect = |code: &str, to: &str| {
+        format!("HTTP/1.1 {}\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", code, to)
+    };
+    let login_page = "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 27\r\nConnection: close\r\n\r\n<form>Please log in</form>\n";
+    let (url, handle) = start_sequence_server(vec![
+        redirect("302 Found", "/sso"),
+        redirect("301 Moved Permanently", "login"),
+        login_page.to_string(),
+    ]);
+
+    let cfg = Config::builder()
+        .https_required(false)
+        .fetch_timestamp(false)
+        .body_must_contain("log in")
+        .build();
+    let ws = WebsiteStatus::request_with(&format!("{}/account", url), &cfg);
+    handle.join().unwrap();
+
+    // The body rules ran against the final (login) page
+    assert!(matches!(ws.status, CheckStatus::Success(200)), "got {:?}", ws.status);
+    assert!(ws.validation.body_ok, "{:?}", ws.validation.issues);
+    assert_eq!(
+        ws.redirect_chain,
+        vec![(302, format!("{}/account", url)), (301, format!("{}/sso", url))]
+    );
+    assert!(ws.to_string().contains(&format!("Redirects: 302 {}/account -> 301 {}/sso", url, url)));
+}
+
+/// Start a one-shot SOCKS5 "proxy" that completes the handshake, then answers the
+/// tunneled HTTP request itself with `response`. Returns the requested "host:port".
+#[cfg(feature = "socks")]
+fn start_socks5_server(response: &'static str) -> (String, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind ephemeral port");
+    let proxy = format!("socks5://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let (mut stream, _peer) = listener.accept().unwrap();
+        // Greeting: version, method count, methods -> pick "no authentication"
+        let mut head = [0u8; 2];
+        stream.read_exact(&mut head).unwrap();
+        let mut methods = vec![0u8; head[1] as usize];
+        stream.read_exact(&mut methods).unwrap();
+        // ureq runs the handshake on a helper thread and can miss a reply that comes back
+        // before it starts waiting (it then sits out the whole timeout), so answer a bit late
+        thread::sleep(Duration::from_millis(200));
+        stream.write_all(&[5, 0]).unwrap();
+
+        // CONNECT request: version, command, reserved, address type, address, port
+        let mut req = [0u8; 4];
+        stream.read_exact(&mut req).unwrap();
+        let host = match req[3] {
+            1 => {
+                let mut ip = [0u8; 4];
+                stream.read_exact(&mut ip).unwrap();
+                std::net::Ipv4Addr::from(ip).to_string()
+            }
+            3 => {
+                let mut len = [0u8; 1];
+                stream.read_exact(&mut len).unwrap();
+                let mut name = vec![0u8; len[0] as usize];
+                stream.read_exact(&mut name).unwrap();
+                String::from_utf8_lossy(&name).into_owned()
+            }
+            other => panic!("unexpected address type {}", other),
+        };
+        let mut port = [0u8; 2];
+        stream.read_exact(&mut port).unwrap();
+        stream.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0]).unwrap();
+
+        // Now "tunneled": serve the HTTP request directly
+        read_request(&mut stream);
+        stream.write_all(response.as_bytes()).unwrap();
+        format!("{}:{}", host, u16::from_be_bytes(port))
+    });
+    (proxy, handle)
+}
+
+#[cfg(feature = "socks")]
+#[test]
+fn socks5_proxy_carries_the_request() {
+    let (proxy, handle) = start_socks5_server(ok_response_html());
+    let cfg = Config::builder()
+        .https_required(false)
+        .fetch_timestamp(false)
+        .socks_proxy(proxy)
+        .build();
+    // Nothing listens on the target port: the check only works through the tunnel
+    let ws = WebsiteStatus::request_with("http://internal.test:8080/", &cfg);
+    assert_eq!(handle.join().unwrap(), "internal.test:8080");
+    assert!(matches!(ws.status, CheckStatus::Success(200)), "got {:?}", ws.status);
+}
+
+#[test]
+fn unusable_socks_proxy_falls_back_to_direct() {
+    let (url, handle) = start_mock_server(ok_response_html(), None);
+    // Without the `socks` feature no SOCKS URL is usable; with it, this one is still the wrong scheme
+    let proxy = if cfg!(feature = "socks") { "http://127.0.0.1:1" } else { "socks5://127.0.0.1:1" };
+    let cfg = Config {
+        socks_proxy: Some(proxy.into()),
+        ..cfg_no_https()
+    };
+    let ws = WebsiteStatus::request_with(&url, &cfg);
+    handle.join().unwrap();
+    assert!(matches!(ws.status, CheckStatus::Success(200)));
+    assert!(ws.validation.issues.iter().any(|i| i.contains("unusable SOCKS proxy")), "{:?}", ws.validation.issues);
+}
+
+#[test]
+fn trace_timing_splits_first_byte_from_completion() {
+    // Headers right away, the body 300ms later
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        read_request(&mut stream);
+        stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n\r\n").unwrap();
+        stream.flush().unwrap();
+        thread::sleep(Duration::from_millis(300));
+        let _ = stream.write_all(b"hello");
+    });
+
+    let cfg = Config { fetch_timestamp: false, trace_timing: true, ..cfg_no_https() };
+    let ws = WebsiteStatus::request_with(&url, &cfg);
+    handle.join().unwrap();
+
+    assert!(matches!(ws.status, CheckStatus::Success(200)), "{:?}", ws.status);
+    let t = ws.validation.timing.expect("timing recorded");
+    assert!(t.connect_start_ms <= t.ttfb_ms);
+    assert!(t.ttfb_ms < t.total_ms, "{:?}", t);
+    assert!(t.total_ms - t.ttfb_ms >= 250, "body delay not in the trace: {:?}", t);
+    assert!(ws.to_string().contains("Timing: connect +"));
+
+    // Off by default
+    let (url, handle) = start_mock_server(ok_response_html(), None);
+    let ws = WebsiteStatus::request_with(&url, &cfg_no_https());
+    handle.join().unwrap();
+    assert!(ws.validation.timing.is_none());
+}
+
+#[test]
+fn etag_turns_the_second_check_into_a_304() {
+    // Answers 304 when the request carries the current ETag, otherwise the full page with it
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let mut requests = Vec::new();
+        for _ in 0..2 {
+            let (mut stream, _) = listener.accept().unwrap();
+            let raw = String::from_utf8_lossy(&read_request(&mut stream)).into_owned();
+            let response = if raw.to_ascii_lowercase().contains("if-none-match: \"v1\"") {
+                "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n"
+            } else {
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nETag: \"v1\"\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello"
+            };
+            stream.write_all(response.as_bytes()).unwrap();
+            requests.push(raw);
+        }
+        requests
+    });
+
+    let path = std::env::temp_dir().join(format!("wc_mock_etags_{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let store = EtagStore::load(&path).unwrap().shared();
+    let mut cfg = Config::builder().etags(store.clone()).build();
+    cfg.https_required = false;
+    cfg.fetch_timestamp = false;
+    cfg.body_contains_all = vec!["hello".into()];
+
+    // First check: full page, ETag recorded
+    let first = WebsiteStatus::request_with(&url, &cfg);
+    assert!(matches!(first.status, CheckStatus::Success(200)), "{:?}", first.status);
+    assert!(!first.status.is_unchanged());
+    assert_eq!(store.lock().unwrap().get(&url, "GET"), Some("\"v1\""));
+
+    // Second check: conditional request, 304 counts as an unchanged success
+    let second = WebsiteStatus::request_with(&url, &cfg);
+    assert!(matches!(second.status, CheckStatus::Unchanged), "{:?}", second.status);
+    assert!(second.status.is_success());
+    assert!(second.healthy, "{:?}", second.validation.issues);
+    assert!(second.validation.body_ok, "no body to check on a 304");
+    assert!(second.to_string().contains("Status: 304 (unchanged)"));
+    assert_eq!((second.kind_str(), second.http_code(), second.as_success_code()), ("unchanged", Some(304), None));
+
+    let requests = handle.join().unwrap();
+    assert!(!requests[0].to_ascii_lowercase().contains("if-none-match"));
+    assert!(requests[1].to_ascii_lowercase().contains("if-none-match: \"v1\""));
+}
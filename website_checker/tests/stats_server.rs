@@ -0,0 +1,75 @@
+// tests/stats_server.rs
+//! Integration test for the `--serve` stats endpoint.
+
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use website_checker::stats::Stats;
+use website_checker::stats_server::{serve, SharedStats, CLIENT_READ_TIMEOUT};
+
+#[test]
+fn serves_latest_stats_as_json_and_metrics() {
+    let shared: SharedStats = Arc::new(Mutex::new(None));
+    let (addr, _handle) = serve("127.0.0.1:0", Arc::clone(&shared)).expect("bind stats server");
+    let base = format!("http://{}", addr);
+
+    // Before the first batch there is nothing to report
+    match ureq::get(&format!("{}/stats", base)).call() {
+        Err(ureq::Error::Status(503, _)) => {}
+        other => panic!("expected 503 before the first batch, got {:?}", other.map(|r| r.status())),
+    }
+
+    // The main loop publishes a batch summary
+    *shared.lock().unwrap() = Some(Stats {
+        total: 4,
+        successes: 3,
+        http_errors: 1,
+        transport_errors: 0,
+        validation_failures: 1,
+        fully_healthy: 2,
+        avg_response_ms: 120.5,
+        uptime_pct: 75.0,
+        retried_count: 0,
+        unchanged: 0,
+    });
+
+    let json: serde_json::Value = ureq::get(&format!("{}/stats", base))
+        .call()
+        .expect("GET /stats")
+        .into_json()
+        .expect("JSON body");
+    assert_eq!(json["total"], 4);
+    assert_eq!(json["uptime_pct"], 75.0);
+
+    let metrics = ureq::get(&format!("{}/metrics", base))
+        .call()
+        .expect("GET /metrics")
+        .into_string()
+        .unwrap();
+    assert!(metrics.contains("# TYPE website_checker_uptime_pct gauge"));
+    assert!(metrics.contains("website_checker_successes 3\n"));
+    assert!(metrics.contains("website_checker_avg_response_ms 120.5\n"));
+
+    assert!(matches!(
+        ureq::get(&format!("{}/nope", base)).call(),
+        Err(ureq::Error::Status(404, _))
+    ));
+}
+
+#[test]
+fn idle_client_does_not_block_the_server() {
+    let shared: SharedStats = Arc::new(Mutex::new(None));
+    let (addr, _handle) = serve("127.0.0.1:0", shared).expect("bind stats server");
+
+    // Connects but never sends a request
+    let _idle = TcpStream::connect(addr).unwrap();
+
+    // The next client is answered once the idle one times out
+    let started = Instant::now();
+    match ureq::get(&format!("http://{}/stats", addr)).call() {
+        Err(ureq::Error::Status(503, _)) => {}
+        other => panic!("expected 503, got {:?}", other.map(|r| r.status())),
+    }
+    assert!(started.elapsed() < CLIENT_READ_TIMEOUT * 2, "took {:?}", started.elapsed());
+}
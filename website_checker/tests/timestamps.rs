@@ -0,0 +1,61 @@
+// tests/timestamps.rs
+//! Checks with `fetch_timestamp = false` must not touch the network time API.
+//! Kept in its own test binary so no other test bumps the call counter meanwhile.
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use website_checker::concurrent::{check_many_with, BatchConfig};
+use website_checker::status::{CheckStatus, WebsiteStatus};
+use website_checker::time_utils::network_time_calls;
+use website_checker::validation::Config;
+
+/// Helper: a local server answering `conns` connections with a small 200 page.
+fn start_ok_server(conns: usize) -> (String, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind ephemeral port");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        for _ in 0..conns {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                );
+            }
+        }
+    });
+    (url, handle)
+}
+
+#[test]
+fn disabled_timestamp_fetch_uses_local_clock() {
+    let (url, server) = start_ok_server(3);
+    let cfg = Config::builder()
+        .https_required(false)
+        .fetch_timestamp(false)
+        .build();
+
+    let single = WebsiteStatus::request_with(&url, &cfg);
+    let batch = check_many_with(
+        vec![url.clone(), url],
+        &BatchConfig {
+            workers: 2,
+            cfg,
+            ..BatchConfig::default()
+        },
+    );
+    server.join().unwrap();
+
+    assert_eq!(network_time_calls(), 0, "no network time call expected");
+    for ws in std::iter::once(&single).chain(&batch) {
+        assert!(matches!(ws.status, CheckStatus::Success(200)));
+        // RFC 3339 from the local clock, e.g. "2026-10-16T08:00:00Z"
+        let ts = &ws.timestamp_utc;
+        assert_eq!(ts.len(), 20, "unexpected timestamp {}", ts);
+        assert_eq!(&ts[4..5], "-");
+        assert_eq!(&ts[10..11], "T");
+        assert!(ts.ends_with('Z'));
+    }
+}
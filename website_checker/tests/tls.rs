@@ -0,0 +1,212 @@
+// tests/tls.rs
+//! TLS integration tests against a local rustls server with a self-signed certificate.
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread;
+
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use website_checker::status::{CheckStatus, WebsiteStatus};
+use website_checker::tls::{client_config_with_roots, load_client_identity, CapturingTlsConnector};
+use website_checker::validation::Config;
+
+/// Self-signed certificate for "localhost" (DER cert, DER key).
+fn self_signed() -> (CertificateDer<'static>, PrivateKeyDer<'static>) {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let key = PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der());
+    (cert.cert.der().clone(), key.into())
+}
+
+/// Start a one-shot HTTPS server answering a small 200 page.
+fn start_tls_server(
+    cert: CertificateDer<'static>,
+    key: PrivateKeyDer<'static>,
+) -> (String, thread::JoinHandle<()>) {
+    let config = rustls::ServerConfig::builder_with_provider(
+        rustls::crypto::ring::default_provider().into(),
+    )
+    .with_safe_default_protocol_versions()
+    .unwrap()
+    .with_no_client_auth()
+    .with_single_cert(vec![cert], key)
+    .unwrap();
+    let config = Arc::new(config);
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind ephemeral port");
+    let url = format!("https://localhost:{}/", listener.local_addr().unwrap().port());
+
+    let handle = thread::spawn(move || {
+        if let Ok((stream, _)) = listener.accept() {
+            let conn = rustls::ServerConnection::new(config).unwrap();
+            let mut tls = rustls::StreamOwned::new(conn, stream);
+            let mut buf = [0u8; 1024];
+            if tls.read(&mut buf).is_ok() {
+                let _ = tls.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                );
+                let _ = tls.flush();
+            }
+        }
+    });
+    (url, handle)
+}
+
+#[test]
+fn connector_reports_negotiated_version_and_cipher() {
+    let (cert, key) = self_signed();
+
+    // Client trusting only our self-signed certificate
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add(cert.clone()).unwrap();
+    let client = rustls::ClientConfig::builder_with_provider(
+        rustls::crypto::ring::default_provider().into(),
+    )
+    .with_safe_default_protocol_versions()
+    .unwrap()
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+
+    let connector = Arc::new(CapturingTlsConnector::new(Arc::new(client)));
+    assert_eq!(connector.last_info(), None, "nothing negotiated yet");
+
+    let (url, handle) = start_tls_server(cert, key);
+    let agent = ureq::AgentBuilder::new()
+        .tls_connector(Arc::clone(&connector))
+        .build();
+    let resp = agent.get(&url).call().expect("TLS request should succeed");
+    assert_eq!(resp.status(), 200);
+    handle.join().unwrap();
+
+    let info = connector.last_info().expect("handshake info captured");
+    assert_eq!(info.version, "TLS 1.3");
+    assert!(info.cipher.starts_with("TLS13_"), "cipher was {}", info.cipher);
+}
+
+/// A CA plus a client certificate it signed, as PEM: (ca_der, client_cert_pem, client_key_pem).
+fn client_identity_pem() -> (CertificateDer<'static>, String, String) {
+    let ca_key = rcgen::KeyPair::generate().unwrap();
+    let mut ca_params = rcgen::CertificateParams::new(Vec::<String>::new()).unwrap();
+    ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    let ca = ca_params.self_signed(&ca_key).unwrap();
+
+    let client_key = rcgen::KeyPair::generate().unwrap();
+    let client = rcgen::CertificateParams::new(vec!["checker.local".to_string()])
+        .unwrap()
+        .signed_by(&client_key, &ca, &ca_key)
+        .unwrap();
+    (ca.der().clone(), client.pem(), client_key.serialize_pem())
+}
+
+/// Start an HTTPS server that requires a client certificate signed by `client_ca`.
+/// Answers up to `conns` connections; handshakes without a valid client cert fail.
+fn start_mtls_server(
+    cert: CertificateDer<'static>,
+    key: PrivateKeyDer<'static>,
+    client_ca: CertificateDer<'static>,
+    conns: usize,
+) -> (String, thread::JoinHandle<()>) {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut client_roots = rustls::RootCertStore::empty();
+    client_roots.add(client_ca).unwrap();
+    let verifier = rustls::server::WebPkiClientVerifier::builder_with_provider(Arc::new(client_roots), Arc::clone(&provider))
+        .build()
+        .unwrap();
+    let config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(vec![cert], key)
+        .unwrap();
+    let config = Arc::new(config);
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind ephemeral port");
+    let url = format!("https://localhost:{}/", listener.local_addr().unwrap().port());
+    let handle = thread::spawn(move || {
+        for _ in 0..conns {
+            let Ok((stream, _)) = listener.accept() else {
+                return;
+            };
+            let conn = rustls::ServerConnection::new(Arc::clone(&config)).unwrap();
+            let mut tls = rustls::StreamOwned::new(conn, stream);
+            let mut buf = [0u8; 1024];
+            if tls.read(&mut buf).is_ok() {
+                let _ = tls.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                );
+                let _ = tls.flush();
+            }
+        }
+    });
+    (url, handle)
+}
+
+#[test]
+fn client_certificate_authenticates_to_mtls_server() {
+    let (server_cert, server_key) = self_signed();
+    let (client_ca, cert_pem, key_pem) = client_identity_pem();
+
+    let dir = std::env::temp_dir();
+    let cert_path = dir.join(format!("wc_client_cert_{}.pem", std::process::id()));
+    let key_path = dir.join(format!("wc_client_key_{}.pem", std::process::id()));
+    std::fs::write(&cert_path, cert_pem).unwrap();
+    std::fs::write(&key_path, key_pem).unwrap();
+    let identity = load_client_identity(&cert_path, &key_path);
+    let _ = std::fs::remove_file(&cert_path);
+    let _ = std::fs::remove_file(&key_path);
+    let identity = identity.expect("PEM identity loads");
+
+    let (url, handle) = start_mtls_server(server_cert.clone(), server_key, client_ca, 2);
+    let agent_for = |identity| {
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(server_cert.clone()).unwrap();
+        let client = client_config_with_roots(roots, identity).unwrap();
+        ureq::AgentBuilder::new()
+            .tls_connector(Arc::new(CapturingTlsConnector::new(Arc::new(client))))
+            .build()
+    };
+
+    // With the client certificate the server answers
+    let resp = agent_for(Some(identity)).get(&url).call().expect("mTLS request should succeed");
+    assert_eq!(resp.status(), 200);
+
+    // Without it the server refuses the connection
+    assert!(agent_for(None).get(&url).call().is_err());
+    handle.join().unwrap();
+}
+
+#[test]
+fn unreadable_client_certificate_fails_the_check() {
+    let missing = std::env::temp_dir().join(format!("wc_missing_cert_{}.pem", std::process::id()));
+    let cfg = Config::builder().client_cert(&missing, &missing).build();
+
+    // Fails before any connection is attempted (nothing listens on this port)
+    let ws = WebsiteStatus::request_with("https://localhost:9/", &Config { fetch_timestamp: false, ..cfg });
+    match &ws.status {
+        CheckStatus::Transport(e) => assert!(e.contains("failed to load client certificate"), "got {}", e),
+        other => panic!("expected a transport error, got {:?}", other),
+    }
+    assert!(ws.validation.issues.iter().any(|i| i.starts_with("Client certificate error:")));
+    assert!(!ws.healthy);
+}
+
+#[test]
+fn self_signed_certificate_needs_accept_invalid_certs() {
+    let (cert, key) = self_signed();
+    let (url, handle) = start_tls_server(cert.clone(), key.clone_key());
+
+    // Verification on (default): the self-signed certificate is rejected
+    let strict = Config { fetch_timestamp: false, ..Config::default() };
+    let ws = WebsiteStatus::request_with(&url, &strict);
+    assert!(matches!(ws.status, CheckStatus::Transport(_)), "got {:?}", ws.status);
+    handle.join().unwrap();
+
+    // Verification off: the check succeeds, with a warning in the report
+    let (url, handle) = start_tls_server(cert, key);
+    let insecure = Config { fetch_timestamp: false, ..Config::builder().accept_invalid_certs(true).build() };
+    let ws = WebsiteStatus::request_with(&url, &insecure);
+    handle.join().unwrap();
+    assert!(matches!(ws.status, CheckStatus::Success(200)), "got {:?}", ws.status);
+    assert!(ws.tls_version.is_some());
+    assert!(ws.validation.issues.iter().any(|i| i.starts_with("Warning: certificate verification disabled")));
+}